@@ -0,0 +1,173 @@
+-- [Ranking] schema and the stored procedures the bot calls.
+-- Written to be re-runnable, so it can be applied on top of a database that was set up by hand.
+
+IF SCHEMA_ID(N'Ranking') IS NULL
+    EXEC(N'CREATE SCHEMA [Ranking]');
+GO
+
+IF OBJECT_ID(N'[Ranking].[Server]', N'U') IS NULL
+CREATE TABLE [Ranking].[Server] (
+    id DECIMAL(20, 0) NOT NULL PRIMARY KEY,
+    -- Milliseconds between messages that give experience.
+    timeout INT NOT NULL DEFAULT 60000
+);
+GO
+
+IF OBJECT_ID(N'[Ranking].[Level]', N'U') IS NULL
+CREATE TABLE [Ranking].[Level] (
+    server_id DECIMAL(20, 0) NOT NULL,
+    [user_id] DECIMAL(20, 0) NOT NULL,
+    xp INT NOT NULL DEFAULT 0,
+    level INT NOT NULL DEFAULT 1,
+    last_xp DATETIME2 NULL,
+    PRIMARY KEY (server_id, [user_id])
+);
+GO
+
+IF OBJECT_ID(N'[Ranking].[Role]', N'U') IS NULL
+CREATE TABLE [Ranking].[Role] (
+    server_id DECIMAL(20, 0) NOT NULL,
+    role_name NVARCHAR(100) NOT NULL,
+    role_id DECIMAL(20, 0) NULL,
+    min_level INT NOT NULL,
+    PRIMARY KEY (server_id, min_level)
+);
+GO
+
+IF OBJECT_ID(N'[Ranking].[DisabledChannel]', N'U') IS NULL
+CREATE TABLE [Ranking].[DisabledChannel] (
+    server_id DECIMAL(20, 0) NOT NULL,
+    channel_id DECIMAL(20, 0) NOT NULL,
+    PRIMARY KEY (server_id, channel_id)
+);
+GO
+
+CREATE OR ALTER FUNCTION [Ranking].[ExperienceForLevel] (@level INT)
+RETURNS INT
+AS
+BEGIN
+    RETURN 5 * @level * @level + 50 * @level + 100;
+END
+GO
+
+CREATE OR ALTER PROCEDURE [Ranking].[CalculateLevel] @level INT
+AS
+BEGIN
+    SET NOCOUNT ON;
+    SELECT [Ranking].[ExperienceForLevel](@level);
+END
+GO
+
+-- Returns (level, old rank, new rank); the level is -1 if the user did not level up.
+CREATE OR ALTER PROCEDURE [Ranking].[ProvideExp] @serverid DECIMAL(20, 0), @userid DECIMAL(20, 0)
+AS
+BEGIN
+    SET NOCOUNT ON;
+    DECLARE @timeout INT, @last_xp DATETIME2, @xp INT, @level INT, @old_level INT;
+    DECLARE @old_rank DECIMAL(20, 0), @new_rank DECIMAL(20, 0);
+
+    IF NOT EXISTS (SELECT 1 FROM [Ranking].[Server] WHERE id = @serverid)
+        INSERT INTO [Ranking].[Server] (id) VALUES (@serverid);
+    IF NOT EXISTS (SELECT 1 FROM [Ranking].[Level] WHERE server_id = @serverid AND [user_id] = @userid)
+        INSERT INTO [Ranking].[Level] (server_id, [user_id]) VALUES (@serverid, @userid);
+
+    SELECT @timeout = timeout FROM [Ranking].[Server] WHERE id = @serverid;
+    SELECT @xp = xp, @level = level, @last_xp = last_xp FROM [Ranking].[Level] WHERE server_id = @serverid AND [user_id] = @userid;
+
+    IF @last_xp IS NOT NULL AND DATEDIFF_BIG(MILLISECOND, @last_xp, SYSUTCDATETIME()) < @timeout
+    BEGIN
+        SELECT CAST(-1 AS INT), CAST(NULL AS DECIMAL(20, 0)), CAST(NULL AS DECIMAL(20, 0));
+        RETURN;
+    END
+
+    SET @old_level = @level;
+    SET @xp = @xp + 15 + ABS(CHECKSUM(NEWID())) % 11;
+    WHILE @xp >= [Ranking].[ExperienceForLevel](@level)
+    BEGIN
+        SET @xp = @xp - [Ranking].[ExperienceForLevel](@level);
+        SET @level = @level + 1;
+    END
+
+    UPDATE [Ranking].[Level] SET xp = @xp, level = @level, last_xp = SYSUTCDATETIME() WHERE server_id = @serverid AND [user_id] = @userid;
+
+    IF @level = @old_level
+    BEGIN
+        SELECT CAST(-1 AS INT), CAST(NULL AS DECIMAL(20, 0)), CAST(NULL AS DECIMAL(20, 0));
+        RETURN;
+    END
+
+    SELECT TOP 1 @old_rank = role_id FROM [Ranking].[Role] WHERE server_id = @serverid AND min_level <= @old_level ORDER BY min_level DESC;
+    SELECT TOP 1 @new_rank = role_id FROM [Ranking].[Role] WHERE server_id = @serverid AND min_level <= @level ORDER BY min_level DESC;
+
+    IF (@old_rank = @new_rank) OR (@old_rank IS NULL AND @new_rank IS NULL)
+        SELECT @level, CAST(NULL AS DECIMAL(20, 0)), CAST(NULL AS DECIMAL(20, 0));
+    ELSE
+        SELECT @level, @old_rank, @new_rank;
+END
+GO
+
+-- Returns 1 if the channel is now disabled.
+CREATE OR ALTER PROCEDURE [Ranking].[ToggleChannel] @serverid DECIMAL(20, 0), @channelid DECIMAL(20, 0)
+AS
+BEGIN
+    SET NOCOUNT ON;
+    DELETE FROM [Ranking].[DisabledChannel] WHERE server_id = @serverid AND channel_id = @channelid;
+
+    IF @@ROWCOUNT > 0
+        SELECT CAST(0 AS BIT);
+    ELSE
+    BEGIN
+        INSERT INTO [Ranking].[DisabledChannel] (server_id, channel_id) VALUES (@serverid, @channelid);
+        SELECT CAST(1 AS BIT);
+    END
+END
+GO
+
+-- Returns 0 if there is already a rank at this level; re-adding a role moves it to the new level.
+CREATE OR ALTER PROCEDURE [Ranking].[AddRole] @server_id DECIMAL(20, 0), @role_name NVARCHAR(100), @role_id DECIMAL(20, 0), @min_level INT
+AS
+BEGIN
+    SET NOCOUNT ON;
+    IF EXISTS (SELECT 1 FROM [Ranking].[Role] WHERE server_id = @server_id AND min_level = @min_level)
+    BEGIN
+        SELECT CAST(0 AS BIT);
+        RETURN;
+    END
+
+    DELETE FROM [Ranking].[Role] WHERE server_id = @server_id AND role_id = @role_id;
+    INSERT INTO [Ranking].[Role] (server_id, role_name, role_id, min_level) VALUES (@server_id, @role_name, @role_id, @min_level);
+    SELECT CAST(1 AS BIT);
+END
+GO
+
+CREATE OR ALTER PROCEDURE [Ranking].[RemoveRole] @serverid DECIMAL(20, 0), @roleid DECIMAL(20, 0)
+AS
+BEGIN
+    SET NOCOUNT ON;
+    DELETE FROM [Ranking].[Role] WHERE server_id = @serverid AND role_id = @roleid;
+    SELECT CAST(CASE WHEN @@ROWCOUNT > 0 THEN 1 ELSE 0 END AS BIT);
+END
+GO
+
+CREATE OR ALTER PROCEDURE [Ranking].[SetServerTimeout] @serverid DECIMAL(20, 0), @timeout INT
+AS
+BEGIN
+    SET NOCOUNT ON;
+    UPDATE [Ranking].[Server] SET timeout = @timeout WHERE id = @serverid;
+    IF @@ROWCOUNT = 0
+        INSERT INTO [Ranking].[Server] (id, timeout) VALUES (@serverid, @timeout);
+    SELECT CAST(1 AS BIT);
+END
+GO
+
+-- Every ranked user, with the role they should have for their level.
+CREATE OR ALTER PROCEDURE [Ranking].[GetAllUsers] @serverid DECIMAL(20, 0)
+AS
+BEGIN
+    SET NOCOUNT ON;
+    SELECT l.[user_id], l.level, l.xp,
+        (SELECT TOP 1 r.role_id FROM [Ranking].[Role] r WHERE r.server_id = l.server_id AND r.min_level <= l.level ORDER BY r.min_level DESC)
+    FROM [Ranking].[Level] l
+    WHERE l.server_id = @serverid;
+END
+GO
@@ -0,0 +1,119 @@
+-- [UniScraper].[UCM] lives in its own database, which the scraper fills in; the bot only owns the reminders.
+-- CREATE DATABASE and full-text indexes can't run inside a transaction, so every step here checks before it creates.
+
+IF DB_ID(N'UniScraper') IS NULL
+    CREATE DATABASE [UniScraper];
+GO
+
+USE [UniScraper];
+GO
+
+IF SCHEMA_ID(N'UCM') IS NULL
+    EXEC(N'CREATE SCHEMA [UCM]');
+GO
+
+IF OBJECT_ID(N'[UCM].[class]', N'U') IS NULL
+CREATE TABLE [UCM].[class] (
+    id INT NOT NULL IDENTITY CONSTRAINT PK_class PRIMARY KEY,
+    term INT NOT NULL,
+    course_reference_number INT NOT NULL UNIQUE,
+    course_number NVARCHAR(32) NOT NULL,
+    campus_description NVARCHAR(64) NULL,
+    course_title NVARCHAR(256) NULL,
+    credit_hours TINYINT NOT NULL,
+    maximum_enrollment SMALLINT NOT NULL,
+    enrollment SMALLINT NOT NULL,
+    seats_available SMALLINT NOT NULL,
+    wait_capacity SMALLINT NOT NULL,
+    wait_available SMALLINT NOT NULL
+);
+GO
+
+IF OBJECT_ID(N'[UCM].[professor]', N'U') IS NULL
+CREATE TABLE [UCM].[professor] (
+    id INT NOT NULL IDENTITY CONSTRAINT PK_professor PRIMARY KEY,
+    rmp_id INT NULL,
+    last_name NVARCHAR(64) NOT NULL,
+    first_name NVARCHAR(64) NOT NULL,
+    middle_name NVARCHAR(64) NULL,
+    email NVARCHAR(128) NULL,
+    department NVARCHAR(128) NULL,
+    num_ratings INT NOT NULL DEFAULT 0,
+    rating REAL NOT NULL DEFAULT 0,
+    full_name NVARCHAR(192) NOT NULL
+);
+GO
+
+IF OBJECT_ID(N'[UCM].[faculty]', N'U') IS NULL
+CREATE TABLE [UCM].[faculty] (
+    professor_id INT NOT NULL REFERENCES [UCM].[professor] (id),
+    class_id INT NOT NULL REFERENCES [UCM].[class] (id),
+    PRIMARY KEY (professor_id, class_id)
+);
+GO
+
+IF OBJECT_ID(N'[UCM].[meeting]', N'U') IS NULL
+CREATE TABLE [UCM].[meeting] (
+    class_id INT NOT NULL REFERENCES [UCM].[class] (id),
+    begin_time NVARCHAR(4) NULL,
+    end_time NVARCHAR(4) NULL,
+    begin_date NVARCHAR(16) NOT NULL,
+    end_date NVARCHAR(16) NOT NULL,
+    building NVARCHAR(16) NULL,
+    building_description NVARCHAR(128) NULL,
+    campus NVARCHAR(16) NULL,
+    campus_description NVARCHAR(64) NULL,
+    room NVARCHAR(16) NULL,
+    credit_hour_session REAL NOT NULL,
+    hours_per_week REAL NOT NULL,
+    in_session TINYINT NOT NULL,
+    meeting_type TINYINT NOT NULL
+);
+GO
+
+IF OBJECT_ID(N'[UCM].[stats]', N'U') IS NULL
+CREATE TABLE [UCM].[stats] (
+    table_name NVARCHAR(64) NOT NULL PRIMARY KEY,
+    last_update DATETIME NOT NULL
+);
+GO
+
+IF OBJECT_ID(N'[UCM].[reminder]', N'U') IS NULL
+CREATE TABLE [UCM].[reminder] (
+    [user_id] DECIMAL(20, 0) NOT NULL,
+    course_reference_number INT NOT NULL,
+    min_trigger INT NOT NULL DEFAULT 1,
+    for_waitlist BIT NOT NULL DEFAULT 0,
+    triggered BIT NOT NULL DEFAULT 0,
+    PRIMARY KEY ([user_id], course_reference_number)
+);
+GO
+
+-- The course and professor searches use CONTAINS.
+IF NOT EXISTS (SELECT 1 FROM sys.fulltext_catalogs WHERE name = N'UCMCatalog')
+    CREATE FULLTEXT CATALOG UCMCatalog;
+GO
+
+IF NOT EXISTS (SELECT 1 FROM sys.fulltext_indexes WHERE object_id = OBJECT_ID(N'[UCM].[class]'))
+    CREATE FULLTEXT INDEX ON [UCM].[class] (course_number, course_title) KEY INDEX PK_class ON UCMCatalog;
+GO
+
+IF NOT EXISTS (SELECT 1 FROM sys.fulltext_indexes WHERE object_id = OBJECT_ID(N'[UCM].[professor]'))
+    CREATE FULLTEXT INDEX ON [UCM].[professor] (full_name) KEY INDEX PK_professor ON UCMCatalog;
+GO
+
+-- Marks reminders with enough open seats as triggered, and returns them.
+CREATE OR ALTER PROCEDURE [UCM].[TriggerReminders]
+AS
+BEGIN
+    SET NOCOUNT ON;
+    UPDATE r
+    SET triggered = 1
+    OUTPUT inserted.[user_id], inserted.course_reference_number, inserted.min_trigger
+    FROM [UCM].[reminder] r
+    INNER JOIN [UCM].[class] c ON c.course_reference_number = r.course_reference_number
+    WHERE r.triggered = 0
+        AND ((r.for_waitlist = 0 AND c.seats_available >= r.min_trigger)
+            OR (r.for_waitlist = 1 AND c.wait_available >= r.min_trigger));
+END
+GO
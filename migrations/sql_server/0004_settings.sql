@@ -0,0 +1,28 @@
+-- [Settings] schema, for per-guild key/value settings.
+
+IF SCHEMA_ID(N'Settings') IS NULL
+    EXEC(N'CREATE SCHEMA [Settings]');
+GO
+
+IF OBJECT_ID(N'[Settings].[Guild]', N'U') IS NULL
+CREATE TABLE [Settings].[Guild] (
+    guild_id DECIMAL(20, 0) NOT NULL,
+    [key] NVARCHAR(64) NOT NULL,
+    value NVARCHAR(2000) NOT NULL,
+    PRIMARY KEY (guild_id, [key])
+);
+GO
+
+CREATE OR ALTER PROCEDURE [Settings].[SetValue] @guild_id DECIMAL(20, 0), @key NVARCHAR(64), @value NVARCHAR(2000)
+AS
+BEGIN
+    SET NOCOUNT ON;
+    UPDATE [Settings].[Guild]
+    SET value = @value
+    WHERE guild_id = @guild_id AND [key] = @key;
+
+    IF @@ROWCOUNT = 0
+        INSERT INTO [Settings].[Guild] (guild_id, [key], value)
+        VALUES (@guild_id, @key, @value);
+END
+GO
@@ -0,0 +1,78 @@
+-- XP gain and the level curve are configurable per server now, so the bot passes them in with each call.
+-- The curve is either a polynomial (up to cubic, @c0 + @c1 * L + @c2 * L^2 + @c3 * L^3) or exponential (@c0 * @c1^L).
+
+CREATE OR ALTER FUNCTION [Ranking].[CurveExperience] (@level INT, @exponential BIT, @c0 FLOAT, @c1 FLOAT, @c2 FLOAT, @c3 FLOAT)
+RETURNS INT
+AS
+BEGIN
+    DECLARE @xp FLOAT, @l FLOAT = @level;
+
+    IF @exponential = 1
+        SET @xp = @c0 * POWER(@c1, @l);
+    ELSE
+        SET @xp = @c0 + @c1 * @l + @c2 * @l * @l + @c3 * @l * @l * @l;
+
+    -- Never less than 1, so levelling up always ends.
+    IF @xp < 1
+        RETURN 1;
+    IF @xp > 2147483647
+        RETURN 2147483647;
+    RETURN CAST(ROUND(@xp, 0) AS INT);
+END
+GO
+
+-- Returns (level, old rank, new rank); the level is -1 if the user did not level up.
+CREATE OR ALTER PROCEDURE [Ranking].[ProvideExp] @serverid DECIMAL(20, 0), @userid DECIMAL(20, 0), @gain INT, @level_cap INT,
+    @exponential BIT, @c0 FLOAT, @c1 FLOAT, @c2 FLOAT, @c3 FLOAT
+AS
+BEGIN
+    SET NOCOUNT ON;
+    DECLARE @timeout INT, @last_xp DATETIME2, @xp INT, @level INT, @old_level INT;
+    DECLARE @old_rank DECIMAL(20, 0), @new_rank DECIMAL(20, 0);
+
+    IF NOT EXISTS (SELECT 1 FROM [Ranking].[Server] WHERE id = @serverid)
+        INSERT INTO [Ranking].[Server] (id) VALUES (@serverid);
+    IF NOT EXISTS (SELECT 1 FROM [Ranking].[Level] WHERE server_id = @serverid AND [user_id] = @userid)
+        INSERT INTO [Ranking].[Level] (server_id, [user_id]) VALUES (@serverid, @userid);
+
+    SELECT @timeout = timeout FROM [Ranking].[Server] WHERE id = @serverid;
+    SELECT @xp = xp, @level = level, @last_xp = last_xp FROM [Ranking].[Level] WHERE server_id = @serverid AND [user_id] = @userid;
+
+    IF @last_xp IS NOT NULL AND DATEDIFF_BIG(MILLISECOND, @last_xp, SYSUTCDATETIME()) < @timeout
+    BEGIN
+        SELECT CAST(-1 AS INT), CAST(NULL AS DECIMAL(20, 0)), CAST(NULL AS DECIMAL(20, 0));
+        RETURN;
+    END
+
+    SET @old_level = @level;
+    SET @xp = @xp + @gain;
+    WHILE (@level_cap IS NULL OR @level < @level_cap) AND @xp >= [Ranking].[CurveExperience](@level, @exponential, @c0, @c1, @c2, @c3)
+    BEGIN
+        SET @xp = @xp - [Ranking].[CurveExperience](@level, @exponential, @c0, @c1, @c2, @c3);
+        SET @level = @level + 1;
+    END
+
+    UPDATE [Ranking].[Level] SET xp = @xp, level = @level, last_xp = SYSUTCDATETIME() WHERE server_id = @serverid AND [user_id] = @userid;
+
+    IF @level = @old_level
+    BEGIN
+        SELECT CAST(-1 AS INT), CAST(NULL AS DECIMAL(20, 0)), CAST(NULL AS DECIMAL(20, 0));
+        RETURN;
+    END
+
+    SELECT TOP 1 @old_rank = role_id FROM [Ranking].[Role] WHERE server_id = @serverid AND min_level <= @old_level ORDER BY min_level DESC;
+    SELECT TOP 1 @new_rank = role_id FROM [Ranking].[Role] WHERE server_id = @serverid AND min_level <= @level ORDER BY min_level DESC;
+
+    IF (@old_rank = @new_rank) OR (@old_rank IS NULL AND @new_rank IS NULL)
+        SELECT @level, CAST(NULL AS DECIMAL(20, 0)), CAST(NULL AS DECIMAL(20, 0));
+    ELSE
+        SELECT @level, @old_rank, @new_rank;
+END
+GO
+
+-- The bot works these out itself now.
+DROP PROCEDURE IF EXISTS [Ranking].[CalculateLevel];
+GO
+
+DROP FUNCTION IF EXISTS [Ranking].[ExperienceForLevel];
+GO
@@ -0,0 +1,25 @@
+-- Weighted experience for channels and roles. Snowflakes never collide, so channels and roles share a table.
+
+IF OBJECT_ID(N'[Ranking].[Multiplier]', N'U') IS NULL
+CREATE TABLE [Ranking].[Multiplier] (
+    server_id DECIMAL(20, 0) NOT NULL,
+    target_id DECIMAL(20, 0) NOT NULL,
+    is_role BIT NOT NULL,
+    multiplier FLOAT NOT NULL,
+    PRIMARY KEY (server_id, target_id)
+);
+GO
+
+CREATE OR ALTER PROCEDURE [Ranking].[SetMultiplier] @serverid DECIMAL(20, 0), @targetid DECIMAL(20, 0), @isrole BIT, @multiplier FLOAT
+AS
+BEGIN
+    SET NOCOUNT ON;
+    UPDATE [Ranking].[Multiplier]
+    SET is_role = @isrole, multiplier = @multiplier
+    WHERE server_id = @serverid AND target_id = @targetid;
+
+    IF @@ROWCOUNT = 0
+        INSERT INTO [Ranking].[Multiplier] (server_id, target_id, is_role, multiplier)
+        VALUES (@serverid, @targetid, @isrole, @multiplier);
+END
+GO
@@ -0,0 +1,18 @@
+-- Changes admins make to people's experience by hand.
+
+IF OBJECT_ID(N'[Ranking].[Audit]', N'U') IS NULL
+CREATE TABLE [Ranking].[Audit] (
+    id INT IDENTITY(1, 1) NOT NULL PRIMARY KEY,
+    server_id DECIMAL(20, 0) NOT NULL,
+    moderator_id DECIMAL(20, 0) NOT NULL,
+    -- NULL when it applied to everyone.
+    [user_id] DECIMAL(20, 0) NULL,
+    action NVARCHAR(32) NOT NULL,
+    detail NVARCHAR(400) NOT NULL,
+    created_at DATETIME2 NOT NULL DEFAULT SYSUTCDATETIME()
+);
+GO
+
+IF NOT EXISTS (SELECT 1 FROM sys.indexes WHERE name = N'IX_Audit_Server' AND object_id = OBJECT_ID(N'[Ranking].[Audit]'))
+CREATE INDEX IX_Audit_Server ON [Ranking].[Audit] (server_id, created_at DESC);
+GO
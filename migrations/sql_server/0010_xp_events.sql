@@ -0,0 +1,61 @@
+-- Every bit of experience someone earns, with when they earned it, so leaderboards can cover a week or a month
+-- instead of all time. Experience admins hand out or take away by hand isn't earned, so it isn't in here.
+
+IF OBJECT_ID(N'[Ranking].[XpEvent]', N'U') IS NULL
+CREATE TABLE [Ranking].[XpEvent] (
+    id BIGINT IDENTITY(1, 1) NOT NULL PRIMARY KEY,
+    server_id DECIMAL(20, 0) NOT NULL,
+    [user_id] DECIMAL(20, 0) NOT NULL,
+    gain INT NOT NULL,
+    created_at DATETIME2 NOT NULL DEFAULT SYSUTCDATETIME()
+);
+GO
+
+IF NOT EXISTS (SELECT 1 FROM sys.indexes WHERE name = N'IX_XpEvent_Server' AND object_id = OBJECT_ID(N'[Ranking].[XpEvent]'))
+CREATE INDEX IX_XpEvent_Server ON [Ranking].[XpEvent] (server_id, created_at) INCLUDE ([user_id], gain);
+GO
+
+-- Same as before, but the gain is written down too.
+CREATE OR ALTER PROCEDURE [Ranking].[AddExp] @serverid DECIMAL(20, 0), @userid DECIMAL(20, 0), @gain INT, @level_cap INT,
+    @exponential BIT, @c0 FLOAT, @c1 FLOAT, @c2 FLOAT, @c3 FLOAT
+AS
+BEGIN
+    SET NOCOUNT ON;
+    DECLARE @xp INT, @level INT, @old_level INT;
+    DECLARE @old_rank DECIMAL(20, 0), @new_rank DECIMAL(20, 0);
+
+    IF NOT EXISTS (SELECT 1 FROM [Ranking].[Server] WHERE id = @serverid)
+        INSERT INTO [Ranking].[Server] (id) VALUES (@serverid);
+    IF NOT EXISTS (SELECT 1 FROM [Ranking].[Level] WHERE server_id = @serverid AND [user_id] = @userid)
+        INSERT INTO [Ranking].[Level] (server_id, [user_id]) VALUES (@serverid, @userid);
+
+    SELECT @xp = xp, @level = level FROM [Ranking].[Level] WHERE server_id = @serverid AND [user_id] = @userid;
+
+    SET @old_level = @level;
+    SET @xp = @xp + @gain;
+    WHILE (@level_cap IS NULL OR @level < @level_cap) AND @xp >= [Ranking].[CurveExperience](@level, @exponential, @c0, @c1, @c2, @c3)
+    BEGIN
+        SET @xp = @xp - [Ranking].[CurveExperience](@level, @exponential, @c0, @c1, @c2, @c3);
+        SET @level = @level + 1;
+    END
+
+    UPDATE [Ranking].[Level] SET xp = @xp, level = @level WHERE server_id = @serverid AND [user_id] = @userid;
+
+    IF @gain > 0
+        INSERT INTO [Ranking].[XpEvent] (server_id, [user_id], gain) VALUES (@serverid, @userid, @gain);
+
+    IF @level = @old_level
+    BEGIN
+        SELECT CAST(-1 AS INT), CAST(NULL AS DECIMAL(20, 0)), CAST(NULL AS DECIMAL(20, 0));
+        RETURN;
+    END
+
+    SELECT TOP 1 @old_rank = role_id FROM [Ranking].[Role] WHERE server_id = @serverid AND min_level <= @old_level ORDER BY min_level DESC;
+    SELECT TOP 1 @new_rank = role_id FROM [Ranking].[Role] WHERE server_id = @serverid AND min_level <= @level ORDER BY min_level DESC;
+
+    IF (@old_rank = @new_rank) OR (@old_rank IS NULL AND @new_rank IS NULL)
+        SELECT @level, CAST(NULL AS DECIMAL(20, 0)), CAST(NULL AS DECIMAL(20, 0));
+    ELSE
+        SELECT @level, @old_rank, @new_rank;
+END
+GO
@@ -0,0 +1,30 @@
+-- Things besides a rank role that reaching a level can give. Which columns are used depends on the kind:
+-- dm has a message, post has a channel and a message, and boost has a multiplier and how long it lasts.
+
+IF OBJECT_ID(N'[Ranking].[Reward]', N'U') IS NULL
+CREATE TABLE [Ranking].[Reward] (
+    id INT IDENTITY(1, 1) NOT NULL PRIMARY KEY,
+    server_id DECIMAL(20, 0) NOT NULL,
+    min_level INT NOT NULL,
+    kind NVARCHAR(16) NOT NULL,
+    channel_id DECIMAL(20, 0) NULL,
+    message NVARCHAR(1500) NULL,
+    multiplier FLOAT NULL,
+    minutes INT NULL
+);
+GO
+
+IF NOT EXISTS (SELECT 1 FROM sys.indexes WHERE name = N'IX_Reward_Server' AND object_id = OBJECT_ID(N'[Ranking].[Reward]'))
+CREATE INDEX IX_Reward_Server ON [Ranking].[Reward] (server_id, min_level);
+GO
+
+-- Boosts handed out by rewards. Someone only has one at a time; a new one replaces the old.
+IF OBJECT_ID(N'[Ranking].[Boost]', N'U') IS NULL
+CREATE TABLE [Ranking].[Boost] (
+    server_id DECIMAL(20, 0) NOT NULL,
+    [user_id] DECIMAL(20, 0) NOT NULL,
+    multiplier FLOAT NOT NULL,
+    expires_at DATETIME2 NOT NULL,
+    PRIMARY KEY (server_id, [user_id])
+);
+GO
@@ -0,0 +1,28 @@
+-- The role changes made by the last `rankconfig fix` on each server, so it can be undone.
+-- A new fix replaces the old one, and the changes go with it.
+
+IF OBJECT_ID(N'[Ranking].[RoleFix]', N'U') IS NULL
+CREATE TABLE [Ranking].[RoleFix] (
+    id INT IDENTITY(1, 1) NOT NULL PRIMARY KEY,
+    server_id DECIMAL(20, 0) NOT NULL,
+    created_at DATETIME2 NOT NULL
+);
+GO
+
+IF NOT EXISTS (SELECT 1 FROM sys.indexes WHERE name = N'IX_RoleFix_Server' AND object_id = OBJECT_ID(N'[Ranking].[RoleFix]'))
+CREATE INDEX IX_RoleFix_Server ON [Ranking].[RoleFix] (server_id);
+GO
+
+IF OBJECT_ID(N'[Ranking].[RoleFixChange]', N'U') IS NULL
+CREATE TABLE [Ranking].[RoleFixChange] (
+    fix_id INT NOT NULL FOREIGN KEY REFERENCES [Ranking].[RoleFix] (id) ON DELETE CASCADE,
+    [user_id] DECIMAL(20, 0) NOT NULL,
+    role_id DECIMAL(20, 0) NOT NULL,
+    -- 1 if the fix added the role, 0 if it took it away.
+    added BIT NOT NULL
+);
+GO
+
+IF NOT EXISTS (SELECT 1 FROM sys.indexes WHERE name = N'IX_RoleFixChange_Fix' AND object_id = OBJECT_ID(N'[Ranking].[RoleFixChange]'))
+CREATE INDEX IX_RoleFixChange_Fix ON [Ranking].[RoleFixChange] (fix_id);
+GO
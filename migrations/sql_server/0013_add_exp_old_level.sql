@@ -0,0 +1,46 @@
+-- 0010 brought back the three column result from before 0008 while adding the XpEvent insert, which broke reading old_level.
+
+-- Returns (level, old rank, new rank, old level); the level is -1 if the user did not level up.
+CREATE OR ALTER PROCEDURE [Ranking].[AddExp] @serverid DECIMAL(20, 0), @userid DECIMAL(20, 0), @gain INT, @level_cap INT,
+    @exponential BIT, @c0 FLOAT, @c1 FLOAT, @c2 FLOAT, @c3 FLOAT
+AS
+BEGIN
+    SET NOCOUNT ON;
+    DECLARE @xp INT, @level INT, @old_level INT;
+    DECLARE @old_rank DECIMAL(20, 0), @new_rank DECIMAL(20, 0);
+
+    IF NOT EXISTS (SELECT 1 FROM [Ranking].[Server] WHERE id = @serverid)
+        INSERT INTO [Ranking].[Server] (id) VALUES (@serverid);
+    IF NOT EXISTS (SELECT 1 FROM [Ranking].[Level] WHERE server_id = @serverid AND [user_id] = @userid)
+        INSERT INTO [Ranking].[Level] (server_id, [user_id]) VALUES (@serverid, @userid);
+
+    SELECT @xp = xp, @level = level FROM [Ranking].[Level] WHERE server_id = @serverid AND [user_id] = @userid;
+
+    SET @old_level = @level;
+    SET @xp = @xp + @gain;
+    WHILE (@level_cap IS NULL OR @level < @level_cap) AND @xp >= [Ranking].[CurveExperience](@level, @exponential, @c0, @c1, @c2, @c3)
+    BEGIN
+        SET @xp = @xp - [Ranking].[CurveExperience](@level, @exponential, @c0, @c1, @c2, @c3);
+        SET @level = @level + 1;
+    END
+
+    UPDATE [Ranking].[Level] SET xp = @xp, level = @level WHERE server_id = @serverid AND [user_id] = @userid;
+
+    IF @gain > 0
+        INSERT INTO [Ranking].[XpEvent] (server_id, [user_id], gain) VALUES (@serverid, @userid, @gain);
+
+    IF @level = @old_level
+    BEGIN
+        SELECT CAST(-1 AS INT), CAST(NULL AS DECIMAL(20, 0)), CAST(NULL AS DECIMAL(20, 0)), @old_level;
+        RETURN;
+    END
+
+    SELECT TOP 1 @old_rank = role_id FROM [Ranking].[Role] WHERE server_id = @serverid AND min_level <= @old_level ORDER BY min_level DESC;
+    SELECT TOP 1 @new_rank = role_id FROM [Ranking].[Role] WHERE server_id = @serverid AND min_level <= @level ORDER BY min_level DESC;
+
+    IF (@old_rank = @new_rank) OR (@old_rank IS NULL AND @new_rank IS NULL)
+        SELECT @level, CAST(NULL AS DECIMAL(20, 0)), CAST(NULL AS DECIMAL(20, 0)), @old_level;
+    ELSE
+        SELECT @level, @old_rank, @new_rank, @old_level;
+END
+GO
@@ -0,0 +1,34 @@
+-- Mirrors the [Ranking] schema on SQL Server.
+-- Snowflakes are stored as INTEGER (they fit in a signed 64-bit integer).
+
+CREATE TABLE ranking_server (
+    id INTEGER NOT NULL PRIMARY KEY,
+    -- Milliseconds between messages that give experience.
+    timeout INTEGER NOT NULL DEFAULT 60000
+);
+
+CREATE TABLE ranking_level (
+    server_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    xp INTEGER NOT NULL DEFAULT 0,
+    level INTEGER NOT NULL DEFAULT 1,
+    -- Unix time in milliseconds of the last message that gave experience.
+    last_xp INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (server_id, user_id)
+);
+
+CREATE INDEX ix_ranking_level_leaderboard ON ranking_level (server_id, level DESC, xp DESC);
+
+CREATE TABLE ranking_role (
+    server_id INTEGER NOT NULL,
+    role_name TEXT NOT NULL,
+    role_id INTEGER NULL,
+    min_level INTEGER NOT NULL,
+    PRIMARY KEY (server_id, min_level)
+);
+
+CREATE TABLE ranking_disabled_channel (
+    server_id INTEGER NOT NULL,
+    channel_id INTEGER NOT NULL,
+    PRIMARY KEY (server_id, channel_id)
+);
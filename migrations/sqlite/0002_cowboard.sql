@@ -0,0 +1,20 @@
+-- Mirrors the [Cowboard] schema on SQL Server.
+
+CREATE TABLE cowboard_server (
+    id INTEGER NOT NULL PRIMARY KEY,
+    channel INTEGER NULL,
+    add_threshold INTEGER NOT NULL DEFAULT 5,
+    remove_threshold INTEGER NOT NULL DEFAULT 4,
+    emote TEXT NOT NULL DEFAULT '🐮',
+    webhook_id INTEGER NULL,
+    webhook_token TEXT NULL
+);
+
+CREATE TABLE cowboard_message (
+    message_id INTEGER NOT NULL,
+    message_channel_id INTEGER NOT NULL,
+    post_id INTEGER NOT NULL,
+    post_channel_id INTEGER NOT NULL,
+    guild_id INTEGER NOT NULL,
+    PRIMARY KEY (message_id, message_channel_id, guild_id)
+);
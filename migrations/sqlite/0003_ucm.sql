@@ -0,0 +1,67 @@
+-- Mirrors the [UniScraper].[UCM] schema on SQL Server.
+-- The class/professor/meeting tables are filled in by the scraper; the bot only reads them.
+
+CREATE TABLE ucm_class (
+    id INTEGER NOT NULL PRIMARY KEY,
+    term INTEGER NOT NULL,
+    course_reference_number INTEGER NOT NULL UNIQUE,
+    course_number TEXT NOT NULL,
+    campus_description TEXT NULL,
+    course_title TEXT NULL,
+    credit_hours INTEGER NOT NULL,
+    maximum_enrollment INTEGER NOT NULL,
+    enrollment INTEGER NOT NULL,
+    seats_available INTEGER NOT NULL,
+    wait_capacity INTEGER NOT NULL,
+    wait_available INTEGER NOT NULL
+);
+
+CREATE TABLE ucm_professor (
+    id INTEGER NOT NULL PRIMARY KEY,
+    rmp_id INTEGER NULL,
+    last_name TEXT NOT NULL,
+    first_name TEXT NOT NULL,
+    middle_name TEXT NULL,
+    email TEXT NULL,
+    department TEXT NULL,
+    num_ratings INTEGER NOT NULL DEFAULT 0,
+    rating REAL NOT NULL DEFAULT 0,
+    full_name TEXT NOT NULL
+);
+
+CREATE TABLE ucm_faculty (
+    professor_id INTEGER NOT NULL REFERENCES ucm_professor (id),
+    class_id INTEGER NOT NULL REFERENCES ucm_class (id),
+    PRIMARY KEY (professor_id, class_id)
+);
+
+CREATE TABLE ucm_meeting (
+    class_id INTEGER NOT NULL REFERENCES ucm_class (id),
+    begin_time TEXT NULL,
+    end_time TEXT NULL,
+    begin_date TEXT NOT NULL,
+    end_date TEXT NOT NULL,
+    building TEXT NULL,
+    building_description TEXT NULL,
+    campus TEXT NULL,
+    campus_description TEXT NULL,
+    room TEXT NULL,
+    credit_hour_session REAL NOT NULL,
+    hours_per_week REAL NOT NULL,
+    in_session INTEGER NOT NULL,
+    meeting_type INTEGER NOT NULL
+);
+
+CREATE TABLE ucm_stats (
+    table_name TEXT NOT NULL PRIMARY KEY,
+    last_update TEXT NOT NULL
+);
+
+CREATE TABLE ucm_reminder (
+    user_id INTEGER NOT NULL,
+    course_reference_number INTEGER NOT NULL,
+    min_trigger INTEGER NOT NULL DEFAULT 1,
+    for_waitlist INTEGER NOT NULL DEFAULT 0,
+    triggered INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (user_id, course_reference_number)
+);
@@ -0,0 +1,8 @@
+-- Mirrors the [Settings] schema on SQL Server.
+
+CREATE TABLE settings_guild (
+    guild_id INTEGER NOT NULL,
+    key TEXT NOT NULL,
+    value TEXT NOT NULL,
+    PRIMARY KEY (guild_id, key)
+);
@@ -0,0 +1,9 @@
+-- Mirrors [Ranking].[Multiplier] on SQL Server.
+
+CREATE TABLE ranking_multiplier (
+    server_id INTEGER NOT NULL,
+    target_id INTEGER NOT NULL,
+    is_role INTEGER NOT NULL,
+    multiplier REAL NOT NULL,
+    PRIMARY KEY (server_id, target_id)
+);
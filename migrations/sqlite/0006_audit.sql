@@ -0,0 +1,14 @@
+-- Mirrors [Ranking].[Audit] on SQL Server.
+
+CREATE TABLE ranking_audit (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    server_id INTEGER NOT NULL,
+    moderator_id INTEGER NOT NULL,
+    -- NULL when it applied to everyone.
+    user_id INTEGER NULL,
+    action TEXT NOT NULL,
+    detail TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX ix_ranking_audit_server ON ranking_audit (server_id, created_at DESC);
@@ -0,0 +1,11 @@
+-- Mirrors [Ranking].[XpEvent] on SQL Server.
+
+CREATE TABLE ranking_xp_event (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    server_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    gain INTEGER NOT NULL,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX ix_ranking_xp_event_server ON ranking_xp_event (server_id, created_at);
@@ -0,0 +1,22 @@
+-- Mirrors [Ranking].[Reward] and [Ranking].[Boost] on SQL Server.
+
+CREATE TABLE ranking_reward (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    server_id INTEGER NOT NULL,
+    min_level INTEGER NOT NULL,
+    kind TEXT NOT NULL,
+    channel_id INTEGER NULL,
+    message TEXT NULL,
+    multiplier REAL NULL,
+    minutes INTEGER NULL
+);
+
+CREATE INDEX ix_ranking_reward_server ON ranking_reward (server_id, min_level);
+
+CREATE TABLE ranking_boost (
+    server_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    multiplier REAL NOT NULL,
+    expires_at TEXT NOT NULL,
+    PRIMARY KEY (server_id, user_id)
+);
@@ -0,0 +1,18 @@
+-- Mirrors [Ranking].[RoleFix] and [Ranking].[RoleFixChange] on SQL Server.
+
+CREATE TABLE ranking_role_fix (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    server_id INTEGER NOT NULL,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX ix_ranking_role_fix_server ON ranking_role_fix (server_id);
+
+CREATE TABLE ranking_role_fix_change (
+    fix_id INTEGER NOT NULL REFERENCES ranking_role_fix (id) ON DELETE CASCADE,
+    user_id INTEGER NOT NULL,
+    role_id INTEGER NOT NULL,
+    added INTEGER NOT NULL
+);
+
+CREATE INDEX ix_ranking_role_fix_change_fix ON ranking_role_fix_change (fix_id);
@@ -1,24 +1,26 @@
 use log::error;
 use serenity::{
-    framework::standard::{
-        macros::command, Args, CommandResult,
-    },
-    model::channel::Message, client::Context
+    framework::standard::CommandResult,
+    model::permissions::Permissions, client::Context
 };
 use serenity::model::channel::ReactionType;
 use serenity::model::id::ChannelId;
 use serenity::utils::MessageBuilder;
-use crate::{Database, db};
+use crate::services::cache::DbCache;
+use crate::services::cow_framework::{command, CowCommand, CommandOption, OptionKind, Invocation};
+
+pub static INFO_COMMAND: CowCommand = CowCommand {
+    only_in_guilds: true,
+    ..CowCommand::new("info", "Get the current settings for the cowboard.", info)
+};
 
 #[command]
-#[description = "Get the current settings for the cowboard."]
-#[only_in(guilds)]
-pub async fn info(ctx: &Context, msg: &Message) -> CommandResult {
-    let db = db!(ctx);
-
-    if let Some(guild_id) = msg.guild_id {
-        if let Ok(config) = db.get_cowboard_config(guild_id).await {
-            msg.channel_id.send_message(&ctx.http, |m| m.embed(|e|
+pub async fn info(ctx: &Context, cmd: &Invocation) -> CommandResult {
+    let cache = DbCache::from_context(ctx).await;
+
+    if let Some(guild_id) = cmd.guild_id() {
+        if let Ok(config) = cache.get_cowboard_config(guild_id).await {
+            cmd.send_message(&ctx.http, |m| m.embed(|e|
                 e
                     .title("Cowboard Settings")
                     .description("If the emote doesn't display properly below, you probably want to use a different one!")
@@ -30,222 +32,217 @@ pub async fn info(ctx: &Context, msg: &Message) -> CommandResult {
                     .field("Webhook", if config.webhook_id.is_some() && config.webhook_token.is_some() { "Enabled" } else { "Disabled" }, true)
             )).await?;
         } else {
-            msg.channel_id.say(&ctx.http, "Failed to fetch Cowboard settings for this server...").await?;
+            cmd.say(&ctx.http, "Failed to fetch Cowboard settings for this server...").await?;
         }
     } else {
-        msg.reply(&ctx.http, "This command can only be run in a server.").await?;
+        cmd.reply(&ctx.http, "This command can only be run in a server.").await?;
     }
 
     Ok(())
 }
 
+pub static EMOTE_COMMAND: CowCommand = CowCommand {
+    usage: Some("An emote, preferably one on the server or a default Discord emoji."),
+    options: &[CommandOption::new("emote", "The emote to react with, like :cow:.", OptionKind::String).required()],
+    only_in_guilds: true,
+    permissions: Permissions::ADMINISTRATOR,
+    ..CowCommand::new("emote", "Set the emote reaction to trigger a cowboard message.", emote)
+};
+
 #[command]
-#[description = "Set the emote reaction to trigger a cowboard message."]
-#[usage = "An emote, preferably one on the server or a default Discord emoji."]
-#[only_in(guilds)]
-#[required_permissions("ADMINISTRATOR")]
-pub async fn emote(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
-    let db = db!(ctx);
-
-    if args.is_empty() {
-        msg.channel_id.say(&ctx.http, "You need to pass an emote to this command, like :cow:.").await?;
-        return Ok(());
-    }
+pub async fn emote(ctx: &Context, cmd: &Invocation) -> CommandResult {
+    let cache = DbCache::from_context(ctx).await;
 
-    if let Ok(emoji) = args.single::<ReactionType>() {
-        if let Some(guild_id) = msg.guild_id {
-            match db.get_cowboard_config(guild_id).await {
+    if let Ok(emoji) = cmd.arg::<String>("emote").unwrap_or_default().trim().parse::<ReactionType>() {
+        if let Some(guild_id) = cmd.guild_id() {
+            match cache.get_cowboard_config(guild_id).await {
                 Ok(mut config) => {
                     config.emote = emoji.to_string();
-                    if let Err(ex) = db.update_cowboard(&config).await {
-                        msg.channel_id.say(&ctx.http, "We couldn't update the cowboard, sorry... Try again later?").await?;
+                    if let Err(ex) = cache.update_cowboard(&config).await {
+                        cmd.say(&ctx.http, "We couldn't update the cowboard, sorry... Try again later?").await?;
                         error!("Failed to update emote for cowboard: {}", ex);
                     } else {
-                        msg.channel_id.say(&ctx.http, "Successfully updated emote!").await?;
+                        cmd.say(&ctx.http, "Successfully updated emote!").await?;
                     }
                 }
                 Err(ex) => {
-                    msg.channel_id.say(&ctx.http, "We couldn't get the cowboard settings... try again later?").await?;
+                    cmd.say(&ctx.http, "We couldn't get the cowboard settings... try again later?").await?;
                     error!("Failed to get cowboard: {}", ex);
                 }
             }
         } else {
-            msg.reply(&ctx.http, "This command can only be run in a server.").await?;
+            cmd.reply(&ctx.http, "This command can only be run in a server.").await?;
         }
     } else {
-        msg.channel_id.say(&ctx.http, "Failed to process an emote from the given message...").await?;
+        cmd.say(&ctx.http, "Failed to process an emote from the given message...").await?;
         return Ok(());
     }
 
     Ok(())
 }
 
+pub static ADDTHRESHOLD_COMMAND: CowCommand = CowCommand {
+    usage: Some("A positive number, greater than the removal bound."),
+    options: &[CommandOption::new("threshold", "The minimum amount of reactions.", OptionKind::Integer).required()],
+    only_in_guilds: true,
+    permissions: Permissions::ADMINISTRATOR,
+    ..CowCommand::new("addthreshold", "Set the minimum amount of reactions to post a message to the cowboard.", addthreshold)
+};
+
 #[command]
-#[description = "Set the minimum amount of reactions to post a message to the cowboard."]
-#[usage = "A positive number, greater than the removal bound."]
-#[only_in(guilds)]
-#[required_permissions("ADMINISTRATOR")]
-pub async fn addthreshold(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
-    let db = db!(ctx);
-
-    if args.is_empty() {
-        msg.channel_id.say(&ctx.http, "You need to pass in a positive number for the minimum amount of reactions.").await?;
-        return Ok(());
-    }
+pub async fn addthreshold(ctx: &Context, cmd: &Invocation) -> CommandResult {
+    let cache = DbCache::from_context(ctx).await;
 
-    if let Ok(add_threshold) = args.single::<i32>() {
+    if let Some(add_threshold) = cmd.arg::<i32>("threshold") {
         if add_threshold <= 0 {
-            msg.channel_id.say(&ctx.http, "The given number must be positive.").await?;
+            cmd.say(&ctx.http, "The given number must be positive.").await?;
             return Ok(())
         }
 
-        if let Some(guild_id) = msg.guild_id {
-            match db.get_cowboard_config(guild_id).await {
+        if let Some(guild_id) = cmd.guild_id() {
+            match cache.get_cowboard_config(guild_id).await {
                 Ok(mut config) => {
                     if add_threshold < config.remove_threshold {
-                        msg.channel_id.say(&ctx.http, format!("The minimum number of reactions required to add must be greater than or equal to the removal limit (currently set to {}).", config.remove_threshold)).await?;
+                        cmd.say(&ctx.http, format!("The minimum number of reactions required to add must be greater than or equal to the removal limit (currently set to {}).", config.remove_threshold)).await?;
                         return Ok(())
                     }
 
                     config.add_threshold = add_threshold;
 
-                    if let Err(ex) = db.update_cowboard(&config).await {
-                        msg.channel_id.say(&ctx.http, "We couldn't update the cowboard, sorry... Try again later?").await?;
+                    if let Err(ex) = cache.update_cowboard(&config).await {
+                        cmd.say(&ctx.http, "We couldn't update the cowboard, sorry... Try again later?").await?;
                         error!("Failed to update cowboard: {}", ex);
                     } else {
-                        msg.channel_id.say(&ctx.http, "Successfully updated minimum add threshold!").await?;
+                        cmd.say(&ctx.http, "Successfully updated minimum add threshold!").await?;
                     }
                 }
                 Err(ex) => {
-                    msg.channel_id.say(&ctx.http, "We couldn't get the cowboard settings... try again later?").await?;
+                    cmd.say(&ctx.http, "We couldn't get the cowboard settings... try again later?").await?;
                     error!("Failed to get cowboard: {}", ex);
                 }
             }
         } else {
-            msg.reply(&ctx.http, "This command can only be run in a server.").await?;
+            cmd.reply(&ctx.http, "This command can only be run in a server.").await?;
         }
     } else {
-        msg.channel_id.say(&ctx.http, "The given value is not a valid number.").await?;
+        cmd.say(&ctx.http, "The given value is not a valid number.").await?;
         return Ok(());
     }
 
     Ok(())
 }
 
+pub static REMOVETHRESHOLD_COMMAND: CowCommand = CowCommand {
+    usage: Some("A positive number, less than the addition bound."),
+    options: &[CommandOption::new("threshold", "The removal reaction count, zero or more.", OptionKind::Integer).required()],
+    only_in_guilds: true,
+    permissions: Permissions::ADMINISTRATOR,
+    ..CowCommand::new("removethreshold", "Set the maximum amount of reactions before removing a message from the cowboard.", removethreshold)
+};
+
 #[command]
-#[description = "Set the maximum amount of reactions before removing a message from the cowboard."]
-#[usage = "A positive number, less than the addition bound."]
-#[only_in(guilds)]
-#[required_permissions("ADMINISTRATOR")]
-pub async fn removethreshold(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
-    let db = db!(ctx);
-
-    if args.is_empty() {
-        msg.channel_id.say(&ctx.http, "You need to pass in a positive number (or zero) for the removal reaction count.").await?;
-        return Ok(());
-    }
+pub async fn removethreshold(ctx: &Context, cmd: &Invocation) -> CommandResult {
+    let cache = DbCache::from_context(ctx).await;
 
-    if let Ok(remove_threshold) = args.single::<i32>() {
+    if let Some(remove_threshold) = cmd.arg::<i32>("threshold") {
         if remove_threshold < 0 {
-            msg.channel_id.say(&ctx.http, "The given number must be positive or zero.").await?;
+            cmd.say(&ctx.http, "The given number must be positive or zero.").await?;
             return Ok(())
         }
 
-        if let Some(guild_id) = msg.guild_id {
-            match db.get_cowboard_config(guild_id).await {
+        if let Some(guild_id) = cmd.guild_id() {
+            match cache.get_cowboard_config(guild_id).await {
                 Ok(mut config) => {
                     if remove_threshold > config.add_threshold {
-                        msg.channel_id.say(&ctx.http, format!("The maximum number of reactions required to remove must be less than or equal to the add limit (currently set to {}).", config.add_threshold)).await?;
+                        cmd.say(&ctx.http, format!("The maximum number of reactions required to remove must be less than or equal to the add limit (currently set to {}).", config.add_threshold)).await?;
                         return Ok(())
                     }
 
                     config.remove_threshold = remove_threshold;
 
-                    if let Err(ex) = db.update_cowboard(&config).await {
-                        msg.channel_id.say(&ctx.http, "We couldn't update the cowboard, sorry... Try again later?").await?;
+                    if let Err(ex) = cache.update_cowboard(&config).await {
+                        cmd.say(&ctx.http, "We couldn't update the cowboard, sorry... Try again later?").await?;
                         error!("Failed to update cowboard: {}", ex);
                     } else {
-                        msg.channel_id.say(&ctx.http, "Successfully updated maximum removal threshold!").await?;
+                        cmd.say(&ctx.http, "Successfully updated maximum removal threshold!").await?;
                     }
                 }
                 Err(ex) => {
-                    msg.channel_id.say(&ctx.http, "We couldn't get the cowboard settings... try again later?").await?;
+                    cmd.say(&ctx.http, "We couldn't get the cowboard settings... try again later?").await?;
                     error!("Failed to get cowboard: {}", ex);
                 }
             }
         } else {
-            msg.reply(&ctx.http, "This command can only be run in a server.").await?;
+            cmd.reply(&ctx.http, "This command can only be run in a server.").await?;
         }
     } else {
-        msg.channel_id.say(&ctx.http, "The given value is not a valid number.").await?;
+        cmd.say(&ctx.http, "The given value is not a valid number.").await?;
         return Ok(());
     }
 
     Ok(())
 }
 
+pub static CHANNEL_COMMAND: CowCommand = CowCommand {
+    usage: Some("Either uses the current channel or a provided channel."),
+    options: &[CommandOption::new("channel", "The channel to use, instead of this one.", OptionKind::Channel)],
+    only_in_guilds: true,
+    permissions: Permissions::ADMINISTRATOR,
+    ..CowCommand::new("channel", "Sets the Cowboard channel to pin messages.", channel)
+};
+
 #[command]
-#[description = "Sets the Cowboard channel to pin messages."]
-#[usage = "Either uses the current channel or a provided channel."]
-#[only_in(guilds)]
-#[required_permissions("ADMINISTRATOR")]
-pub async fn channel(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
-    let db = db!(ctx);
-
-    let mut channel = msg.channel_id;
-
-    if !args.is_empty() {
-        let custom_channel = args.single::<ChannelId>();
-        if custom_channel.is_err() {
-            msg.channel_id.say(&ctx.http, "Could not get a channel from your input!").await?;
-            return Ok(())
-        }
-        channel = custom_channel.unwrap();
-    }
+pub async fn channel(ctx: &Context, cmd: &Invocation) -> CommandResult {
+    let cache = DbCache::from_context(ctx).await;
+
+    let channel = cmd.arg::<ChannelId>("channel").unwrap_or_else(|| cmd.channel_id());
 
-    if let Some(guild_id) = msg.guild_id {
-        if !msg.guild(ctx).await.map(|g| g.channels.contains_key(&channel)).unwrap_or(false) {
-            msg.channel_id.say(&ctx.http, "Could not find channel in this server!").await?;
+    if let Some(guild_id) = cmd.guild_id() {
+        if !cmd.guild(ctx).await.map(|g| g.channels.contains_key(&channel)).unwrap_or(false) {
+            cmd.say(&ctx.http, "Could not find channel in this server!").await?;
             return Ok(())
         }
 
-        match db.get_cowboard_config(guild_id).await {
+        match cache.get_cowboard_config(guild_id).await {
             Ok(mut config) => {
                 config.channel = Some(channel.0);
                 config.webhook_id = None;
                 config.webhook_token = None;
 
-                if let Err(ex) = db.update_cowboard(&config).await {
-                    msg.channel_id.say(&ctx.http, "We couldn't update the cowboard, sorry... Try again later?").await?;
+                if let Err(ex) = cache.update_cowboard(&config).await {
+                    cmd.say(&ctx.http, "We couldn't update the cowboard, sorry... Try again later?").await?;
                     error!("Failed to update cowboard: {}", ex);
                 } else {
-                    msg.channel_id.say(&ctx.http, "Successfully updated channel! You may want to check webhooks; try using `.cowboard webhook` to enable it.").await?;
+                    cmd.say(&ctx.http, "Successfully updated channel! You may want to check webhooks; try using `.cowboard webhook` to enable it.").await?;
                 }
             }
             Err(ex) => {
-                msg.channel_id.say(&ctx.http, "We couldn't get the cowboard settings... try again later?").await?;
+                cmd.say(&ctx.http, "We couldn't get the cowboard settings... try again later?").await?;
                 error!("Failed to get cowboard: {}", ex);
             }
         }
     } else {
-        msg.reply(&ctx.http, "This command can only be run in a server.").await?;
+        cmd.reply(&ctx.http, "This command can only be run in a server.").await?;
     }
 
     Ok(())
 }
 
+pub static WEBHOOK_COMMAND: CowCommand = CowCommand {
+    only_in_guilds: true,
+    permissions: Permissions::ADMINISTRATOR,
+    ..CowCommand::new("webhook", "Toggle webhook usage for the cowboard, versus the bot sending the messages.", webhook)
+};
+
 #[command]
-#[description = "Toggle webhook usage for the cowboard, versus the bot sending the messages."]
-#[only_in(guilds)]
-#[required_permissions("ADMINISTRATOR")]
-pub async fn webhook(ctx: &Context, msg: &Message) -> CommandResult {
-    let db = db!(ctx);
-
-    if let Some(guild) = msg.guild(ctx).await {
-        match db.get_cowboard_config(guild.id).await {
+pub async fn webhook(ctx: &Context, cmd: &Invocation) -> CommandResult {
+    let cache = DbCache::from_context(ctx).await;
+
+    if let Some(guild) = cmd.guild(ctx).await {
+        match cache.get_cowboard_config(guild.id).await {
             Ok(mut config) => {
                 if config.channel == None {
-                    msg.channel_id.say(&ctx.http, "Cowboard channel is not set up!").await?;
+                    cmd.say(&ctx.http, "Cowboard channel is not set up!").await?;
                     return Ok(());
                 }
                 let channel = ChannelId::from(config.channel.unwrap());
@@ -257,7 +254,7 @@ pub async fn webhook(ctx: &Context, msg: &Message) -> CommandResult {
                                 config.webhook_token = Some(webhook.token.unwrap())
                             }
                             Err(ex) => {
-                                msg.channel_id.say(&ctx.http, format!("Failed to add webhook; maybe I do not have permissions for the channel <#{}>?", channel)).await?;
+                                cmd.say(&ctx.http, format!("Failed to add webhook; maybe I do not have permissions for the channel <#{}>?", channel)).await?;
                                 error!("Failed to create webhook: {}", ex);
                                 return Ok(())
                             }
@@ -267,25 +264,25 @@ pub async fn webhook(ctx: &Context, msg: &Message) -> CommandResult {
                         config.webhook_token = None;
                     }
 
-                    if let Err(ex) = db.update_cowboard(&config).await {
-                        msg.channel_id.say(&ctx.http, "We couldn't update the cowboard, sorry... Try again later?").await?;
+                    if let Err(ex) = cache.update_cowboard(&config).await {
+                        cmd.say(&ctx.http, "We couldn't update the cowboard, sorry... Try again later?").await?;
                         error!("Failed to update cowboard: {}", ex);
                     } else if config.webhook_id == None {
-                        msg.channel_id.say(&ctx.http, format!("Disabled webhooks for <#{}>.", channel)).await?;
+                        cmd.say(&ctx.http, format!("Disabled webhooks for <#{}>.", channel)).await?;
                     } else {
-                        msg.channel_id.say(&ctx.http, format!("Enabled webhooks for <#{}>.", channel)).await?;
+                        cmd.say(&ctx.http, format!("Enabled webhooks for <#{}>.", channel)).await?;
                     }
                 } else {
-                    msg.channel_id.say(&ctx.http, format!("We don't have access to <#{}>... maybe it's hidden for us?", channel)).await?;
+                    cmd.say(&ctx.http, format!("We don't have access to <#{}>... maybe it's hidden for us?", channel)).await?;
                 }
             }
             Err(ex) => {
-                msg.channel_id.say(&ctx.http, "We couldn't get the cowboard settings... try again later?").await?;
+                cmd.say(&ctx.http, "We couldn't get the cowboard settings... try again later?").await?;
                 error!("Failed to get cowboard: {}", ex);
             }
         }
     } else {
-        msg.reply(&ctx.http, "This command can only be run in a server.").await?;
+        cmd.reply(&ctx.http, "This command can only be run in a server.").await?;
     }
 
     Ok(())
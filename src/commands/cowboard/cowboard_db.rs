@@ -4,21 +4,31 @@ use serenity::{
         ChannelId
     }
 };
-use rust_decimal::{
-    Decimal,
-    prelude::FromPrimitive
-};
-use rust_decimal::prelude::ToPrimitive;
 use serenity::model::id::MessageId;
+use async_trait::async_trait;
+use rusqlite::{OptionalExtension, params};
 
-use crate::Database;
+use crate::models::error::CowError;
+use crate::services::database::{SqlServerDatabase, SqliteDatabase};
+use crate::services::database::sql_server::{to_decimal, from_decimal, column, nullable, nullable_id};
+use crate::services::database::sqlite::{to_sql_id, from_sql_id};
 use crate::commands::cowboard::cowboard_db_models::*;
 
+#[async_trait]
+pub trait CowboardStore {
+    async fn get_cowboard_config(&self, server_id: GuildId) -> Result<Cowboard, CowError>;
+    async fn update_cowboard(&self, config: &Cowboard) -> Result<(), CowError>;
+    async fn get_cowboard_message(&self, message: MessageId, channel: ChannelId, guild: GuildId) -> Result<Option<CowboardMessage>, CowError>;
+    async fn moo_message(&self, message: MessageId, channel: ChannelId, post_message: MessageId, post_channel: ChannelId, guild: GuildId) -> Result<(), CowError>;
+    async fn unmoo_message(&self, message: MessageId, channel: ChannelId, guild: GuildId) -> Result<(), CowError>;
+}
+
 // Separating the database into different modules so it doesn't become a 2000 line file.
-impl Database {
-    pub async fn get_cowboard_config(&self, server_id: GuildId) -> Result<Cowboard, Box<dyn std::error::Error + Send + Sync>> {
+#[async_trait]
+impl CowboardStore for SqlServerDatabase {
+    async fn get_cowboard_config(&self, server_id: GuildId) -> Result<Cowboard, CowError> {
         let mut conn = self.pool.get().await?;
-        let server = Decimal::from_u64(*server_id.as_u64()).unwrap();
+        let server = to_decimal(server_id.0);
         let res = conn.query(
             "SELECT channel, add_threshold, remove_threshold, emote, webhook_id, webhook_token FROM [Cowboard].[Server] WHERE id = @P1",
             &[&server])
@@ -29,17 +39,15 @@ impl Database {
         let mut out = Cowboard::new(server_id.0);
 
         if let Some(item) = res {
-            let channel_id: Option<rust_decimal::Decimal> = item.get(0);
-            let emote_str: &str = item.get(3).unwrap();
-            let webhook_id: Option<rust_decimal::Decimal> = item.get(4);
-            let webhook_token: Option<&str> = item.get(5);
+            let emote_str: &str = column(&item, 3)?;
+            let webhook_token: Option<&str> = nullable(&item, 5)?;
             out = Cowboard {
                 id: server_id.0,
-                channel: channel_id.and_then(|o| o.to_u64()),
-                add_threshold: item.get(1).unwrap(),
-                remove_threshold: item.get(2).unwrap(),
+                channel: nullable_id(&item, 0)?,
+                add_threshold: column(&item, 1)?,
+                remove_threshold: column(&item, 2)?,
                 emote: emote_str.to_string(),
-                webhook_id: webhook_id.and_then(|o| o.to_u64()),
+                webhook_id: nullable_id(&item, 4)?,
                 webhook_token: webhook_token.map(|o| o.to_string())
             };
         }
@@ -47,11 +55,11 @@ impl Database {
         Ok(out)
     }
 
-    pub async fn update_cowboard(&self, config: &Cowboard) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    async fn update_cowboard(&self, config: &Cowboard) -> Result<(), CowError> {
         let mut conn = self.pool.get().await?;
-        let server = Decimal::from_u64(config.id).unwrap();
-        let channel = config.channel.map(|o| Decimal::from_u64(o).unwrap());
-        let webhook_id = config.webhook_id.map(|o| Decimal::from_u64(o).unwrap());
+        let server = to_decimal(config.id);
+        let channel = config.channel.map(to_decimal);
+        let webhook_id = config.webhook_id.map(to_decimal);
 
         conn.query(
             "EXEC [Cowboard].[UpdateServer] @id = @P1, @channel = @P2, @add_threshold = @P3, @remove_threshold = @P4, @emote = @P5, @webhook_id = @P6, @webhook_token = @P7",
@@ -61,11 +69,11 @@ impl Database {
         Ok(())
     }
 
-    pub async fn get_cowboard_message(&self, message: MessageId, channel: ChannelId, guild: GuildId) -> Result<Option<CowboardMessage>, Box<dyn std::error::Error + Send + Sync>> {
+    async fn get_cowboard_message(&self, message: MessageId, channel: ChannelId, guild: GuildId) -> Result<Option<CowboardMessage>, CowError> {
         let mut conn = self.pool.get().await?;
-        let message_decimal = Decimal::from_u64(message.0).unwrap();
-        let channel_decimal = Decimal::from_u64(channel.0).unwrap();
-        let server_decimal = Decimal::from_u64(guild.0).unwrap();
+        let message_decimal = to_decimal(message.0);
+        let channel_decimal = to_decimal(channel.0);
+        let server_decimal = to_decimal(guild.0);
         let res = conn.query(
             "SELECT post_id, post_channel_id FROM [Cowboard].[Message] WHERE message_id = @P1 AND message_channel_id = @P2 AND guild_id = @P3",
             &[&message_decimal, &channel_decimal, &server_decimal])
@@ -76,8 +84,8 @@ impl Database {
         let mut out: Option<CowboardMessage> = None;
 
         if let Some(item) = res {
-            let post_id = item.get(0).and_then(|u: rust_decimal::Decimal| u.to_u64()).unwrap();
-            let post_channel_id = item.get(1).and_then(|u: rust_decimal::Decimal| u.to_u64()).unwrap();
+            let post_id = from_decimal(column(&item, 0)?)?;
+            let post_channel_id = from_decimal(column(&item, 1)?)?;
 
             out = Some(CowboardMessage {
                 message_id: message.0,
@@ -91,13 +99,13 @@ impl Database {
         Ok(out)
     }
 
-    pub async fn moo_message(&self, message: MessageId, channel: ChannelId, post_message: MessageId, post_channel: ChannelId, guild: GuildId) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    async fn moo_message(&self, message: MessageId, channel: ChannelId, post_message: MessageId, post_channel: ChannelId, guild: GuildId) -> Result<(), CowError> {
         let mut conn = self.pool.get().await?;
-        let message = Decimal::from_u64(message.0).unwrap();
-        let channel = Decimal::from_u64(channel.0).unwrap();
-        let post_message = Decimal::from_u64(post_message.0).unwrap();
-        let post_channel = Decimal::from_u64(post_channel.0).unwrap();
-        let server = Decimal::from_u64(guild.0).unwrap();
+        let message = to_decimal(message.0);
+        let channel = to_decimal(channel.0);
+        let post_message = to_decimal(post_message.0);
+        let post_channel = to_decimal(post_channel.0);
+        let server = to_decimal(guild.0);
 
         conn.query(
             "INSERT INTO [Cowboard].[Message] (message_id, message_channel_id, post_id, post_channel_id, guild_id) VALUES (@P1, @P2, @P3, @P4, @P5)",
@@ -107,11 +115,11 @@ impl Database {
         Ok(())
     }
 
-    pub async fn unmoo_message(&self, message: MessageId, channel: ChannelId, guild: GuildId) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    async fn unmoo_message(&self, message: MessageId, channel: ChannelId, guild: GuildId) -> Result<(), CowError> {
         let mut conn = self.pool.get().await?;
-        let message = Decimal::from_u64(message.0).unwrap();
-        let channel = Decimal::from_u64(channel.0).unwrap();
-        let server = Decimal::from_u64(guild.0).unwrap();
+        let message = to_decimal(message.0);
+        let channel = to_decimal(channel.0);
+        let server = to_decimal(guild.0);
 
         conn.query(
             "DELETE FROM [Cowboard].[Message] WHERE message_id = @P1 AND message_channel_id = @P2 AND guild_id = @P3",
@@ -120,4 +128,73 @@ impl Database {
 
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+#[async_trait]
+impl CowboardStore for SqliteDatabase {
+    async fn get_cowboard_config(&self, server_id: GuildId) -> Result<Cowboard, CowError> {
+        let conn = self.conn();
+        let res = conn.query_row(
+            "SELECT channel, add_threshold, remove_threshold, emote, webhook_id, webhook_token FROM cowboard_server WHERE id = ?1",
+            params![to_sql_id(server_id.0)],
+            |row| Ok(Cowboard {
+                id: server_id.0,
+                channel: row.get::<_, Option<i64>>(0)?.map(from_sql_id),
+                add_threshold: row.get(1)?,
+                remove_threshold: row.get(2)?,
+                emote: row.get(3)?,
+                webhook_id: row.get::<_, Option<i64>>(4)?.map(from_sql_id),
+                webhook_token: row.get(5)?
+            }))
+            .optional()?;
+
+        Ok(res.unwrap_or_else(|| Cowboard::new(server_id.0)))
+    }
+
+    // Replaces [Cowboard].[UpdateServer].
+    async fn update_cowboard(&self, config: &Cowboard) -> Result<(), CowError> {
+        let conn = self.conn();
+        conn.execute(
+            "INSERT INTO cowboard_server (id, channel, add_threshold, remove_threshold, emote, webhook_id, webhook_token) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7) \
+            ON CONFLICT (id) DO UPDATE SET channel = excluded.channel, add_threshold = excluded.add_threshold, remove_threshold = excluded.remove_threshold, \
+            emote = excluded.emote, webhook_id = excluded.webhook_id, webhook_token = excluded.webhook_token",
+            params![to_sql_id(config.id), config.channel.map(to_sql_id), config.add_threshold, config.remove_threshold, config.emote, config.webhook_id.map(to_sql_id), config.webhook_token])?;
+
+        Ok(())
+    }
+
+    async fn get_cowboard_message(&self, message: MessageId, channel: ChannelId, guild: GuildId) -> Result<Option<CowboardMessage>, CowError> {
+        let conn = self.conn();
+        let res = conn.query_row(
+            "SELECT post_id, post_channel_id FROM cowboard_message WHERE message_id = ?1 AND message_channel_id = ?2 AND guild_id = ?3",
+            params![to_sql_id(message.0), to_sql_id(channel.0), to_sql_id(guild.0)],
+            |row| Ok(CowboardMessage {
+                message_id: message.0,
+                message_channel_id: channel.0,
+                post_id: from_sql_id(row.get(0)?),
+                post_channel_id: from_sql_id(row.get(1)?),
+                guild_id: guild.0
+            }))
+            .optional()?;
+
+        Ok(res)
+    }
+
+    async fn moo_message(&self, message: MessageId, channel: ChannelId, post_message: MessageId, post_channel: ChannelId, guild: GuildId) -> Result<(), CowError> {
+        let conn = self.conn();
+        conn.execute(
+            "INSERT INTO cowboard_message (message_id, message_channel_id, post_id, post_channel_id, guild_id) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![to_sql_id(message.0), to_sql_id(channel.0), to_sql_id(post_message.0), to_sql_id(post_channel.0), to_sql_id(guild.0)])?;
+
+        Ok(())
+    }
+
+    async fn unmoo_message(&self, message: MessageId, channel: ChannelId, guild: GuildId) -> Result<(), CowError> {
+        let conn = self.conn();
+        conn.execute(
+            "DELETE FROM cowboard_message WHERE message_id = ?1 AND message_channel_id = ?2 AND guild_id = ?3",
+            params![to_sql_id(message.0), to_sql_id(channel.0), to_sql_id(guild.0)])?;
+
+        Ok(())
+    }
+}
@@ -1,3 +1,4 @@
+#[derive(Clone)]
 pub struct Cowboard {
     pub id: u64,
     pub channel: Option<u64>,
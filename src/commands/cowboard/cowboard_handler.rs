@@ -7,10 +7,13 @@ use serenity::model::channel::{Embed, Message, Reaction, ReactionType};
 use serenity::model::id::{ChannelId, GuildId, MessageId, UserId};
 use tokio::io::AsyncWriteExt;
 use crate::{Database, db};
+use crate::services::cache::DbCache;
+use crate::services::supervisor::Supervisor;
+use crate::models::error::CowError;
 use crate::commands::cowboard::cowboard_db_models::{Cowboard};
 
-async fn count_reactions(ctx: &Context, message: &Message, config: &Cowboard) -> Result<u64, Box<dyn std::error::Error + Send + Sync>>{
-    let config_emote = ReactionType::try_from(config.emote.as_str())?;
+async fn count_reactions(ctx: &Context, message: &Message, config: &Cowboard) -> Result<u64, CowError>{
+    let config_emote = ReactionType::try_from(config.emote.as_str()).map_err(|_| CowError::decode(format!("{} is not a valid cowboard emote", config.emote)))?;
     let matched_reaction = message.reactions.iter().find(|o|o.reaction_type.eq(&config_emote));
     if let Some(reaction) = matched_reaction {
         let count = reaction.count;
@@ -30,7 +33,8 @@ pub async fn add_reaction(ctx: &Context, added_reaction: &Reaction) {
     }
     let guild_id = added_reaction.guild_id.unwrap();
     let db = db!(ctx);
-    match db.get_cowboard_config(guild_id).await {
+    let cache = DbCache::from_context(ctx).await;
+    match cache.get_cowboard_config(guild_id).await {
         Ok(mut config) => {
             if config.channel.is_none() {
                 // No cowboard, why even check?
@@ -81,6 +85,8 @@ pub async fn add_reaction(ctx: &Context, added_reaction: &Reaction) {
 
 async fn add_moo(ctx: &Context, guild_id: GuildId, reaction: &Reaction, message: &Message, config: &mut Cowboard) {
     let db = db!(ctx);
+    // Downloads attachments to disk, so a shutdown should let this finish.
+    let _work = Supervisor::from_context(ctx).await.begin_work();
 
     let message_result = if config.webhook_id.is_some() && config.webhook_token.is_some() {
         send_webhook_message(ctx, message, config).await
@@ -88,12 +94,13 @@ async fn add_moo(ctx: &Context, guild_id: GuildId, reaction: &Reaction, message:
         send_bot_message(ctx, message, config).await
     };
 
-    if let Err(ex) = message_result {
-        error!("Failed to send cowboard message: {}", ex);
-        return;
-    }
-
-    let post_message = message_result.unwrap();
+    let post_message = match message_result {
+        Ok(post_message) => post_message,
+        Err(ex) => {
+            error!("Failed to send cowboard message: {}", ex);
+            return;
+        }
+    };
 
     if let Err(ex) = db.moo_message(message.id, reaction.channel_id, post_message.id, post_message.channel_id, guild_id).await {
         error!("Failed to moo a message in the database: {}", ex);
@@ -108,7 +115,7 @@ async fn update_moo(ctx: &Context, message: &Message, post_message: &mut Message
     };
 }
 
-async fn send_bot_message(ctx: &Context, message: &Message, config: &Cowboard) -> Result<Message, Box<dyn std::error::Error + Send + Sync>> {
+async fn send_bot_message(ctx: &Context, message: &Message, config: &Cowboard) -> Result<Message, CowError> {
     let channel = ChannelId::from(config.channel.unwrap());
     let output_username = format_username(ctx, message).await;
     let safe_content = message.content_safe(ctx).await;
@@ -152,7 +159,7 @@ async fn send_bot_message(ctx: &Context, message: &Message, config: &Cowboard) -
             Ok(message)
         }
         Err(ex) => {
-            Err(Box::new(ex))
+            Err(ex.into())
         }
     }
 }
@@ -172,7 +179,7 @@ async fn update_bot_message(ctx: &Context, message: &Message, post_message: &mut
     }
 }
 
-async fn send_webhook_message(ctx: &Context, message: &Message, config: &mut Cowboard) -> Result<Message, Box<dyn std::error::Error + Send + Sync>> {
+async fn send_webhook_message(ctx: &Context, message: &Message, config: &mut Cowboard) -> Result<Message, CowError> {
     let token = config.webhook_token.clone().unwrap();
     if let Ok(webhook) = ctx.http.get_webhook_with_token(config.webhook_id.unwrap(), &*token).await {
         let output_username = format_username(ctx, message).await;
@@ -312,11 +319,11 @@ async fn update_webhook_message(ctx: &Context, message: &Message, post_message:
 }
 
 async fn disable_webhook(ctx: &Context, config: &mut Cowboard) {
-    let db = db!(ctx);
+    let cache = DbCache::from_context(ctx).await;
 
     config.webhook_id = None;
     config.webhook_token = None;
-    if let Err(ex) = db.update_cowboard(config).await {
+    if let Err(ex) = cache.update_cowboard(config).await {
         error!("Failed to update cowboard settings: {}", ex);
     }
 }
@@ -328,7 +335,8 @@ pub async fn remove_reaction(ctx: &Context, removed_reaction: &Reaction) {
 
     let guild_id = removed_reaction.guild_id.unwrap();
     let db = db!(ctx);
-    match db.get_cowboard_config(guild_id).await {
+    let cache = DbCache::from_context(ctx).await;
+    match cache.get_cowboard_config(guild_id).await {
         Ok(mut config) => {
             match removed_reaction.message(&ctx.http).await {
                 Ok(message) => {
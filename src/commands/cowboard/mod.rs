@@ -1,17 +1,18 @@
 mod cowboard_config;
-mod cowboard_db;
-mod cowboard_db_models;
+pub mod cowboard_db;
+pub mod cowboard_db_models;
 pub mod cowboard_handler;
 
-use serenity::framework::standard::macros::group;
+use crate::services::cow_framework::CowGroup;
 
 use cowboard_config::*;
 
-#[group]
-#[prefixes("cowboard")]
-#[description = "Commands for modifying how the cowboard (starboard) functions."]
-#[summary = "Cowboard"]
-#[default_command(info)]
-#[commands(info, emote, addthreshold, removethreshold, channel, webhook)]
-struct Cowboard;
-
+pub static COWBOARD_GROUP: CowGroup = CowGroup {
+    name: "Cowboard",
+    prefixes: &["cowboard"],
+    description: "Commands for modifying how the cowboard (starboard) functions.",
+    summary: "Cowboard",
+    default_command: Some(&INFO_COMMAND),
+    commands: &[&INFO_COMMAND, &EMOTE_COMMAND, &ADDTHRESHOLD_COMMAND, &REMOVETHRESHOLD_COMMAND, &CHANNEL_COMMAND, &WEBHOOK_COMMAND],
+    sub_groups: &[]
+};
@@ -1,42 +1,59 @@
 use serenity::client::Context;
-use serenity::framework::standard::{Args, CommandResult};
-use serenity::model::channel::{Message};
-use serenity::framework::standard::macros::{command};
+use serenity::framework::standard::CommandResult;
+use serenity::model::permissions::Permissions;
+use crate::services::cow_framework::{command, CowCommand, CommandOption, OptionKind, Invocation};
+
+const REASON: [CommandOption; 1] = [CommandOption::new("reason", "What to put in the audit log.", OptionKind::String)];
+
+pub static BANLEAGUEPLAYERS_COMMAND: CowCommand = CowCommand {
+    options: &REASON,
+    only_in_guilds: true,
+    permissions: Permissions::BAN_MEMBERS,
+    ..CowCommand::new("banleagueplayers", "Ban everyone playing League.", banleagueplayers)
+};
 
 #[command]
-#[only_in(guilds)]
-#[required_permissions("BAN_MEMBERS")]
-async fn banleagueplayers(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
-    if args.is_empty() {
-        return ban_game_players(ctx, msg, 356869127241072640, "Playing League? Cringe.").await;
+async fn banleagueplayers(ctx: &Context, cmd: &Invocation) -> CommandResult {
+    if let Some(reason) = cmd.arg::<String>("reason") {
+        return ban_game_players(ctx, cmd, 356869127241072640, reason).await;
     }
 
-    ban_game_players(ctx, msg, 356869127241072640, args.message()).await
+    ban_game_players(ctx, cmd, 356869127241072640, "Playing League? Cringe.").await
 }
 
+pub static BANVALORANTPLAYERS_COMMAND: CowCommand = CowCommand {
+    options: &REASON,
+    only_in_guilds: true,
+    permissions: Permissions::BAN_MEMBERS,
+    ..CowCommand::new("banvalorantplayers", "Ban everyone playing VALORANT.", banvalorantplayers)
+};
+
 #[command]
-#[only_in(guilds)]
-#[required_permissions("BAN_MEMBERS")]
-async fn banvalorantplayers(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
-    if args.is_empty() {
-        return ban_game_players(ctx, msg, 700136079562375258, "Playing VALORANT? Cringe.").await;
+async fn banvalorantplayers(ctx: &Context, cmd: &Invocation) -> CommandResult {
+    if let Some(reason) = cmd.arg::<String>("reason") {
+        return ban_game_players(ctx, cmd, 700136079562375258, reason).await;
     }
-    ban_game_players(ctx, msg, 700136079562375258, args.message()).await
+    ban_game_players(ctx, cmd, 700136079562375258, "Playing VALORANT? Cringe.").await
 }
 
+pub static BANGENSHINPLAYERS_COMMAND: CowCommand = CowCommand {
+    options: &REASON,
+    only_in_guilds: true,
+    permissions: Permissions::BAN_MEMBERS,
+    ..CowCommand::new("bangenshinplayers", "Ban everyone playing Genshin.", bangenshinplayers)
+};
+
 #[command]
-#[only_in(guilds)]
-#[required_permissions("BAN_MEMBERS")]
-async fn bangenshinplayers(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
-    if args.is_empty() {
-        return ban_game_players(ctx, msg, 762434991303950386, "Playing Genshin? Cringe.").await;
+async fn bangenshinplayers(ctx: &Context, cmd: &Invocation) -> CommandResult {
+    if let Some(reason) = cmd.arg::<String>("reason") {
+        return ban_game_players(ctx, cmd, 762434991303950386, reason).await;
     }
 
-    ban_game_players(ctx, msg, 762434991303950386, args.message()).await
+    ban_game_players(ctx, cmd, 762434991303950386, "Playing Genshin? Cringe.").await
 }
 
-async fn ban_game_players(ctx: &Context, msg: &Message, game_id: u64, message: impl AsRef<str>) -> CommandResult {
-    if let Some(guild) = msg.guild(&ctx).await {
+async fn ban_game_players(ctx: &Context, cmd: &Invocation, game_id: u64, message: impl AsRef<str>) -> CommandResult {
+    if let Some(guild) = cmd.guild(ctx).await {
         let mut degenerates: Vec<u64> = Vec::new();
         for (_, presence) in guild.presences.iter() {
             if presence.activities.iter()
@@ -52,9 +69,9 @@ async fn ban_game_players(ctx: &Context, msg: &Message, game_id: u64, message: i
 
         let list = degenerates.iter().map(|o| format!("<@{}>", o)).reduce(|a, b| format!("{}, {}", a, b));
         if let Some(output) = list {
-            msg.channel_id.say(&ctx.http, format!("Successfully banned these degenerates: {}", output)).await?;
+            cmd.say(&ctx.http, format!("Successfully banned these degenerates: {}", output)).await?;
         } else {
-            msg.channel_id.say(&ctx.http, "No haram activities detected.").await?;
+            cmd.say(&ctx.http, "No haram activities detected.").await?;
         }
     }
 
@@ -0,0 +1,121 @@
+use std::borrow::Cow;
+use serde_json::json;
+use serenity::{
+    client::Context,
+    http::AttachmentType,
+    model::permissions::Permissions,
+    framework::standard::CommandResult
+};
+use crate::{Database, db};
+use crate::services::cow_framework::{command, CowCommand, CommandOption, OptionChoice, OptionKind, Invocation};
+
+pub static EXPORT_COMMAND: CowCommand = CowCommand {
+    usage: Some("[csv|json]"),
+    options: &[CommandOption::new("format", "The kind of file to export.", OptionKind::String).choices(&[
+        OptionChoice::string("CSV", "csv"),
+        OptionChoice::string("JSON", "json")
+    ])],
+    only_in_guilds: true,
+    bucket: Some("diagnostics"),
+    permissions: Permissions::ADMINISTRATOR,
+    ..CowCommand::new("export", "Download everyone's level, experience and rank on this server as a file.", export)
+};
+
+struct ExportRow {
+    rank: usize,
+    user_id: u64,
+    username: String,
+    level: i32,
+    xp: i32,
+    role_id: Option<u64>,
+    role_name: String
+}
+
+// Quotes a cell only when it needs it, doubling any quotes inside.
+// Names that a spreadsheet would read as a formula get a leading ' so they stay text.
+fn csv_field(value: &str) -> String {
+    if value.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("\"'{}\"", value.replace('"', "\"\""))
+    } else if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn to_csv(rows: &[ExportRow]) -> String {
+    let mut out = String::from("rank,user_id,username,level,xp,role_id,role_name\n");
+    for row in rows {
+        out += &format!("{},{},{},{},{},{},{}\n",
+            row.rank,
+            row.user_id,
+            csv_field(&row.username),
+            row.level,
+            row.xp,
+            row.role_id.map(|r| r.to_string()).unwrap_or_default(),
+            csv_field(&row.role_name));
+    }
+    out
+}
+
+// IDs are strings so they survive anything that reads numbers as doubles.
+fn to_json(rows: &[ExportRow]) -> String {
+    let rows = rows.iter()
+        .map(|row| json!({
+            "rank": row.rank,
+            "user_id": row.user_id.to_string(),
+            "username": row.username,
+            "level": row.level,
+            "xp": row.xp,
+            "role_id": row.role_id.map(|r| r.to_string()),
+            "role_name": if row.role_name.is_empty() { None } else { Some(&row.role_name) }
+        }))
+        .collect::<Vec<_>>();
+
+    serde_json::to_string_pretty(&rows).unwrap_or_default()
+}
+
+#[command]
+pub async fn export(ctx: &Context, cmd: &Invocation) -> CommandResult {
+    let server_id = match cmd.guild_id() {
+        Some(server_id) => server_id,
+        None => {
+            cmd.reply(&ctx.http, "This command can only be run in a server.").await?;
+            return Ok(());
+        }
+    };
+
+    cmd.defer(&ctx.http).await?;
+    let db = db!(ctx);
+    let mut users = db.get_users(server_id).await?;
+    // Same order as the leaderboard.
+    users.sort_by(|a, b| b.exp.level.cmp(&a.exp.level).then(b.exp.xp.cmp(&a.exp.xp)).then(a.user.cmp(&b.user)));
+
+    let roles = ctx.cache.guild_field(server_id, |g| g.roles.clone()).await.unwrap_or_default();
+    let mut rows = Vec::with_capacity(users.len());
+    for (index, member) in users.iter().enumerate() {
+        // Only whoever is cached; looking up everyone who ever talked here would take forever.
+        let username = ctx.cache.user(member.user).await.map(|u| u.tag()).unwrap_or_default();
+        let role_name = member.role_id.and_then(|r| roles.get(&r)).map(|r| r.name.clone()).unwrap_or_default();
+        rows.push(ExportRow {
+            rank: index + 1,
+            user_id: member.user.0,
+            username,
+            level: member.exp.level,
+            xp: member.exp.xp,
+            role_id: member.role_id.map(|r| r.0),
+            role_name
+        });
+    }
+
+    let format = cmd.arg::<String>("format").unwrap_or_else(|| "csv".to_string());
+    let (data, extension) = if format == "json" { (to_json(&rows), "json") } else { (to_csv(&rows), "csv") };
+    let file = AttachmentType::Bytes {
+        data: Cow::Owned(data.into_bytes()),
+        filename: format!("levels-{}.{}", server_id, extension)
+    };
+
+    cmd.send_file(&ctx.http, format!("Here are all {} ranked members.", rows.len()), file).await?;
+
+    Ok(())
+}
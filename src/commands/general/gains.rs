@@ -0,0 +1,121 @@
+use async_trait::async_trait;
+use chrono::{Duration, NaiveDate, NaiveDateTime, Utc};
+use serenity::{
+    builder::CreateEmbed,
+    client::Context,
+    model::id::UserId,
+    framework::standard::CommandResult
+};
+use crate::{Database, db};
+use crate::models::db_models::XpGain;
+use crate::models::error::CowError;
+use crate::services::cow_framework::{command, paginate, CowCommand, CommandOption, OptionKind, Invocation, PageSource};
+
+const GAINS_PAGE_SIZE: usize = 10;
+
+pub static WEEK_COMMAND: CowCommand = CowCommand {
+    options: &[CommandOption::new("page", "The page of the leaderboard to show.", OptionKind::Integer)],
+    only_in_guilds: true,
+    ..CowCommand::new("week", "Rank everyone by the experience they earned in the last 7 days.", week)
+};
+
+pub static MONTH_COMMAND: CowCommand = CowCommand {
+    options: &[CommandOption::new("page", "The page of the leaderboard to show.", OptionKind::Integer)],
+    only_in_guilds: true,
+    ..CowCommand::new("month", "Rank everyone by the experience they earned in the last 30 days.", month)
+};
+
+pub static SINCE_COMMAND: CowCommand = CowCommand {
+    usage: Some("<YYYY-MM-DD> [page]"),
+    options: &[
+        CommandOption::new("date", "The day to start counting from (UTC), like 2022-01-24.", OptionKind::String).required(),
+        CommandOption::new("page", "The page of the leaderboard to show.", OptionKind::Integer)
+    ],
+    only_in_guilds: true,
+    ..CowCommand::new("since", "Rank everyone by the experience they earned since a date, like the start of the semester.", since)
+};
+
+// Everything is fetched up front; it's one row per person who talked, not per message.
+struct GainLeaderboard {
+    title: String,
+    gains: Vec<XpGain>
+}
+
+#[async_trait]
+impl PageSource for GainLeaderboard {
+    fn page_count(&self) -> usize {
+        self.gains.chunks(GAINS_PAGE_SIZE).len()
+    }
+
+    async fn page(&self, _: &Context, index: usize) -> Result<CreateEmbed, CowError> {
+        let content = self.gains.iter()
+            .enumerate()
+            .skip(index * GAINS_PAGE_SIZE)
+            .take(GAINS_PAGE_SIZE)
+            .map(|(position, gain)| format!("`#{}` <@{}> - {} xp", position + 1, gain.user, gain.gain))
+            .reduce(|a, b| format!("{}\n{}", a, b))
+            .unwrap_or_else(|| "There is nothing on this page.".to_string());
+
+        let mut embed = CreateEmbed::default();
+        embed.title(&self.title).description(content);
+        Ok(embed)
+    }
+
+    fn can_jump(&self) -> bool {
+        true
+    }
+
+    async fn page_of(&self, _: &Context, user_id: UserId) -> Result<Option<usize>, CowError> {
+        Ok(self.gains.iter().position(|gain| gain.user == user_id).map(|position| position / GAINS_PAGE_SIZE))
+    }
+}
+
+async fn show_gains(ctx: &Context, cmd: &Invocation, since: NaiveDateTime, title: String) -> CommandResult {
+    let server_id = match cmd.guild_id() {
+        Some(server_id) => server_id,
+        None => {
+            cmd.reply(&ctx.http, "This command can only be run in a server.").await?;
+            return Ok(());
+        }
+    };
+
+    let db = db!(ctx);
+    let gains = db.top_gains(server_id, since).await?;
+    if gains.is_empty() {
+        cmd.reply(&ctx.http, "Nobody has earned any experience in that time.").await?;
+        return Ok(());
+    }
+
+    let page = cmd.arg::<i32>("page").unwrap_or(1).max(1);
+    paginate(ctx, cmd, &GainLeaderboard { title, gains }, page as usize - 1).await?;
+
+    Ok(())
+}
+
+#[command]
+pub async fn week(ctx: &Context, cmd: &Invocation) -> CommandResult {
+    show_gains(ctx, cmd, Utc::now().naive_utc() - Duration::days(7), "Top Users This Week".to_string()).await
+}
+
+#[command]
+pub async fn month(ctx: &Context, cmd: &Invocation) -> CommandResult {
+    show_gains(ctx, cmd, Utc::now().naive_utc() - Duration::days(30), "Top Users This Month".to_string()).await
+}
+
+#[command]
+pub async fn since(ctx: &Context, cmd: &Invocation) -> CommandResult {
+    let date = cmd.arg::<String>("date").unwrap_or_default();
+    let start = match NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d").ok().and_then(|d| d.and_hms_opt(0, 0, 0)) {
+        Some(start) if start <= Utc::now().naive_utc() => start,
+        Some(_) => {
+            cmd.reply(&ctx.http, "That date hasn't happened yet.").await?;
+            return Ok(());
+        },
+        None => {
+            cmd.reply(&ctx.http, "The date should look like 2022-01-24.").await?;
+            return Ok(());
+        }
+    };
+
+    show_gains(ctx, cmd, start, format!("Top Users Since {}", start.format("%B %-d, %Y"))).await
+}
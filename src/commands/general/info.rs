@@ -1,19 +1,79 @@
+use std::time::Instant;
 use serenity::{
     client::Context,
-    model::channel::Message,
-    framework::standard::{
-        CommandResult,
-        macros::{
-            command
-        }
-    }
+    framework::standard::CommandResult
 };
+use crate::services::cow_framework::{command, CowCommand, Invocation};
+use crate::services::cache::DbCache;
+use crate::services::guild_settings::GuildSettings;
+use crate::services::supervisor::{JobStatus, Supervisor};
+
+pub static INFO_COMMAND: CowCommand = CowCommand::new("info", "Info about this bot.", info);
 
 #[command]
-#[description = "Info about this bot."]
-pub async fn info(ctx: &Context, msg: &Message) -> CommandResult {
+pub async fn info(ctx: &Context, cmd: &Invocation) -> CommandResult {
     const VERSION: Option<&str> = option_env!("CARGO_PKG_VERSION");
     let content = format!("Cow v{} - A Discord bot written by HelloAndrew and DoggySazHi", VERSION.unwrap_or("<unknown>"));
-    msg.channel_id.send_message(&ctx.http, |m| {m.content(content)}).await?;
+    cmd.send_message(&ctx.http, |m| {m.content(content)}).await?;
+    Ok(())
+}
+
+pub static CACHESTATS_COMMAND: CowCommand = CowCommand {
+    owners_only: true,
+    ..CowCommand::new("cachestats", "How often the caches in front of the database are being used.", cachestats)
+};
+
+#[command]
+pub async fn cachestats(ctx: &Context, cmd: &Invocation) -> CommandResult {
+    let mut stats = DbCache::from_context(ctx).await.stats();
+    stats.push(GuildSettings::from_context(ctx).await.stats());
+
+    cmd.send_message(&ctx.http, |m| m.embed(|e| {
+        e.title("Cache Stats");
+
+        for stat in &stats {
+            e.field(stat.name, format!("{} hits, {} misses ({:.1}%)\n{} entries", stat.hits, stat.misses, stat.hit_rate(), stat.entries), false);
+        }
+
+        e
+    })).await?;
+
+    Ok(())
+}
+
+pub static JOBS_COMMAND: CowCommand = CowCommand {
+    owners_only: true,
+    ..CowCommand::new("jobs", "The state of the tasks running in the background.", jobs)
+};
+
+#[command]
+pub async fn jobs(ctx: &Context, cmd: &Invocation) -> CommandResult {
+    let health = Supervisor::from_context(ctx).await.health();
+
+    cmd.send_message(&ctx.http, |m| m.embed(|e| {
+        e.title("Background Jobs");
+
+        if health.is_empty() {
+            e.description("Nothing is running in the background.");
+        }
+
+        for job in &health {
+            let status = match job.status {
+                JobStatus::Running => "Running".to_string(),
+                JobStatus::Restarting { at } => format!("Restarting in {}s", at.saturating_duration_since(Instant::now()).as_secs()),
+                JobStatus::Finished => "Finished".to_string(),
+                JobStatus::Stopped => "Stopped".to_string()
+            };
+
+            let mut content = format!("{}, restarted {} time(s)", status, job.restarts);
+            if let Some(panic) = &job.last_panic {
+                content += &format!("\nLast panic: `{}`", panic);
+            }
+            e.field(job.name, content, false);
+        }
+
+        e
+    })).await?;
+
     Ok(())
-}
\ No newline at end of file
+}
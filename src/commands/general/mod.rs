@@ -1,15 +1,33 @@
 mod info;
 mod rank;
 mod ban;
+mod export;
+mod gains;
 
-use serenity::framework::standard::macros::group;
-
+use crate::services::cow_framework::CowGroup;
 use info::*;
 use rank::*;
 use ban::*;
+use export::*;
+use gains::*;
+
+pub static GENERAL_GROUP: CowGroup = CowGroup {
+    name: "General",
+    prefixes: &[],
+    description: "General commands for miscellaneous tasks.",
+    summary: "Basic commands",
+    default_command: None,
+    commands: &[&INFO_COMMAND, &CACHESTATS_COMMAND, &JOBS_COMMAND, &RANK_COMMAND, &DISABLEXP_COMMAND, &BANGENSHINPLAYERS_COMMAND, &BANLEAGUEPLAYERS_COMMAND, &BANVALORANTPLAYERS_COMMAND],
+    sub_groups: &[]
+};
 
-#[group]
-#[commands(info, rank, disablexp, levels, bangenshinplayers, banleagueplayers, banvalorantplayers)]
-#[description = "General commands for miscellaneous tasks."]
-#[summary = "Basic commands"]
-struct General;
+// `levels` on its own still shows the leaderboard.
+pub static LEVELS_GROUP: CowGroup = CowGroup {
+    name: "Levels",
+    prefixes: &["levels"],
+    description: "See who has the most experience on the server.",
+    summary: "Leaderboard",
+    default_command: Some(&LEVELS_COMMAND),
+    commands: &[&LEVELS_COMMAND, &WEEK_COMMAND, &MONTH_COMMAND, &SINCE_COMMAND, &EXPORT_COMMAND],
+    sub_groups: &[]
+};
@@ -1,34 +1,44 @@
+use std::borrow::Cow;
+use async_trait::async_trait;
+use log::error;
 use serenity::{
+    builder::CreateEmbed,
     client::Context,
+    http::AttachmentType,
     model::{
-        channel::Message,
         id::{
             UserId,
             GuildId
         },
+        permissions::Permissions,
         user::User
     },
-    framework::standard::{
-        CommandResult,
-        macros::{
-            command
-        },
-        Args
-    },
+    framework::standard::CommandResult,
     utils::MessageBuilder
 };
 use crate::{Database, db};
-use log::{error};
+use crate::services::cow_framework::{command, paginate, CowCommand, CommandOption, OptionKind, Invocation, PageSource};
+use crate::services::cache::DbCache;
+use crate::services::guild_settings::GuildSettings;
+use crate::services::rank_card::{fetch_avatar, RankCard, DEFAULT_ACCENT};
+use crate::models::error::CowError;
+use crate::models::xp_settings::XpSettings;
 
-async fn rank_embed(ctx: &Context, msg: &Message, server_id: &GuildId, user: &User) {
+async fn rank_embed(ctx: &Context, cmd: &Invocation, server_id: &GuildId, user: &User) -> Result<(), CowError> {
     let db = db!(ctx);
 
-    let experience = db.get_xp(*server_id, user.id).await.unwrap();
+    let experience = db.get_xp(*server_id, user.id).await?;
     let xp = experience.xp;
     let level = experience.level;
-    let next_level_xp = db.calculate_level(level).await.unwrap();
+    let guild_settings = GuildSettings::from_context(ctx).await;
+    let settings = guild_settings.xp_settings(*server_id).await?;
+    let progress = if settings.at_cap(level) {
+        format!("{} (Max level)", xp)
+    } else {
+        format!("{}/{}", xp, settings.curve.experience_for_level(level))
+    };
 
-    let current_role = db.get_highest_role(*server_id, level).await.unwrap();
+    let current_role = db.get_highest_role(*server_id, level).await?;
     let mut current_role_str: String = String::from("No role");
     if let Some(current_role_id) = current_role {
         current_role_str = format!("Current role: <@&{}>", current_role_id);
@@ -39,12 +49,43 @@ async fn rank_embed(ctx: &Context, msg: &Message, server_id: &GuildId, user: &Us
         pfp_url = pfp_custom;
     }
 
+    let rank = db.rank_within_members(*server_id, user.id).await?;
     let mut rank_str = String::from("(Unranked)");
-    if let Some(rank) = db.rank_within_members(*server_id, user.id).await.unwrap() {
+    if let Some(rank) = rank {
         rank_str = format!("#{}", rank);
     }
 
-    if let Err(ex) = msg.channel_id.send_message(&ctx.http, |m| {m.embed(|e| {
+    if guild_settings.rank_card(*server_id).await? {
+        let accent = match current_role {
+            Some(role_id) => ctx.cache.role(*server_id, role_id).await
+                .map(|role| role.colour)
+                .filter(|colour| colour.0 != 0)
+                .map(|colour| (colour.r(), colour.g(), colour.b()))
+                .unwrap_or(DEFAULT_ACCENT),
+            None => DEFAULT_ACCENT
+        };
+
+        let card = RankCard {
+            username: user.tag(),
+            avatar: fetch_avatar(user).await,
+            level,
+            xp,
+            needed: if settings.at_cap(level) { None } else { Some(settings.curve.experience_for_level(level)) },
+            rank,
+            accent
+        };
+
+        match card.render() {
+            Ok(png) => {
+                cmd.send_file(&ctx.http, "", AttachmentType::Bytes { data: Cow::Owned(png), filename: "rank.png".to_string() }).await?;
+                return Ok(());
+            },
+            // The embed has everything the card does, so it can stand in.
+            Err(ex) => error!("Failed to draw a rank card: {}", ex)
+        }
+    }
+
+    cmd.send_message(&ctx.http, |m| {m.embed(|e| {
         e
             .title(
                 MessageBuilder::new()
@@ -56,102 +97,132 @@ async fn rank_embed(ctx: &Context, msg: &Message, server_id: &GuildId, user: &Us
             )
             .description(current_role_str)
             .field("Level", level, true)
-            .field("XP", format!("{}/{}", xp, next_level_xp), true)
+            .field("XP", progress, true)
             .field("Rank", rank_str, true)
             .thumbnail(pfp_url)
-    })}).await {
-        error!("Failed to send embed: {}", ex);
-    }
+    })}).await?;
+
+    Ok(())
 }
 
+pub static RANK_COMMAND: CowCommand = CowCommand {
+    options: &[CommandOption::new("user", "Someone else to look up.", OptionKind::User)],
+    only_in_guilds: true,
+    ..CowCommand::new("rank", "Get your current rank.", rank)
+};
+
 #[command]
-#[description = "Get your current rank."]
-#[only_in(guilds)]
-pub async fn rank(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
-    let other = args.single::<UserId>();
-    if let Some(server_id) = msg.guild_id {
-        if let Ok(other_id) = other {
+pub async fn rank(ctx: &Context, cmd: &Invocation) -> CommandResult {
+    let other = cmd.arg::<UserId>("user");
+    if let Some(server_id) = cmd.guild_id() {
+        if let Some(other_id) = other {
             if let Ok(other_user) = other_id.to_user(&ctx.http).await {
-                rank_embed(ctx, msg, &server_id, &other_user).await;
+                rank_embed(ctx, cmd, &server_id, &other_user).await?;
             } else {
-                msg.channel_id.say(&ctx.http, "Could not find user...").await?;
+                cmd.say(&ctx.http, "Could not find user...").await?;
             }
         } else {
-            rank_embed(ctx, msg, &server_id, &msg.author).await;
+            rank_embed(ctx, cmd, &server_id, cmd.author()).await?;
         }
     } else {
-        msg.reply(&ctx.http, "This command can only be run in a server.").await?;
+        cmd.reply(&ctx.http, "This command can only be run in a server.").await?;
     }
 
     Ok(())
 }
 
+pub static DISABLEXP_COMMAND: CowCommand = CowCommand {
+    aliases: &["enablexp"],
+    only_in_guilds: true,
+    permissions: Permissions::ADMINISTRATOR,
+    ..CowCommand::new("disablexp", "Disable/enable experience from being collected in the current channel.", disablexp)
+};
+
 #[command]
-#[description = "Disable/enable experience from being collected in the current channel."]
-#[only_in(guilds)]
-#[required_permissions("ADMINISTRATOR")]
-#[aliases("enablexp")]
-pub async fn disablexp(ctx: &Context, msg: &Message) -> CommandResult {
-    let db = db!(ctx);
-    if let Some(server_id) = msg.guild_id {
+pub async fn disablexp(ctx: &Context, cmd: &Invocation) -> CommandResult {
+    let cache = DbCache::from_context(ctx).await;
+    if let Some(server_id) = cmd.guild_id() {
         let mut content: String;
-        match db.toggle_channel_xp(server_id, msg.channel_id).await {
-            Ok(toggle) => {
-                if toggle {
-                    content = "Disabled".to_string();
-                } else {
-                    content = "Enabled".to_string();
-                }
-                content += &*format!(" collecting experience in <#{}>.", msg.channel_id.as_u64());
-            },
-            Err(ex) => {
-                content = "Failed to toggle channel xp status.".to_string();
-                error!("Failed to toggle channel xp status: {}", ex);
-            }
+        if cache.toggle_channel_xp(server_id, cmd.channel_id()).await? {
+            content = "Disabled".to_string();
+        } else {
+            content = "Enabled".to_string();
         }
+        content += &*format!(" collecting experience in <#{}>.", cmd.channel_id().as_u64());
 
-        msg.channel_id.send_message(&ctx.http, |m| {m.content(content)}).await?;
+        cmd.send_message(&ctx.http, |m| {m.content(content)}).await?;
     } else {
-        msg.reply(&ctx.http, "This command can only be run in a server.").await?;
+        cmd.reply(&ctx.http, "This command can only be run in a server.").await?;
     }
 
     Ok(())
 }
 
+pub static LEVELS_COMMAND: CowCommand = CowCommand {
+    options: &[CommandOption::new("page", "The page of the leaderboard to show.", OptionKind::Integer)],
+    only_in_guilds: true,
+    ..CowCommand::new("top", "Get the current rankings in the server.", levels)
+};
+
+// top_members always hands back this many.
+const LEADERBOARD_PAGE_SIZE: i64 = 10;
+
+struct Leaderboard {
+    server_id: GuildId,
+    settings: XpSettings,
+    pages: usize
+}
+
+#[async_trait]
+impl PageSource for Leaderboard {
+    fn page_count(&self) -> usize {
+        self.pages
+    }
+
+    async fn page(&self, ctx: &Context, index: usize) -> Result<CreateEmbed, CowError> {
+        let db = db!(ctx);
+        let pagination = db.top_members(self.server_id, index as i32).await?;
+        let content = pagination.members.into_iter()
+            .enumerate()
+            .map(|(position, member)| {
+                let progress = if self.settings.at_cap(member.exp.level) {
+                    format!("{} xp (max level)", member.exp.xp)
+                } else {
+                    format!("{}/{} xp", member.exp.xp, self.settings.curve.experience_for_level(member.exp.level))
+                };
+                format!("`#{}` <@{}> - Level {}, {}", position as i64 + LEADERBOARD_PAGE_SIZE * index as i64 + 1, member.id, member.exp.level, progress)
+            })
+            .reduce(|a, b| {format!("{}\n{}", a, b)})
+            .unwrap_or_else(|| "There is nothing on this page.".to_string());
+
+        let mut embed = CreateEmbed::default();
+        embed.title("Top Users").description(content);
+        Ok(embed)
+    }
+
+    fn can_jump(&self) -> bool {
+        true
+    }
+
+    async fn page_of(&self, ctx: &Context, user_id: UserId) -> Result<Option<usize>, CowError> {
+        let db = db!(ctx);
+        Ok(db.rank_within_members(self.server_id, user_id).await?.map(|rank| ((rank - 1) / LEADERBOARD_PAGE_SIZE) as usize))
+    }
+}
+
 #[command]
-#[description = "Get the current rankings in the server."]
-#[only_in(guilds)]
-pub async fn levels(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+pub async fn levels(ctx: &Context, cmd: &Invocation) -> CommandResult {
     let db = db!(ctx);
-    if let Some(server_id) = msg.guild_id {
-        let page = args.single::<i32>().unwrap_or(1).max(1);
-        match db.top_members(server_id, page - 1).await {
-            Ok(pagination) => {
-                let content = pagination.members.into_iter()
-                    .enumerate()
-                    .into_iter()
-                    .map(|o| {
-                        let (index, member) = o;
-                        format!("`#{}` <@{}> - Level {}, {} xp", (index as i32) + 10 * (page - 1) + 1, member.id, member.exp.level, member.exp.xp)
-                    })
-                    .reduce(|a, b| {format!("{}\n{}", a, b)})
-                    .unwrap_or_else(|| "There is nothing on this page.".to_string());
-                msg.channel_id.send_message(&ctx.http, |m| {
-                    m.embed(|e|
-                        e
-                            .title("Top Users")
-                            .description(content)
-                            .footer(|e| e.text(format!("Page {}/{}", page, pagination.last_page)))
-                    )}).await?;
-            },
-            Err(ex) => {
-                msg.channel_id.say(&ctx.http, "Failed to get rankings.".to_string()).await?;
-                error!("Failed to get rankings: {}", ex);
-            }
-        }
+    if let Some(server_id) = cmd.guild_id() {
+        let page = cmd.arg::<i32>("page").unwrap_or(1).max(1);
+        let settings = GuildSettings::from_context(ctx).await.xp_settings(server_id).await?;
+        // The page count is only worked out once; it's fine if it goes a little stale while someone is flipping through.
+        let pages = db.top_members(server_id, 0).await?.last_page.max(1) as usize;
+        let leaderboard = Leaderboard { server_id, settings, pages };
+        paginate(ctx, cmd, &leaderboard, page as usize - 1).await?;
     } else {
-        msg.reply(&ctx.http, "This command can only be run in a server.").await?;
+        cmd.reply(&ctx.http, "This command can only be run in a server.").await?;
     }
 
     Ok(())
-}
\ No newline at end of file
+}
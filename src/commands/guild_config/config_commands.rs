@@ -0,0 +1,146 @@
+use serenity::{
+    framework::standard::CommandResult,
+    model::permissions::Permissions, client::Context
+};
+use serenity::utils::MessageBuilder;
+use crate::services::cow_framework::{command, CowCommand, CommandOption, OptionKind, Invocation};
+use crate::services::guild_settings::{GuildSettings, SettingDefinition, SETTINGS, find_setting};
+
+fn valid_keys() -> String {
+    SETTINGS.iter().map(|s| format!("`{}`", s.key)).collect::<Vec<_>>().join(", ")
+}
+
+fn display_value(setting: &SettingDefinition, value: Option<&str>) -> String {
+    match value {
+        Some(value) => MessageBuilder::new().push_mono_safe(value).build(),
+        None if setting.default.is_empty() => "Not set (using the bot default)".to_string(),
+        None => format!("{} (default)", MessageBuilder::new().push_mono_safe(setting.default).build())
+    }
+}
+
+pub static LIST_COMMAND: CowCommand = CowCommand {
+    only_in_guilds: true,
+    ..CowCommand::new("list", "List every setting for this server.", list)
+};
+
+#[command]
+pub async fn list(ctx: &Context, cmd: &Invocation) -> CommandResult {
+    if let Some(server_id) = cmd.guild_id() {
+        let settings = GuildSettings::from_context(ctx).await;
+
+        let mut values = Vec::new();
+        for setting in SETTINGS {
+            values.push((setting, settings.get(server_id, setting.key).await?));
+        }
+
+        cmd.send_message(&ctx.http, |m| m.embed(|e| {
+            e
+                .title("Server Settings")
+                .description("Change these with `config set <key> <value>`.");
+
+            for (setting, value) in &values {
+                e.field(setting.key, format!("{}\n{}", display_value(setting, value.as_deref()), setting.description), false);
+            }
+
+            e
+        })).await?;
+    } else {
+        cmd.reply(&ctx.http, "This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
+
+pub static GET_COMMAND: CowCommand = CowCommand {
+    options: &[CommandOption::new("key", "The setting to look up.", OptionKind::String).required()],
+    only_in_guilds: true,
+    ..CowCommand::new("get", "Get the value of a setting for this server.", get)
+};
+
+#[command]
+pub async fn get(ctx: &Context, cmd: &Invocation) -> CommandResult {
+    if let Some(server_id) = cmd.guild_id() {
+        let key = cmd.arg::<String>("key").unwrap_or_default();
+
+        if let Some(setting) = find_setting(key.trim()) {
+            let settings = GuildSettings::from_context(ctx).await;
+
+            let value = settings.get(server_id, setting.key).await?;
+            cmd.say(&ctx.http, format!("`{}` is {}.", setting.key, display_value(setting, value.as_deref()))).await?;
+        } else {
+            cmd.say(&ctx.http, format!("That isn't a setting. The settings are {}.", valid_keys())).await?;
+        }
+    } else {
+        cmd.reply(&ctx.http, "This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
+
+pub static SET_COMMAND: CowCommand = CowCommand {
+    options: &[
+        CommandOption::new("key", "The setting to change.", OptionKind::String).required(),
+        CommandOption::new("value", "The new value.", OptionKind::String).required()
+    ],
+    only_in_guilds: true,
+    permissions: Permissions::ADMINISTRATOR,
+    ..CowCommand::new("set", "Change a setting for this server.", set)
+};
+
+#[command]
+pub async fn set(ctx: &Context, cmd: &Invocation) -> CommandResult {
+    if let Some(server_id) = cmd.guild_id() {
+        let key = cmd.arg::<String>("key").unwrap_or_default();
+        let value = cmd.arg::<String>("value").unwrap_or_default();
+
+        if let Some(setting) = find_setting(key.trim()) {
+            match (setting.validate)(&value) {
+                Ok(value) => {
+                    let settings = GuildSettings::from_context(ctx).await;
+
+                    settings.set(server_id, setting.key, &value).await?;
+                    cmd.say(&ctx.http, format!("`{}` is now {}.", setting.key, display_value(setting, Some(&value)))).await?;
+                }
+                Err(reason) => {
+                    cmd.say(&ctx.http, reason).await?;
+                }
+            }
+        } else {
+            cmd.say(&ctx.http, format!("That isn't a setting. The settings are {}.", valid_keys())).await?;
+        }
+    } else {
+        cmd.reply(&ctx.http, "This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
+
+pub static RESET_COMMAND: CowCommand = CowCommand {
+    options: &[CommandOption::new("key", "The setting to put back to its default.", OptionKind::String).required()],
+    only_in_guilds: true,
+    permissions: Permissions::ADMINISTRATOR,
+    ..CowCommand::new("reset", "Put a setting back to its default for this server.", reset)
+};
+
+#[command]
+pub async fn reset(ctx: &Context, cmd: &Invocation) -> CommandResult {
+    if let Some(server_id) = cmd.guild_id() {
+        let key = cmd.arg::<String>("key").unwrap_or_default();
+
+        if let Some(setting) = find_setting(key.trim()) {
+            let settings = GuildSettings::from_context(ctx).await;
+
+            if settings.reset(server_id, setting.key).await? {
+                cmd.say(&ctx.http, format!("`{}` is back to {}.", setting.key, display_value(setting, None))).await?;
+            } else {
+                cmd.say(&ctx.http, format!("`{}` was already using the default.", setting.key)).await?;
+            }
+        } else {
+            cmd.say(&ctx.http, format!("That isn't a setting. The settings are {}.", valid_keys())).await?;
+        }
+    } else {
+        cmd.reply(&ctx.http, "This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
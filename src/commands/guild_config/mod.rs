@@ -0,0 +1,16 @@
+mod config_commands;
+pub mod settings_db;
+
+use crate::services::cow_framework::CowGroup;
+
+use config_commands::*;
+
+pub static CONFIG_GROUP: CowGroup = CowGroup {
+    name: "Config",
+    prefixes: &["config"],
+    description: "View and change the bot's settings for this server.",
+    summary: "Server settings",
+    default_command: Some(&LIST_COMMAND),
+    commands: &[&LIST_COMMAND, &GET_COMMAND, &SET_COMMAND, &RESET_COMMAND],
+    sub_groups: &[]
+};
@@ -0,0 +1,90 @@
+use serenity::model::id::GuildId;
+use async_trait::async_trait;
+use rusqlite::params;
+
+use crate::models::error::CowError;
+use crate::services::database::{SqlServerDatabase, SqliteDatabase};
+use crate::services::database::sql_server::{to_decimal, column};
+use crate::services::database::sqlite::to_sql_id;
+
+#[async_trait]
+pub trait SettingsStore {
+    async fn get_guild_settings(&self, server_id: GuildId) -> Result<Vec<(String, String)>, CowError>;
+    async fn set_guild_setting(&self, server_id: GuildId, key: &str, value: &str) -> Result<(), CowError>;
+    // False if the setting was never set.
+    async fn remove_guild_setting(&self, server_id: GuildId, key: &str) -> Result<bool, CowError>;
+}
+
+#[async_trait]
+impl SettingsStore for SqlServerDatabase {
+    async fn get_guild_settings(&self, server_id: GuildId) -> Result<Vec<(String, String)>, CowError> {
+        let mut conn = self.pool.get().await?;
+        let server = to_decimal(server_id.0);
+        let res = conn.query(
+            "SELECT [key], value FROM [Settings].[Guild] WHERE guild_id = @P1",
+            &[&server])
+            .await?
+            .into_first_result()
+            .await?
+            .into_iter()
+            .map(|row| Ok((column::<&str>(&row, 0)?.to_string(), column::<&str>(&row, 1)?.to_string())))
+            .collect::<Result<Vec<_>, CowError>>()?;
+
+        Ok(res)
+    }
+
+    async fn set_guild_setting(&self, server_id: GuildId, key: &str, value: &str) -> Result<(), CowError> {
+        let mut conn = self.pool.get().await?;
+        let server = to_decimal(server_id.0);
+
+        conn.query(
+            "EXEC [Settings].[SetValue] @guild_id = @P1, @key = @P2, @value = @P3",
+            &[&server, &key, &value])
+            .await?;
+
+        Ok(())
+    }
+
+    async fn remove_guild_setting(&self, server_id: GuildId, key: &str) -> Result<bool, CowError> {
+        let mut conn = self.pool.get().await?;
+        let server = to_decimal(server_id.0);
+
+        let total = conn.execute(
+            "DELETE FROM [Settings].[Guild] WHERE guild_id = @P1 AND [key] = @P2",
+            &[&server, &key])
+            .await?.total();
+
+        Ok(total > 0)
+    }
+}
+
+#[async_trait]
+impl SettingsStore for SqliteDatabase {
+    async fn get_guild_settings(&self, server_id: GuildId) -> Result<Vec<(String, String)>, CowError> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare("SELECT key, value FROM settings_guild WHERE guild_id = ?1")?;
+        let res = stmt.query_map(params![to_sql_id(server_id.0)], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<(String, String)>, _>>()?;
+
+        Ok(res)
+    }
+
+    // Replaces [Settings].[SetValue].
+    async fn set_guild_setting(&self, server_id: GuildId, key: &str, value: &str) -> Result<(), CowError> {
+        let conn = self.conn();
+        conn.execute(
+            "INSERT INTO settings_guild (guild_id, key, value) VALUES (?1, ?2, ?3) ON CONFLICT (guild_id, key) DO UPDATE SET value = excluded.value",
+            params![to_sql_id(server_id.0), key, value])?;
+
+        Ok(())
+    }
+
+    async fn remove_guild_setting(&self, server_id: GuildId, key: &str) -> Result<bool, CowError> {
+        let conn = self.conn();
+        let res = conn.execute(
+            "DELETE FROM settings_guild WHERE guild_id = ?1 AND key = ?2",
+            params![to_sql_id(server_id.0), key])?;
+
+        Ok(res > 0)
+    }
+}
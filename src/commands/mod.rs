@@ -1,57 +1,154 @@
 mod general;
-mod rank_config;
+pub mod rank_config;
 mod timeout;
 pub mod ucm;
 pub mod cowboard;
 mod music;
+pub mod guild_config;
 
-use std::{collections::HashSet};
+use std::collections::HashSet;
 use std::sync::Arc;
-use log::error;
+use std::time::Duration;
+use log::{error, info};
 
-use serenity:: {
+use serenity::{
     model::{
         id::UserId,
-        channel::Message
+        channel::Message,
+        permissions::Permissions
     },
-    framework:: {
-        Framework,
-        standard::{
-            macros::{
-                hook,
-                help
-            },
-            buckets::LimitedFor,
-            StandardFramework, HelpOptions, CommandGroup, CommandResult, help_commands, Args, DispatchError
-        }
-    },
-    client::Context
+    framework::standard::{CommandResult, macros::hook},
+    client::Context,
+    utils::Colour
 };
 
-use crate::commands::general::GENERAL_GROUP;
+use crate::services::cow_framework::{command, Bucket, CowFramework, CowCommand, CowGroup, CommandOption, ChoiceValue, DispatchError, Invocation, OptionKind};
+use crate::commands::general::{GENERAL_GROUP, LEVELS_GROUP};
 use crate::commands::rank_config::RANKCONFIG_GROUP;
 use crate::commands::timeout::TIMEOUT_GROUP;
 use crate::commands::ucm::UCM_GROUP;
 use crate::commands::cowboard::COWBOARD_GROUP;
 use crate::commands::music::MUSIC_GROUP;
+use crate::commands::guild_config::CONFIG_GROUP;
+use crate::services::guild_settings::GuildSettings;
+use crate::models::error::CowError;
+use crate::util::error_id;
+
+pub static GROUPS: [&CowGroup; 9] = [&HELP_GROUP, &GENERAL_GROUP, &LEVELS_GROUP, &RANKCONFIG_GROUP, &TIMEOUT_GROUP, &UCM_GROUP, &COWBOARD_GROUP, &MUSIC_GROUP, &CONFIG_GROUP];
+
+static HELP_COMMAND: CowCommand = CowCommand {
+    options: &[CommandOption::new("command", "The command you want to learn more about.", OptionKind::String)],
+    ..CowCommand::new("help", "Cow help command.", cow_help)
+};
+
+static HELP_GROUP: CowGroup = CowGroup {
+    name: "Help",
+    prefixes: &[],
+    description: "Learn how to use the bot.",
+    summary: "Help",
+    default_command: None,
+    commands: &[&HELP_COMMAND],
+    sub_groups: &[]
+};
+
+// Lists every command in a group, including the ones in sub-groups, with the prefixes needed to run them.
+fn list_commands(group: &CowGroup, path: &str, permissions: Option<Permissions>, out: &mut Vec<String>) {
+    let path = match group.prefixes.first() {
+        Some(prefix) if path.is_empty() => prefix.to_string(),
+        Some(prefix) => format!("{} {}", path, prefix),
+        None => path.to_string()
+    };
+
+    for command in group.commands {
+        let name = if path.is_empty() { command.name.to_string() } else { format!("{} {}", path, command.name) };
+        if permissions.map(|p| !p.contains(command.permissions)).unwrap_or(false) {
+            out.push(format!("~~`{}`~~", name));
+        } else {
+            out.push(format!("`{}`", name));
+        }
+    }
+
+    for sub_group in group.sub_groups {
+        list_commands(sub_group, &path, permissions, out);
+    }
+}
+
+// Walks down the groups the same way the framework does, so "rc add" finds the same command as running it.
+fn find_help(words: &[&str]) -> Option<&'static CowCommand> {
+    fn find_in_group(group: &'static CowGroup, words: &[&str]) -> Option<&'static CowCommand> {
+        match words.first() {
+            Some(word) => match group.sub_group(word) {
+                Some(sub_group) => find_in_group(sub_group, &words[1..]),
+                None => group.command(word)
+            },
+            None => group.default_command
+        }
+    }
+
+    let first = words.first()?;
+    GROUPS.iter().find_map(|group| {
+        if group.prefixes.is_empty() {
+            group.command(first)
+        } else if group.matches(first) {
+            find_in_group(group, &words[1..])
+        } else {
+            None
+        }
+    })
+}
+
+#[command]
+async fn cow_help(ctx: &Context, cmd: &Invocation) -> CommandResult {
+    let query = cmd.arg::<String>("command").unwrap_or_default();
+    let words = query.split_whitespace().collect::<Vec<_>>();
+
+    if words.is_empty() {
+        let permissions = cmd.permissions(ctx).await;
+
+        cmd.send_message(&ctx.http, |m| m.embed(|e| {
+            e
+                .title("Cow help command")
+                .description("Add the command you want to learn more about to the help command.");
+
+            for group in GROUPS.iter() {
+                let mut commands = Vec::new();
+                list_commands(group, "", permissions, &mut commands);
+                e.field(group.name, commands.join(", "), false);
+            }
+
+            if cmd.guild_id().is_some() {
+                e.footer(|f| f.text("Strikethrough commands require elevated permissions."));
+            }
+
+            e
+        })).await?;
+
+        return Ok(());
+    }
+
+    if let Some(command) = find_help(&words) {
+        let usage = format!("{} {}", words.join(" "), command.usage());
+
+        cmd.send_message(&ctx.http, |m| m.embed(|e| {
+            e
+                .title(command.name)
+                .description(command.description)
+                .field("Usage", format!("`{}`", usage.trim_end()), false);
+
+            if !command.aliases.is_empty() {
+                e.field("Aliases", command.aliases.iter().map(|a| format!("`{}`", a)).collect::<Vec<_>>().join(", "), false);
+            }
+
+            for option in command.options {
+                e.field(option.name, format!("{} ({}{})", option.description, option.kind.name(), if option.required { "" } else { ", optional" }), false);
+            }
+
+            e
+        })).await?;
+    } else {
+        cmd.say(&ctx.http, format!("Could not find command: `{}`.", query)).await?;
+    }
 
-#[help]
-#[individual_command_tip = "Cow help command\n\n\
-Add the command you want to learn more about to the help command\n"]
-#[command_not_found_text = "Could not find command: `{}`."]
-#[max_levenshtein_distance(2)]
-#[lacking_permissions = "Nothing"]
-#[strikethrough_commands_tip_in_dm = ""]
-#[strikethrough_commands_tip_in_guild = "Strikethrough commands require elevated permissions."]
-async fn cow_help(
-    context: &Context,
-    msg: &Message,
-    args: Args,
-    help_options: &'static HelpOptions,
-    groups: &[&'static CommandGroup],
-    owners: HashSet<UserId>,
-) -> CommandResult {
-    help_commands::with_embeds(context, msg, args, help_options, groups, owners).await;
     Ok(())
 }
 
@@ -60,36 +157,105 @@ async fn non_command(ctx: &Context, msg: &Message) {
     crate::message_handler::non_command(ctx, msg).await;
 }
 
+// Servers can pick their own prefix with "config set prefix"; None falls back to the bot default.
 #[hook]
-async fn on_error(ctx: &Context, msg: &Message, error: DispatchError) {
-    if let DispatchError::Ratelimited(info) = error {
-        if info.is_first_try {
+async fn guild_prefix(ctx: &Context, msg: &Message) -> Option<String> {
+    let server_id = msg.guild_id?;
+    let settings = GuildSettings::from_context(ctx).await;
+
+    match settings.get(server_id, "prefix").await {
+        Ok(prefix) => prefix,
+        Err(ex) => {
+            error!("Failed to get the prefix for {}: {}", server_id, ex);
+            None
+        }
+    }
+}
+
+// Every failed command gets the same embed, with an ID that also goes in the log so mods can point at the exact error.
+async fn send_error(ctx: &Context, cmd: &Invocation, id: &str, description: impl ToString) {
+    let sent = cmd.send_message(&ctx.http, |m| m.embed(|e| e
+        .title(format!("Couldn't run `{}`", cmd.command.name))
+        .description(description)
+        .colour(Colour::RED)
+        .footer(|f| f.text(format!("Error ID: {}", id)))
+    )).await;
+
+    if let Err(ex) = sent {
+        error!("[{}] Failed to send error message for command {}: {}", id, cmd.command.name, ex);
+    }
+}
+
+#[hook]
+async fn on_error(ctx: &Context, cmd: &Invocation, error: DispatchError) {
+    let content = match &error {
+        DispatchError::Ratelimited { remaining, is_first_try } => {
+            if !is_first_try {
+                return;
+            }
             // Why round up when we can add one?
-            if let Err(ex) = msg.channel_id.say(&ctx.http, &format!("This command is rate-limited, please try this again in {} seconds.", info.as_secs() + 1)).await {
-                error!("Failed to send rate-limit message: {}", ex);
+            format!("This command is rate-limited, please try this again in {} seconds.", remaining.as_secs() + 1)
+        },
+        DispatchError::NotEnoughArguments { min, given } => format!("This command needs at least {} argument(s), but you gave {}. The usage is `{} {}`.", min, given, cmd.command.name, cmd.command.usage()),
+        DispatchError::InvalidArgument(option) => {
+            if option.choices.is_empty() {
+                format!("`{}` should be a {}.", option.name, option.kind.name())
+            } else {
+                let choices = option.choices.iter()
+                    .map(|c| match c.value {
+                        ChoiceValue::String(s) => format!("`{}`", s),
+                        ChoiceValue::Integer(i) => format!("`{}`", i)
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!("`{}` should be one of {}.", option.name, choices)
             }
-        }
+        },
+        DispatchError::OnlyForGuilds => "This command can only be run in a server.".to_string(),
+        DispatchError::OnlyForOwners => "Only the owners of this bot can run this command.".to_string(),
+        DispatchError::LackingPermissions(permissions) => format!("You need the {} permission(s) to run this command.", permissions.get_permission_names().join(", ")),
+        DispatchError::CheckFailed { reason, .. } => reason.clone()
+    };
+
+    // These are the user's mistake rather than ours, so they don't need to be errors in the log.
+    let id = error_id();
+    match error {
+        DispatchError::CheckFailed { name, .. } => info!("[{}] {} couldn't run {}: failed the {} check", id, cmd.author().tag(), cmd.command.name, name),
+        error => info!("[{}] {} couldn't run {}: {:?}", id, cmd.author().tag(), cmd.command.name, error)
     }
+    send_error(ctx, cmd, &id, content).await;
 }
 
-pub async fn get_framework(pref: &str, app_id: UserId, owners: HashSet<UserId>) -> Arc<Box<dyn Framework + Sync + std::marker::Send>> {
-    Arc::new(Box::new(StandardFramework::new()
-        .configure(|c| c
-            .prefix(pref)
-            .on_mention(Some(app_id))
-            .owners(owners)
-        )
+#[hook]
+async fn after(ctx: &Context, cmd: &Invocation, result: CommandResult) {
+    if let Err(ex) = result {
+        let id = error_id();
+        error!("[{}] Command {} failed: {}", id, cmd.command.name, ex);
+
+        // Anything we know how to explain gets the same reply, no matter which command it came from.
+        let content = match ex.downcast_ref::<CowError>() {
+            Some(ex) => ex.user_message(),
+            None => "Something went wrong running this command... try again later?"
+        };
+        send_error(ctx, cmd, &id, content).await;
+    }
+}
+
+pub fn get_framework(pref: &str, app_id: UserId, owners: HashSet<UserId>) -> Arc<CowFramework> {
+    let mut framework = CowFramework::new()
+        .prefix(pref)
+        .on_mention(Some(app_id))
+        .dynamic_prefix(guild_prefix)
+        .owners(owners)
         .normal_message(non_command)
         .on_dispatch_error(on_error)
-        .bucket("diagnostics", |b| b.limit(2).time_span(15 * 60) // 15 minute delay for scan and fix.
-            .limit_for(LimitedFor::Guild)
-            .await_ratelimits(0)).await  // Don't delay, force them to re-execute since we don't want to hang the bot
-        .help(&COW_HELP)
-        .group(&GENERAL_GROUP)
-        .group(&RANKCONFIG_GROUP)
-        .group(&TIMEOUT_GROUP)
-        .group(&UCM_GROUP)
-        .group(&COWBOARD_GROUP)
-        .group(&MUSIC_GROUP)
-    ))
-}
\ No newline at end of file
+        .after(after)
+        // 15 minute delay for scan and fix. Don't wait it out, force them to re-execute since we don't want to hang the bot.
+        .bucket("diagnostics", Bucket { limit: 2, time_span: Duration::from_secs(15 * 60) });
+
+    for group in GROUPS {
+        framework = framework.group(group);
+    }
+
+    Arc::new(framework)
+}
@@ -1,13 +1,14 @@
 mod music_commands;
 
-use serenity::framework::standard::macros::group;
-
+use crate::services::cow_framework::CowGroup;
 use music_commands::*;
 
-#[group]
-#[prefixes("music")]
-#[description = "Commands for playing music."]
-#[summary = "Music"]
-#[default_command(help)]
-#[commands(help, join, leave, play, playlist, pause, now_playing, skip, queue)]
-struct Music;
\ No newline at end of file
+pub static MUSIC_GROUP: CowGroup = CowGroup {
+    name: "Music",
+    prefixes: &["music"],
+    description: "Commands for playing music.",
+    summary: "Music",
+    default_command: Some(&HELP_COMMAND),
+    commands: &[&HELP_COMMAND, &JOIN_COMMAND, &LEAVE_COMMAND, &PLAY_COMMAND, &PLAYLIST_COMMAND, &PAUSE_COMMAND, &NOW_PLAYING_COMMAND, &SKIP_COMMAND, &QUEUE_COMMAND],
+    sub_groups: &[]
+};
@@ -1,34 +1,53 @@
 use lavalink_rs::model::{TrackQueue};
 use log::error;
 use regex::Regex;
+use serenity::builder::CreateEmbed;
 use serenity::client::Context;
-use serenity::framework::standard::{CommandResult, Args};
-use serenity::model::channel::{Message};
-use serenity::framework::standard::macros::{command};
+use serenity::framework::standard::CommandResult;
 use serenity::utils::MessageBuilder;
 use crate::Lavalink;
+use crate::services::cow_framework::{command, paginate, Check, CowCommand, CommandOption, OptionKind, Invocation, StaticPages};
+
+// Lavalink is optional, so the bot can still start without it. Everything but help needs it though.
+static LAVALINK_CHECK: Check = Check {
+    name: "lavalink",
+    function: lavalink_connected
+};
+
+#[command]
+async fn lavalink_connected(ctx: &Context, _cmd: &Invocation) -> Result<(), String> {
+    if ctx.data.read().await.contains_key::<Lavalink>() {
+        Ok(())
+    } else {
+        Err("Music isn't set up on this bot right now.".to_string())
+    }
+}
+
+pub static HELP_COMMAND: CowCommand = CowCommand {
+    aliases: &["p"],
+    ..CowCommand::new("help", "List the music commands.", help)
+};
 
 #[command]
-#[aliases(p)]
-async fn help(ctx: &Context, msg: &Message) -> CommandResult {
-    msg.channel_id.say(&ctx.http, "`help, join, leave, play, playlist, pause, now_playing, skip, queue`").await?;
+async fn help(ctx: &Context, cmd: &Invocation) -> CommandResult {
+    cmd.say(&ctx.http, "`help, join, leave, play, playlist, pause, now_playing, skip, queue`").await?;
 
     Ok(())
 }
 
-async fn join_interactive(ctx: &Context, msg: &Message) -> CommandResult {
-    let guild = msg.guild(&ctx.cache).await.unwrap();
+async fn join_interactive(ctx: &Context, cmd: &Invocation) -> CommandResult {
+    let guild = cmd.guild(ctx).await.unwrap();
     let guild_id = guild.id;
 
     let channel_id = guild
         .voice_states
-        .get(&msg.author.id)
+        .get(&cmd.author().id)
         .and_then(|voice_state| voice_state.channel_id);
 
     let connect_to = match channel_id {
         Some(channel) => channel,
         None => {
-            msg.channel_id.say(&ctx.http, "Join a voice channel first.").await?;
+            cmd.say(&ctx.http, "Join a voice channel first.").await?;
             return Ok(());
         }
     };
@@ -45,10 +64,10 @@ async fn join_interactive(ctx: &Context, msg: &Message) -> CommandResult {
             };
 
             lava_client.create_session(&connection_info).await?;
-            msg.channel_id.say(&ctx.http, format!("Joined <#{}>", connect_to)).await?;
+            cmd.say(&ctx.http, format!("Joined <#{}>", connect_to)).await?;
         }
         Err(ex) => {
-            msg.channel_id.say(&ctx.http, "Failed to join your VC...").await?;
+            cmd.say(&ctx.http, "Failed to join your VC...").await?;
             error!("Error joining the channel: {}", ex)
         }
     }
@@ -56,16 +75,26 @@ async fn join_interactive(ctx: &Context, msg: &Message) -> CommandResult {
     Ok(())
 }
 
+pub static JOIN_COMMAND: CowCommand = CowCommand {
+    only_in_guilds: true,
+    checks: &[&LAVALINK_CHECK],
+    ..CowCommand::new("join", "Join your voice channel.", join)
+};
+
 #[command]
-#[only_in(guilds)]
-async fn join(ctx: &Context, msg: &Message) -> CommandResult {
-    join_interactive(ctx, msg).await
+async fn join(ctx: &Context, cmd: &Invocation) -> CommandResult {
+    join_interactive(ctx, cmd).await
 }
 
+pub static LEAVE_COMMAND: CowCommand = CowCommand {
+    only_in_guilds: true,
+    checks: &[&LAVALINK_CHECK],
+    ..CowCommand::new("leave", "Leave the voice channel.", leave)
+};
+
 #[command]
-#[only_in(guilds)]
-async fn leave(ctx: &Context, msg: &Message) -> CommandResult {
-    let guild = msg.guild(&ctx.cache).await.unwrap();
+async fn leave(ctx: &Context, cmd: &Invocation) -> CommandResult {
+    let guild = cmd.guild(ctx).await.unwrap();
     let guild_id = guild.id;
 
     let manager = songbird::get(ctx).await.unwrap().clone();
@@ -83,29 +112,30 @@ async fn leave(ctx: &Context, msg: &Message) -> CommandResult {
             lava_client.destroy(guild_id).await?;
         }
 
-        msg.channel_id.say(&ctx.http, "Disconnected from VC. Goodbye!").await?;
+        cmd.say(&ctx.http, "Disconnected from VC. Goodbye!").await?;
     } else {
-        msg.channel_id.say(&ctx.http, "I'm not in a VC.").await?;
+        cmd.say(&ctx.http, "I'm not in a VC.").await?;
     }
 
     Ok(())
 }
 
-#[command]
-#[only_in(guilds)]
-async fn play(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
-
-    if args.is_empty() {
-        msg.channel_id.say(&ctx.http, "Please enter a query or link.").await?;
-    }
+pub static PLAY_COMMAND: CowCommand = CowCommand {
+    options: &[CommandOption::new("query", "What to search for, or a link.", OptionKind::String).required()],
+    only_in_guilds: true,
+    checks: &[&LAVALINK_CHECK],
+    ..CowCommand::new("play", "Play a song, or add it to the queue.", play)
+};
 
-    let query = args.message().to_string();
+#[command]
+async fn play(ctx: &Context, cmd: &Invocation) -> CommandResult {
+    let query = cmd.arg::<String>("query").unwrap_or_default();
 
-    let guild_id = match ctx.cache.guild_channel(msg.channel_id).await {
+    let guild_id = match ctx.cache.guild_channel(cmd.channel_id()).await {
         Some(channel) => channel.guild_id,
         None => {
             
-            msg.channel_id.say(&ctx.http, "Error finding channel info").await?;
+            cmd.say(&ctx.http, "Error finding channel info").await?;
 
             return Ok(());
         }
@@ -119,8 +149,8 @@ async fn play(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
     let manager = songbird::get(ctx).await.unwrap().clone();
 
     if manager.get(guild_id).is_none() {
-        if let Err(ex) = join_interactive(ctx, msg).await {
-            msg.channel_id.say(&ctx.http, "Failed to connect to voice channel; maybe I don't have permissions?").await?;
+        if let Err(ex) = join_interactive(ctx, cmd).await {
+            cmd.say(&ctx.http, "Failed to connect to voice channel; maybe I don't have permissions?").await?;
             error!("Failed to connect to vc: {}", ex);
             return Ok(());
         }
@@ -130,7 +160,7 @@ async fn play(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
         let query_information = lava_client.auto_search_tracks(&query).await?;
 
         if query_information.tracks.is_empty() {
-            msg.channel_id.say(&ctx, "Could not find any video of the search query.").await?;
+            cmd.say(&ctx.http, "Could not find any video of the search query.").await?;
             return Ok(());
         }
 
@@ -144,29 +174,28 @@ async fn play(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
         let message = MessageBuilder::new().push("Added to queue: ").push_mono_safe(&query_information.tracks[0].info.as_ref().unwrap().title).build();
         if let Ok(tracks) = lava_client.get_tracks(query).await {
             if tracks.tracks.len() > 1 {
-                msg.channel_id.say(&ctx.http, "Note: This seems to be a playlist. If you want to add all tracks at once, use `playlist` instead of `play`.\n".to_string() + &*message).await?;
+                cmd.say(&ctx.http, "Note: This seems to be a playlist. If you want to add all tracks at once, use `playlist` instead of `play`.\n".to_string() + &*message).await?;
                 return Ok(())
             }
         }
-        msg.channel_id.say(&ctx.http, message).await?;
+        cmd.say(&ctx.http, message).await?;
     }
 
     Ok(())
 }
 
-#[command]
-#[only_in(guilds)]
-async fn playlist(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
-
-    if let Some(guild_id) = msg.guild_id {
-        if args.is_empty() {
-            msg.channel_id.say(&ctx.http, "Please enter a query or link.").await?;
-            return Ok(())
-        }
-
-        let query = args.message().to_string();
+pub static PLAYLIST_COMMAND: CowCommand = CowCommand {
+    options: &[CommandOption::new("query", "A link to the playlist.", OptionKind::String).required()],
+    only_in_guilds: true,
+    checks: &[&LAVALINK_CHECK],
+    ..CowCommand::new("playlist", "Add every track in a playlist to the queue.", playlist)
+};
 
+#[command]
+async fn playlist(ctx: &Context, cmd: &Invocation) -> CommandResult {
 
+    if let Some(guild_id) = cmd.guild_id() {
+        let query = cmd.arg::<String>("query").unwrap_or_default();
 
         let lava_client = {
             let data = ctx.data.read().await;
@@ -176,8 +205,8 @@ async fn playlist(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
         let manager = songbird::get(ctx).await.unwrap().clone();
 
         if manager.get(guild_id).is_none() {
-            if let Err(ex) = join_interactive(ctx, msg).await {
-                msg.channel_id.say(&ctx.http, "Failed to connect to voice channel; maybe I don't have permissions?").await?;
+            if let Err(ex) = join_interactive(ctx, cmd).await {
+                cmd.say(&ctx.http, "Failed to connect to voice channel; maybe I don't have permissions?").await?;
                 error!("Failed to connect to vc: {}", ex);
                 return Ok(());
             }
@@ -196,17 +225,17 @@ async fn playlist(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
 
                     if let Some(info) = &tracks.playlist_info {
                         if let Some(name) = &info.name {
-                            msg.channel_id.say(&ctx.http, MessageBuilder::new().push("Added to the queue ").push(tracks.tracks.len()).push(" tracks from ").push_mono_safe(name).push(".")).await?;
+                            cmd.say(&ctx.http, MessageBuilder::new().push("Added to the queue ").push(tracks.tracks.len()).push(" tracks from ").push_mono_safe(name).push(".")).await?;
                         } else {
-                            msg.channel_id.say(&ctx.http, format!("Added to the queue {} tracks.", tracks.tracks.len())).await?;
+                            cmd.say(&ctx.http, format!("Added to the queue {} tracks.", tracks.tracks.len())).await?;
                         }
                     } else {
-                        msg.channel_id.say(&ctx.http, format!("Added to the queue {} tracks.", tracks.tracks.len())).await?;
+                        cmd.say(&ctx.http, format!("Added to the queue {} tracks.", tracks.tracks.len())).await?;
                     }
                 }
                 Err(ex) => {
                     error!("Failed to load tracks: {}", ex);
-                    msg.channel_id.say(&ctx, "Could not load any tracks from the given input.").await?;
+                    cmd.say(&ctx.http, "Could not load any tracks from the given input.").await?;
                 }
             }
         }
@@ -215,10 +244,15 @@ async fn playlist(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
     Ok(())
 }
 
+pub static PAUSE_COMMAND: CowCommand = CowCommand {
+    only_in_guilds: true,
+    checks: &[&LAVALINK_CHECK],
+    ..CowCommand::new("pause", "Pause or unpause the player.", pause)
+};
+
 #[command]
-#[only_in(guilds)]
-async fn pause(ctx: &Context, msg: &Message) -> CommandResult {
-    if let Some(guild_id) = msg.guild_id {
+async fn pause(ctx: &Context, cmd: &Invocation) -> CommandResult {
+    if let Some(guild_id) = cmd.guild_id() {
         let lava_client = {
             let data = ctx.data.read().await;
             data.get::<Lavalink>().unwrap().clone()
@@ -229,12 +263,12 @@ async fn pause(ctx: &Context, msg: &Message) -> CommandResult {
                 if let Err(ex) = lava_client.set_pause(guild_id, false).await {
                     error!("Failed to unpause music: {}", ex);
                 } else {
-                    msg.channel_id.say(&ctx.http, "Unpaused the player.").await?;
+                    cmd.say(&ctx.http, "Unpaused the player.").await?;
                 }
             } else if let Err(ex) = lava_client.pause(guild_id).await {
                 error!("Failed to pause music: {}", ex);
             } else {
-                msg.channel_id.say(&ctx.http, "Paused the player.").await?;
+                cmd.say(&ctx.http, "Paused the player.").await?;
             }
         }
     }
@@ -242,16 +276,21 @@ async fn pause(ctx: &Context, msg: &Message) -> CommandResult {
     Ok(())
 }
 
+pub static NOW_PLAYING_COMMAND: CowCommand = CowCommand {
+    aliases: &["np", "nowplaying"],
+    only_in_guilds: true,
+    checks: &[&LAVALINK_CHECK],
+    ..CowCommand::new("now_playing", "Show the song that is playing.", now_playing)
+};
+
 #[command]
-#[only_in(guilds)]
-#[aliases(np, nowplaying)]
-async fn now_playing(ctx: &Context, msg: &Message) -> CommandResult {
+async fn now_playing(ctx: &Context, cmd: &Invocation) -> CommandResult {
     let lava_client = {
         let data = ctx.data.read().await;
         data.get::<Lavalink>().unwrap().clone()
     };
 
-    let guild_id = msg.guild_id.unwrap();
+    let guild_id = cmd.guild_id().unwrap();
 
     if let Some(node) = lava_client.nodes().await.get(&guild_id.0) {
         if let Some(track) = &node.now_playing {
@@ -261,7 +300,7 @@ async fn now_playing(ctx: &Context, msg: &Message) -> CommandResult {
             let id = caps.get(1).map(|m| m.as_str());
             let server_name = guild_id.name(&ctx).await;
 
-            msg.channel_id.send_message(&ctx.http, |m| m.embed(|e| {
+            cmd.send_message(&ctx.http, |m| m.embed(|e| {
                  e
                     .author(|a| a.name(match server_name {
                         Some(name) => format!("Now Playing in {}", name),
@@ -285,36 +324,41 @@ async fn now_playing(ctx: &Context, msg: &Message) -> CommandResult {
             }
             )).await?;
         } else {
-            msg.channel_id.say(&ctx.http, "Nothing is playing at the moment.").await?;
+            cmd.say(&ctx.http, "Nothing is playing at the moment.").await?;
         }
     } else {
-        msg.channel_id.say(&ctx.http, "Nothing is playing at the moment.").await?;
+        cmd.say(&ctx.http, "Nothing is playing at the moment.").await?;
     }
 
     Ok(())
 }
 
+pub static SKIP_COMMAND: CowCommand = CowCommand {
+    only_in_guilds: true,
+    checks: &[&LAVALINK_CHECK],
+    ..CowCommand::new("skip", "Skip the current song.", skip)
+};
+
 #[command]
-#[only_in(guilds)]
-async fn skip(ctx: &Context, msg: &Message) -> CommandResult {
+async fn skip(ctx: &Context, cmd: &Invocation) -> CommandResult {
     let lava_client = {
         let data = ctx.data.read().await;
         data.get::<Lavalink>().unwrap().clone()
     };
 
-    if let Some(track) = lava_client.skip(msg.guild_id.unwrap()).await {
-        msg.channel_id.say(&ctx.http, MessageBuilder::new().push("Skipped: ").push_mono_line_safe(&track.track.info.as_ref().unwrap().title)).await?;
+    if let Some(track) = lava_client.skip(cmd.guild_id().unwrap()).await {
+        cmd.say(&ctx.http, MessageBuilder::new().push("Skipped: ").push_mono_line_safe(&track.track.info.as_ref().unwrap().title)).await?;
 
         // Need to check if it's empty, so we can stop playing (can crash if we don't check)
-        if let Some(node) = lava_client.nodes().await.get(&msg.guild_id.unwrap().0) {
+        if let Some(node) = lava_client.nodes().await.get(&cmd.guild_id().unwrap().0) {
             if node.now_playing.is_none() {
-                if let Err(ex) = lava_client.stop(msg.guild_id.unwrap()).await {
+                if let Err(ex) = lava_client.stop(cmd.guild_id().unwrap()).await {
                     error!("Failed to stop music: {}", ex);
                 }
             }
         }
     } else {
-        msg.channel_id.say(&ctx.http, "There is nothing to skip.").await?;
+        cmd.say(&ctx.http, "There is nothing to skip.").await?;
     }
 
     Ok(())
@@ -365,60 +409,53 @@ fn generate_queue(queue: &[TrackQueue]) -> Vec<String> {
     output
 }
 
+pub static QUEUE_COMMAND: CowCommand = CowCommand {
+    aliases: &["q"],
+    options: &[CommandOption::new("page", "The page of the queue to show.", OptionKind::Integer)],
+    only_in_guilds: true,
+    checks: &[&LAVALINK_CHECK],
+    ..CowCommand::new("queue", "Show the songs in the queue.", queue)
+};
+
 #[command]
-#[only_in(guilds)]
-#[aliases(q)]
-async fn queue(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+async fn queue(ctx: &Context, cmd: &Invocation) -> CommandResult {
     let lava_client = {
         let data = ctx.data.read().await;
         data.get::<Lavalink>().unwrap().clone()
     };
 
-    let mut page_num = if let Ok(arg_page) = args.single::<usize>() {
-        arg_page
-    } else {
-        1
-    };
-
-    let guild_id = msg.guild_id.unwrap();
-    if let Some(node) = lava_client.nodes().await.get(&guild_id.0) {
-        let queue = &node.queue;
-        let pages = generate_queue(queue);
-
-        if page_num > pages.len() {
-            page_num = pages.len();
-        } else if page_num == 0 {
-            page_num = 1;
-        }
+    let page_num = cmd.arg::<i64>("page").map(|p| p.max(0) as usize).unwrap_or(1);
 
-        let page = &pages[page_num - 1];
-        let server_name = guild_id.name(&ctx).await;
-
-        msg.channel_id.send_message(&ctx.http, |m| m.embed(|e| {
-            e
-                .author(|a| {
-                    if let Some(server) = server_name {
-                        a.name(format!("Player Queue | Page {}/{} | Playing in {}", page_num, pages.len(), server));
-                    } else {
-                        a.name(format!("Player Queue | Page {}/{}", page_num, pages.len()));
-                    }
-
-                    a
-                })
-                .title("Now Playing")
-                .field("Queued", page, false);
-
-            if let Some(now_playing) = &node.now_playing {
-                e.description(generate_line(now_playing));
-            } else {
-                e.description("Nothing is playing.");
-            }
+    let guild_id = cmd.guild_id().unwrap();
+    let server_name = guild_id.name(&ctx).await;
+    // Built up front so the node isn't held on to while someone flips through the pages.
+    let embeds = lava_client.nodes().await.get(&guild_id.0).map(|node| {
+        let now_playing = node.now_playing.as_ref().map(generate_line).unwrap_or_else(|| "Nothing is playing.".to_string());
+        generate_queue(&node.queue).iter()
+            .map(|page| {
+                let mut e = CreateEmbed::default();
+                e
+                    .author(|a| {
+                        if let Some(server) = &server_name {
+                            a.name(format!("Player Queue | Playing in {}", server));
+                        } else {
+                            a.name("Player Queue");
+                        }
 
-            e
-        })).await?;
+                        a
+                    })
+                    .title("Now Playing")
+                    .description(&now_playing)
+                    .field("Queued", page, false);
+                e
+            })
+            .collect::<Vec<_>>()
+    });
 
+    if let Some(embeds) = embeds {
+        paginate(ctx, cmd, &StaticPages(embeds), page_num.max(1) - 1).await?;
     } else {
-        msg.channel_id.say(&ctx.http, "Nothing is playing at the moment.").await?;
+        cmd.say(&ctx.http, "Nothing is playing at the moment.").await?;
     }
 
     Ok(())
@@ -1,69 +1,116 @@
-use std::collections::{HashMap, HashSet};
-use log::error;
+use std::collections::HashMap;
+use chrono::{Duration, Utc};
 use serenity::{
+    builder::CreateEmbed,
     client::Context,
     model::{
-        channel::Message,
-        id::{
-            RoleId
-        }
-    },
-    framework::standard::{
-        CommandResult,
-        macros::{
-            command
-        },
-        Args
+        guild::Member,
+        id::{GuildId, RoleId, UserId},
+        permissions::Permissions
     },
+    framework::standard::CommandResult,
     utils::MessageBuilder
 };
 use crate::{Database, db};
+use crate::models::db_models::RoleChange;
+use crate::models::error::CowError;
+use crate::services::cow_framework::{command, paginate, CowCommand, CowGroup, CommandOption, OptionKind, Invocation, StaticPages};
+use crate::services::database::Storage;
+use crate::services::guild_settings::GuildSettings;
+use super::role_sync::{apply_fix, check_member, describe_fix, expected_roles, role_levels, FixKind, FixOptions, MemberCheck, RoleFix};
+
+// How long after a fix it can still be undone. Past this, members have probably earned or lost ranks on their own.
+const FIX_UNDO_HOURS: i64 = 24;
+const PREVIEW_PAGE_SIZE: usize = 15;
+
+pub static FIX_GROUP: CowGroup = CowGroup {
+    name: "Fix",
+    prefixes: &["fix"],
+    description: "Fix any discrepancies between server member roles and the stored info, or undo the last fix.",
+    summary: "Role fixes",
+    default_command: Some(&RUN_COMMAND),
+    commands: &[&RUN_COMMAND, &UNDO_COMMAND],
+    sub_groups: &[]
+};
+
+// Someone in the database whose rank roles don't match their level.
+struct Discrepancy {
+    member: Member,
+    expected: Vec<RoleId>,
+    ranks: Vec<RoleId>,
+    // Worked out with every option on; FixOptions::allows says whether a given run goes through with it.
+    fix: RoleFix
+}
+
+// Checks everyone in the database who is still in the server. Returns how many that was, along with everyone who's wrong.
+async fn find_discrepancies(ctx: &Context, db: &dyn Storage, guild_id: GuildId) -> Result<(usize, Vec<Discrepancy>), CowError> {
+    let mode = GuildSettings::from_context(ctx).await.rank_mode(guild_id).await?;
+    let role_levels = role_levels(db, guild_id).await?;
+    let users = db.get_users(guild_id).await?;
+
+    let mut total = 0;
+    let mut out = Vec::new();
+    for u in users {
+        if let Ok(member) = guild_id.member(&ctx.http, u.user).await {
+            total += 1;
+
+            let expected = expected_roles(u.exp.level, u.role_id, &role_levels, mode);
+            if let MemberCheck::Fix(fix) = check_member(&expected, &member.roles, &role_levels, mode, FixOptions::all()) {
+                let ranks = member.roles.iter().filter(|r| role_levels.contains_key(r)).cloned().collect();
+                out.push(Discrepancy { member, expected, ranks, fix });
+            }
+        }
+    }
+
+    Ok((total, out))
+}
+
+fn option_name(kind: FixKind) -> &'static str {
+    match kind {
+        FixKind::Trivial => "",
+        FixKind::Multiple => "multiple",
+        FixKind::Remove => "remove",
+        FixKind::Demote => "demote"
+    }
+}
+
+pub static SCAN_COMMAND: CowCommand = CowCommand {
+    only_in_guilds: true,
+    bucket: Some("diagnostics"),
+    permissions: Permissions::ADMINISTRATOR,
+    ..CowCommand::new("scan", "Scan for discrepancies between server member roles and the stored info.", scan)
+};
 
 #[command]
-#[description = "Scan for discrepancies between server member roles and the stored info."]
-#[only_in(guilds)]
-#[bucket = "diagnostics"]
-#[required_permissions("ADMINISTRATOR")]
-pub async fn scan(ctx: &Context, msg: &Message) -> CommandResult {
+pub async fn scan(ctx: &Context, cmd: &Invocation) -> CommandResult {
     let db = db!(ctx);
-    if let Some(guild_id) = msg.guild_id {
+    if let Some(guild_id) = cmd.guild_id() {
         let mut message = MessageBuilder::new();
 
-        let mut discord_message = msg.channel_id.send_message(&ctx.http, |m| m.embed(|e| e
+        let mut discord_message = cmd.send_message(&ctx.http, |m| m.embed(|e| e
             .title("Member Scan")
             .description("Now processing, please wait warmly...")
         )).await?;
 
-        let roles = db.get_roles(guild_id).await?;
-        let role_set = roles.into_iter().filter_map(|r| r.role_id).collect::<HashSet<_>>();
-        let users = db.get_users(guild_id).await?;
-        for u in users {
-            if let Ok(member) = guild_id.member(&ctx.http, u.user).await {
-                let member_role_set: HashSet<RoleId> = HashSet::from_iter(member.roles.iter().cloned());
-                let intersection = role_set.intersection(&member_role_set).collect::<HashSet<_>>();
-                if let Some(expected_role) = u.role_id {
-                    if intersection.contains(&expected_role) && intersection.len() == 1 {
-                        continue; // Correct: one role and it's the expected one
-                    }
-                    // Either doesn't have the role, wrong role, or too many roles
-                    message.push("<@").push(u.user).push("> should have ").role(expected_role);
-                    if intersection.is_empty() {
-                        message.push(" but doesn't");
-                    } else {
-                        message.push(" but has: ");
-                        intersection.into_iter().for_each(|r| { message.push(" ").role(r).push(" "); });
-                    }
-                    message.push("\n");
+        let (_, discrepancies) = find_discrepancies(ctx, db.as_ref(), guild_id).await?;
+        for d in discrepancies {
+            let user = d.member.user.id;
+            if d.expected.is_empty() {
+                // Has a role, when they shouldn't
+                message.push("<@").push(user).push("> has excess roles: ");
+                d.ranks.into_iter().for_each(|r| { message.push(" ").role(r).push(" "); });
+            } else {
+                // Either doesn't have the role, wrong role, or too many roles
+                message.push("<@").push(user).push("> should have");
+                d.expected.iter().for_each(|r| { message.push(" ").role(r); });
+                if d.ranks.is_empty() {
+                    message.push(" but doesn't");
                 } else {
-                    if intersection.is_empty() {
-                        continue; // Correct: no roles
-                    }
-                    // Has a role, when they shouldn't
-                    message.push("<@").push(u.user).push("> has excess roles: ");
-                    intersection.into_iter().for_each(|r| { message.push(" ").role(r).push(" "); });
-                    message.push("\n");
+                    message.push(" but has: ");
+                    d.ranks.into_iter().for_each(|r| { message.push(" ").role(r).push(" "); });
                 }
             }
+            message.push("\n");
         }
 
         let mut content = message.build();
@@ -76,131 +123,99 @@ pub async fn scan(ctx: &Context, msg: &Message) -> CommandResult {
             .description(content)
         )).await?;
     } else {
-        msg.reply(&ctx.http, "This command can only be run in a server.").await?;
+        cmd.reply(&ctx.http, "This command can only be run in a server.").await?;
     }
 
     Ok(())
 }
 
+pub static RUN_COMMAND: CowCommand = CowCommand {
+    usage: Some("\"multiple\" to fix users with multiple roles, \"remove\" to remove roles from users, \"demote\" to modify ranks downwards, and \"--dry-run\" to only list the changes."),
+    options: &[CommandOption::new("options", "Any of \"multiple\", \"remove\" and \"demote\" to also fix those cases, and \"--dry-run\" to only preview.", OptionKind::String)],
+    only_in_guilds: true,
+    bucket: Some("diagnostics"),
+    permissions: Permissions::ADMINISTRATOR,
+    ..CowCommand::new("run", "Fix any discrepancies between server member roles and the stored info. By default, this will only affect trivial cases.", fix)
+};
+
+// "--dry-run", "dry-run", "dryrun" and "preview" all work, since nobody remembers which one it is.
+fn is_dry_run(options: &str) -> bool {
+    options.to_lowercase().split_whitespace()
+        .map(|arg| arg.trim_start_matches('-').replace('-', ""))
+        .any(|arg| arg == "dryrun" || arg == "preview")
+}
+
 #[command]
-#[description = "Fix any discrepancies between server member roles and the stored info. By default, this will only affect"]
-#[only_in(guilds)]
-#[required_permissions("ADMINISTRATOR")]
-#[bucket = "diagnostics"]
-#[usage = "\"multiple\" to fix users with multiple roles, \"remove\" to remove roles from users, and \"demote\" to modify ranks downwards."]
-pub async fn fix(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+pub async fn fix(ctx: &Context, cmd: &Invocation) -> CommandResult {
     let db = db!(ctx);
-    if let Some(guild_id) = msg.guild_id {
-        /*
-            There are several invalid cases we have to worry about:
-            - The user shouldn't have the role, and yet they do have conflicting roles (non-trivial) -> remove
-            - The user should have the role, and:
-              - they *do not* have any conflicting roles (trivial)
-              - they have one conflicting role
-                - and they should be higher up (trivial)
-                - and they should be lower down (non-trivial) -> demote
-              - they have multiple conflicting roles (non-trivial) -> multiple
-
-             The trivial cases will be done by default, and the non-trivial cases can be done by options.
-         */
-
-        let (mut count_trivial, mut count_multiple, mut count_remove, mut count_demote, mut count_error, mut total_error, mut total) = (0, 0, 0, 0, 0, 0, 0);
-
-        let (mut option_multiple, mut option_remove, mut option_demote) = (false, false, false);
-
-        while !args.is_empty() {
-            let arg = args.single::<String>().unwrap().to_lowercase();
-            option_multiple |= arg.contains("multiple");
-            option_remove |= arg.contains("remove");
-            option_demote |= arg.contains("demote");
-        }
+    if let Some(guild_id) = cmd.guild_id() {
+        let (mut count_trivial, mut count_multiple, mut count_remove, mut count_demote, mut count_error) = (0, 0, 0, 0, 0);
+
+        let arg = cmd.arg::<String>("options").unwrap_or_default();
+        let options = FixOptions::parse(&arg);
+        let dry_run = is_dry_run(&arg);
 
-        let mut discord_message = msg.channel_id.send_message(&ctx.http, |m| m.embed(|e| e
+        let mut discord_message = cmd.send_message(&ctx.http, |m| m.embed(|e| e
             .title("Role Auto-fix")
-            .description("Now fixing roles, please wait warmly...")
+            .description(if dry_run { "Now checking roles, please wait warmly..." } else { "Now fixing roles, please wait warmly..." })
         )).await?;
 
-        let roles = db.get_roles(guild_id).await?;
-        let role_map = roles.into_iter().filter(|r| r.role_id.is_some()).map(|r| (r.role_id.unwrap(), r.min_level)).collect::<HashMap<_, _>>();
-        let role_set: HashSet<RoleId> = role_map.keys().cloned().collect(); // Mildly disgusting.
-        let users = db.get_users(guild_id).await?;
-        for u in users {
-            if let Ok(mut member) = guild_id.member(&ctx.http, u.user).await {
-                total += 1;
-
-                let member_role_set: HashSet<RoleId> = HashSet::from_iter(member.roles.iter().cloned());
-                let intersection = role_set.intersection(&member_role_set).collect::<HashSet<_>>();
-                if let Some(expected_role) = u.role_id {
-                    if intersection.contains(&expected_role) && intersection.len() == 1 {
-                        continue; // Correct: one role and it's the expected one
-                    }
-                    total_error += 1;
-
-                    if intersection.is_empty() { // They do not have the role, and need it
-                        if let Err(ex) = member.add_role(&ctx.http, expected_role).await {
-                            error!("Failed to add role: {}", ex);
-                            count_error += 1;
-                        } else {
-                            count_trivial += 1;
-                        }
-                    } else if intersection.len() == 1 { // They have another role in place
-                        let existing_role = intersection.into_iter().next().unwrap();
-                        let promote = role_map[existing_role] < role_map[&expected_role];
-                        if promote || option_demote { // Promote them
-                            if let Err(ex) = member.remove_role(&ctx.http, existing_role).await {
-                                error!("Failed to remove role for demoting: {}", ex);
-                                count_error += 1;
-                            }
-
-                            if let Err(ex) = member.add_role(&ctx.http, expected_role).await {
-                                error!("Failed to add role for promoting/demoting: {}", ex);
-                                count_error += 1;
-                            } else if promote {
-                                count_trivial += 1;
-                            } else {
-                                count_demote += 1;
-                            }
-                        }
-                    } else if option_multiple { // We have multiple to deal with
-                        for r in intersection {
-                            if *r == expected_role {
-                                continue;
-                            }
-
-                            if let Err(ex) = member.remove_role(&ctx.http, r).await {
-                                error!("Failed to remove excess roles: {}", ex);
-                                count_error += 1;
-                            }
-                        }
-
-                        if !member.roles.contains(&expected_role) {
-                            if let Err(ex) = member.add_role(&ctx.http, expected_role).await {
-                                error!("Failed to add role: {}", ex);
-                                count_error += 1;
-                            }
-                        }
-
-                        count_multiple += 1;
-                    }
-                } else {
-                    if intersection.is_empty() {
-                        continue; // Correct: no roles
-                    }
-
-                    total_error += 1;
-
-                    if option_remove {
-                        for r in intersection {
-                            if let Err(ex) = member.remove_role(&ctx.http, r).await {
-                                error!("Failed to remove role: {}", ex);
-                                count_error += 1;
-                            } else {
-                                count_remove += 1;
-                            }
-                        }
-                    }
-                }
+        let (total, discrepancies) = find_discrepancies(ctx, db.as_ref(), guild_id).await?;
+        let total_error = discrepancies.len();
+
+        if dry_run {
+            let (fixes, skipped): (Vec<_>, Vec<_>) = discrepancies.iter().partition(|d| options.allows(d.fix.kind));
+            let lines = fixes.iter()
+                .map(|d| describe_fix(d.member.user.id, &d.fix))
+                .chain(skipped.iter().map(|d| format!("{} (skipped, needs `{}`)", describe_fix(d.member.user.id, &d.fix), option_name(d.fix.kind))))
+                .collect::<Vec<_>>();
+
+            discord_message.edit(&ctx.http, |m| m.embed(|e| e
+                .title("Role Auto-fix Preview")
+                .description(format!("Processed {} members in the database with {} errors found. \
+                Running fix with these options would change {} members and skip {}. Nothing has been changed yet.",
+                    total, total_error, fixes.len(), skipped.len()))
+            )).await?;
+
+            if !lines.is_empty() {
+                let pages = lines.chunks(PREVIEW_PAGE_SIZE)
+                    .map(|chunk| {
+                        let mut embed = CreateEmbed::default();
+                        embed.title("Role Auto-fix Preview").description(chunk.join("\n"));
+                        embed
+                    })
+                    .collect::<Vec<_>>();
+                paginate(ctx, cmd, &StaticPages(pages), 0).await?;
             }
+
+            return Ok(());
+        }
+
+        let mut changes = Vec::new();
+        for mut d in discrepancies {
+            if !options.allows(d.fix.kind) {
+                continue;
+            }
+
+            let outcome = apply_fix(&ctx.http, &mut d.member, &d.fix).await;
+            changes.extend(outcome.applied);
+            if outcome.errors > 0 {
+                count_error += outcome.errors;
+                continue;
+            }
+
+            match d.fix.kind {
+                FixKind::Trivial => count_trivial += 1,
+                FixKind::Multiple => count_multiple += 1,
+                FixKind::Remove => count_remove += 1,
+                FixKind::Demote => count_demote += 1
+            }
+        }
+
+        let mut undo = String::new();
+        if !changes.is_empty() {
+            db.record_fix(guild_id, Utc::now().naive_utc(), &changes).await?;
+            undo = format!("\n\nRun `rankconfig fix undo` within {} hours to put these back.", FIX_UNDO_HOURS);
         }
 
         discord_message.edit(&ctx.http, |m| m.embed(|e| e
@@ -210,11 +225,82 @@ pub async fn fix(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult
             - Fixes for multiple roles: {}\n\
             - Members with their roles fully revoked: {}\n\
             - Members demoted: {}\n\
-            - Errors adding/removing roles: {}", total, total_error, count_trivial, count_multiple, count_remove, count_demote, count_error))
+            - Errors adding/removing roles: {}{}", total, total_error, count_trivial, count_multiple, count_remove, count_demote, count_error, undo))
         )).await?;
     } else {
-        msg.reply(&ctx.http, "This command can only be run in a server.").await?;
+        cmd.reply(&ctx.http, "This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
+
+pub static UNDO_COMMAND: CowCommand = CowCommand {
+    only_in_guilds: true,
+    bucket: Some("diagnostics"),
+    permissions: Permissions::ADMINISTRATOR,
+    ..CowCommand::new("undo", "Put back the roles the last fix changed, if it was recent enough.", undo)
+};
+
+#[command]
+pub async fn undo(ctx: &Context, cmd: &Invocation) -> CommandResult {
+    let guild_id = match cmd.guild_id() {
+        Some(guild_id) => guild_id,
+        None => {
+            cmd.reply(&ctx.http, "This command can only be run in a server.").await?;
+            return Ok(());
+        }
+    };
+
+    let db = db!(ctx);
+    let changeset = match db.last_fix(guild_id).await? {
+        Some(changeset) if Utc::now().naive_utc() - changeset.created_at <= Duration::hours(FIX_UNDO_HOURS) => changeset,
+        Some(_) => {
+            cmd.reply(&ctx.http, format!("The last fix was more than {} hours ago, so it can't be undone anymore.", FIX_UNDO_HOURS)).await?;
+            return Ok(());
+        },
+        None => {
+            cmd.reply(&ctx.http, "There isn't a fix to undo.").await?;
+            return Ok(());
+        }
+    };
+
+    let mut by_member: HashMap<UserId, Vec<RoleChange>> = HashMap::new();
+    changeset.changes.iter().for_each(|c| by_member.entry(c.user).or_default().push(*c));
+
+    if !cmd.confirm(ctx, format!("This reverts {} role changes for {} members from the fix at {} UTC. Are you sure?",
+        changeset.changes.len(), by_member.len(), changeset.created_at.format("%B %-d, %H:%M"))).await? {
+        return Ok(());
     }
 
+    // Taken out first, so a second undo can't apply the same changes again.
+    if !db.remove_fix(guild_id, changeset.id).await? {
+        cmd.reply(&ctx.http, "That fix has already been undone.").await?;
+        return Ok(());
+    }
+
+    let (mut reverted, mut left, mut errors) = (0, 0, 0);
+    for (user, changes) in by_member {
+        let mut member = match guild_id.member(&ctx.http, user).await {
+            Ok(member) => member,
+            Err(_) => {
+                left += 1;
+                continue;
+            }
+        };
+
+        // Swaps what was added and removed. apply_fix still removes first, so the ranks the fix gave are
+        // taken away before the old ones come back, and the end result doesn't depend on the order.
+        let fix = RoleFix {
+            kind: FixKind::Trivial,
+            add: changes.iter().filter(|c| !c.added).map(|c| c.role).collect(),
+            remove: changes.iter().filter(|c| c.added).map(|c| c.role).collect()
+        };
+        let outcome = apply_fix(&ctx.http, &mut member, &fix).await;
+        reverted += outcome.applied.len();
+        errors += outcome.errors;
+    }
+
+    cmd.reply(&ctx.http, format!("Reverted {} role changes. {} members have left the server, and {} changes failed.", reverted, left, errors)).await?;
+
     Ok(())
-}
\ No newline at end of file
+}
@@ -0,0 +1,170 @@
+use std::collections::HashSet;
+use reqwest::Url;
+use serenity::{
+    client::Context,
+    model::permissions::Permissions,
+    framework::standard::CommandResult
+};
+use crate::{Database, db};
+use crate::models::db_models::AuditEntry;
+use crate::models::error::CowError;
+use crate::models::xp_import::{ImportFile, ImportMode};
+use crate::services::cow_framework::{command, CowCommand, CommandOption, OptionChoice, OptionKind, Invocation};
+use crate::services::guild_settings::GuildSettings;
+use crate::services::supervisor::Supervisor;
+
+// Exports are a few lines per member, so this is plenty even for large servers.
+const MAX_FILE_SIZE: u64 = 8 * 1024 * 1024;
+const PREVIEW_MEMBERS: usize = 10;
+
+pub static IMPORT_COMMAND: CowCommand = CowCommand {
+    usage: Some("[level|xp] [url] (with the export attached)"),
+    options: &[
+        CommandOption::new("mode", "Whether members keep their level or their total xp.", OptionKind::String).choices(&[
+            OptionChoice::string("Keep levels", "level"),
+            OptionChoice::string("Keep total xp", "xp")
+        ]),
+        CommandOption::new("url", "A link to the exported leaderboard, uploaded to Discord.", OptionKind::String)
+    ],
+    only_in_guilds: true,
+    permissions: Permissions::ADMINISTRATOR,
+    ..CowCommand::new("import", "Bring levels over from MEE6, Arcane, Tatsu or anything else that exports JSON or CSV.", import)
+};
+
+// Only files on Discord, so this can't be pointed at anything else we can reach.
+fn discord_file(url: &str) -> Option<Url> {
+    let url = Url::parse(url.trim().trim_matches(|c| c == '<' || c == '>')).ok()?;
+    let host = url.host_str()?;
+    if url.scheme() == "https" && (host == "cdn.discordapp.com" || host == "media.discordapp.net") {
+        Some(url)
+    } else {
+        None
+    }
+}
+
+// Content-Length is only a hint (chunked responses don't have one), so the limit is checked as the body comes in too.
+async fn download(url: Url) -> Result<Vec<u8>, CowError> {
+    let mut response = reqwest::get(url).await?.error_for_status()?;
+    if response.content_length().unwrap_or(0) > MAX_FILE_SIZE {
+        return Err(CowError::external("The file is too large."));
+    }
+
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        if (body.len() + chunk.len()) as u64 > MAX_FILE_SIZE {
+            return Err(CowError::external("The file is too large."));
+        }
+        body.extend_from_slice(&chunk);
+    }
+
+    Ok(body)
+}
+
+#[command]
+pub async fn import(ctx: &Context, cmd: &Invocation) -> CommandResult {
+    let server_id = match cmd.guild_id() {
+        Some(server_id) => server_id,
+        None => {
+            cmd.reply(&ctx.http, "This command can only be run in a server.").await?;
+            return Ok(());
+        }
+    };
+
+    let mode = match cmd.arg::<String>("mode") {
+        Some(mode) => match ImportMode::parse(&mode) {
+            Some(mode) => mode,
+            None => {
+                cmd.reply(&ctx.http, "The mode should be level (members keep their level) or xp (members keep their total xp).").await?;
+                return Ok(());
+            }
+        },
+        None => ImportMode::Level
+    };
+
+    let attachment = cmd.message().and_then(|m| m.attachments.first());
+    let url = match (attachment, cmd.arg::<String>("url")) {
+        (Some(attachment), _) if attachment.size > MAX_FILE_SIZE => {
+            cmd.reply(&ctx.http, "That file is too large to be a leaderboard export.").await?;
+            return Ok(());
+        },
+        (Some(attachment), _) => discord_file(&attachment.url),
+        (None, Some(url)) => discord_file(&url),
+        (None, None) => {
+            cmd.reply(&ctx.http, "Attach the exported leaderboard (JSON or CSV) to the command, or link to it if it's already uploaded to Discord.").await?;
+            return Ok(());
+        }
+    };
+
+    let url = match url {
+        Some(url) => url,
+        None => {
+            cmd.reply(&ctx.http, "The export has to be a file uploaded to Discord.").await?;
+            return Ok(());
+        }
+    };
+
+    cmd.defer(&ctx.http).await?;
+    let content = match download(url).await {
+        Ok(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+        Err(ex) => {
+            cmd.reply(&ctx.http, format!("I couldn't download that file: {}", ex)).await?;
+            return Ok(());
+        }
+    };
+
+    let file = match ImportFile::parse(&content) {
+        Ok(file) => file,
+        Err(message) => {
+            cmd.reply(&ctx.http, message).await?;
+            return Ok(());
+        }
+    };
+
+    let db = db!(ctx);
+    let settings = GuildSettings::from_context(ctx).await.xp_settings(server_id).await?;
+    let members = file.map(&settings, mode);
+
+    // Nothing has been written yet; this is what would happen.
+    let existing = db.get_users(server_id).await?.into_iter().map(|u| u.user).collect::<HashSet<_>>();
+    let overwritten = members.iter().filter(|(user, _)| existing.contains(user)).count();
+    let highest = members.iter().map(|(_, exp)| exp.level).max().unwrap_or(0);
+
+    let mut by_level = members.iter().zip(&file.rows).collect::<Vec<_>>();
+    by_level.sort_by_key(|((_, exp), _)| (-exp.level, -exp.xp));
+    let preview = by_level.iter()
+        .take(PREVIEW_MEMBERS)
+        .map(|((user, exp), row)| {
+            let before = row.level.map(|l| format!("level {}, ", l)).unwrap_or_default();
+            format!("<@{}>: {}{} xp → level {}, {} xp", user, before, row.xp, exp.level, exp.xp)
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    cmd.send_message(&ctx.http, |m| m.embed(|e| e
+        .title("Import Preview")
+        .description(preview)
+        .field("Members", members.len(), true)
+        .field("Skipped rows", file.skipped, true)
+        .field("Already ranked here", format!("{} (will be overwritten)", overwritten), true)
+        .field("Mapping", format!("{}, highest level {}", mode, highest), false)
+        .footer(|f| f.text("Nothing has been changed yet."))
+    )).await?;
+
+    if !cmd.confirm(ctx, format!("Import {} members? Their current experience here will be replaced.", members.len())).await? {
+        return Ok(());
+    }
+
+    let _work = Supervisor::from_context(ctx).await.begin_work();
+    let written = db.import_exp(server_id, &members).await?;
+    db.add_audit(server_id, &AuditEntry {
+        moderator: cmd.author().id,
+        user: None,
+        action: "import".to_string(),
+        detail: format!("{} members, {} overwritten, {}", written, overwritten, mode),
+        created_at: None
+    }).await?;
+
+    cmd.reply(&ctx.http, format!("Imported {} members. Run `rankconfig fix` to hand out their rank roles.", written)).await?;
+
+    Ok(())
+}
@@ -0,0 +1,318 @@
+use serenity::{
+    client::Context,
+    model::{
+        id::{GuildId, UserId},
+        permissions::Permissions
+    },
+    framework::standard::CommandResult
+};
+use crate::{Database, db};
+use crate::models::db_models::{AuditEntry, Experience};
+use crate::services::cow_framework::{command, CowCommand, CowGroup, CommandOption, OptionKind, Invocation};
+use crate::services::database::Storage;
+use crate::services::guild_settings::GuildSettings;
+use super::role_sync::{sync_member, MemberSync};
+
+const MAX_AMOUNT: i32 = 1_000_000;
+const MAX_LEVEL: i32 = 10_000;
+const AUDIT_ENTRIES: i32 = 15;
+
+pub static LEVEL_GROUP: CowGroup = CowGroup {
+    name: "Level",
+    prefixes: &["level"],
+    description: "Change someone's level directly.",
+    summary: "Level management",
+    default_command: None,
+    commands: &[&SET_LEVEL_COMMAND],
+    sub_groups: &[]
+};
+
+fn sync_note(sync: &MemberSync) -> &'static str {
+    match sync {
+        MemberSync::Failed => "\n(I couldn't update their roles; maybe I don't have permission?)",
+        _ => ""
+    }
+}
+
+async fn audit(db: &dyn Storage, server_id: GuildId, moderator: UserId, user: Option<UserId>, action: &str, detail: String) -> CommandResult {
+    db.add_audit(server_id, &AuditEntry {
+        moderator,
+        user,
+        action: action.to_string(),
+        detail,
+        created_at: None
+    }).await?;
+
+    Ok(())
+}
+
+// Works on total experience so amounts carry over levels, then puts their roles in line with wherever they end up.
+// With existing_only, people who have never earned experience are left alone instead of getting a new row.
+async fn change_experience(ctx: &Context, cmd: &Invocation, action: &str, existing_only: bool, change: impl FnOnce(i64) -> i64) -> CommandResult {
+    let server_id = match cmd.guild_id() {
+        Some(server_id) => server_id,
+        None => {
+            cmd.reply(&ctx.http, "This command can only be run in a server.").await?;
+            return Ok(());
+        }
+    };
+
+    let db = db!(ctx);
+    let user = match cmd.arg::<UserId>("user") {
+        Some(user) => user,
+        None => {
+            cmd.reply(&ctx.http, "Could not find that user.").await?;
+            return Ok(());
+        }
+    };
+    let guild_settings = GuildSettings::from_context(ctx).await;
+    let settings = guild_settings.xp_settings(server_id).await?;
+    let mode = guild_settings.rank_mode(server_id).await?;
+
+    let before = match db.find_xp(server_id, user).await? {
+        Some(before) => before,
+        None if existing_only => {
+            cmd.reply(&ctx.http, "They don't have any experience yet.").await?;
+            return Ok(());
+        },
+        None => Experience::new()
+    };
+    let before_total = settings.total_experience(before.level, before.xp);
+    let (xp, level) = settings.split_total(change(before_total), MAX_LEVEL);
+    let after_total = settings.total_experience(level, xp);
+
+    db.set_exp(server_id, user, xp, level).await?;
+    let sync = sync_member(&ctx.http, db.as_ref(), server_id, user, mode).await?;
+    audit(db.as_ref(), server_id, cmd.author().id, Some(user), action,
+        format!("{} xp (level {}) → {} xp (level {})", before_total, before.level, after_total, level)).await?;
+
+    cmd.reply(&ctx.http, format!("<@{}> now has {} xp in total and is level {} (was {} xp, level {}).{}",
+        user, after_total, level, before_total, before.level, sync_note(&sync))).await?;
+
+    Ok(())
+}
+
+fn amount(cmd: &Invocation) -> Option<i64> {
+    cmd.arg::<i32>("amount").filter(|amount| (0..=MAX_AMOUNT).contains(amount)).map(|amount| amount as i64)
+}
+
+pub static GIVE_COMMAND: CowCommand = CowCommand {
+    usage: Some("<user> <amount>"),
+    options: &[
+        CommandOption::new("user", "Who to give experience to.", OptionKind::User).required(),
+        CommandOption::new("amount", "How much experience to give.", OptionKind::Integer).required()
+    ],
+    only_in_guilds: true,
+    permissions: Permissions::ADMINISTRATOR,
+    ..CowCommand::new("give", "Give someone experience, levelling them up if it's enough.", give)
+};
+
+#[command]
+pub async fn give(ctx: &Context, cmd: &Invocation) -> CommandResult {
+    match amount(cmd) {
+        Some(amount) => change_experience(ctx, cmd, "xp give", false, |total| total + amount).await,
+        None => {
+            cmd.reply(&ctx.http, format!("The amount must be between 0 and {}.", MAX_AMOUNT)).await?;
+            Ok(())
+        }
+    }
+}
+
+pub static TAKE_COMMAND: CowCommand = CowCommand {
+    usage: Some("<user> <amount>"),
+    options: &[
+        CommandOption::new("user", "Who to take experience from.", OptionKind::User).required(),
+        CommandOption::new("amount", "How much experience to take.", OptionKind::Integer).required()
+    ],
+    only_in_guilds: true,
+    permissions: Permissions::ADMINISTRATOR,
+    ..CowCommand::new("take", "Take experience away from someone, dropping their level if it comes to that.", take)
+};
+
+#[command]
+pub async fn take(ctx: &Context, cmd: &Invocation) -> CommandResult {
+    match amount(cmd) {
+        Some(amount) => change_experience(ctx, cmd, "xp take", true, |total| total - amount).await,
+        None => {
+            cmd.reply(&ctx.http, format!("The amount must be between 0 and {}.", MAX_AMOUNT)).await?;
+            Ok(())
+        }
+    }
+}
+
+pub static SET_XP_COMMAND: CowCommand = CowCommand {
+    usage: Some("<user> <amount>"),
+    options: &[
+        CommandOption::new("user", "Whose experience to set.", OptionKind::User).required(),
+        CommandOption::new("amount", "How much experience they should have in total.", OptionKind::Integer).required()
+    ],
+    only_in_guilds: true,
+    permissions: Permissions::ADMINISTRATOR,
+    ..CowCommand::new("set", "Set how much experience someone has in total, across all their levels.", set_xp)
+};
+
+#[command]
+pub async fn set_xp(ctx: &Context, cmd: &Invocation) -> CommandResult {
+    match amount(cmd) {
+        Some(amount) => change_experience(ctx, cmd, "xp set", false, |_| amount).await,
+        None => {
+            cmd.reply(&ctx.http, format!("The amount must be between 0 and {}.", MAX_AMOUNT)).await?;
+            Ok(())
+        }
+    }
+}
+
+pub static SET_LEVEL_COMMAND: CowCommand = CowCommand {
+    usage: Some("<user> <level>"),
+    options: &[
+        CommandOption::new("user", "Whose level to set.", OptionKind::User).required(),
+        CommandOption::new("level", "The level they should be at.", OptionKind::Integer).required()
+    ],
+    only_in_guilds: true,
+    permissions: Permissions::ADMINISTRATOR,
+    ..CowCommand::new("set", "Put someone at the start of a level.", set_level)
+};
+
+#[command]
+pub async fn set_level(ctx: &Context, cmd: &Invocation) -> CommandResult {
+    let server_id = match cmd.guild_id() {
+        Some(server_id) => server_id,
+        None => {
+            cmd.reply(&ctx.http, "This command can only be run in a server.").await?;
+            return Ok(());
+        }
+    };
+
+    let guild_settings = GuildSettings::from_context(ctx).await;
+    let settings = guild_settings.xp_settings(server_id).await?;
+    let mode = guild_settings.rank_mode(server_id).await?;
+    let max_level = settings.level_cap.unwrap_or(MAX_LEVEL).min(MAX_LEVEL);
+    let level = match cmd.arg::<i32>("level") {
+        Some(level) if (1..=max_level).contains(&level) => level,
+        _ => {
+            cmd.reply(&ctx.http, format!("The level must be between 1 and {}.", max_level)).await?;
+            return Ok(());
+        }
+    };
+
+    let db = db!(ctx);
+    let user = match cmd.arg::<UserId>("user") {
+        Some(user) => user,
+        None => {
+            cmd.reply(&ctx.http, "Could not find that user.").await?;
+            return Ok(());
+        }
+    };
+    let before = db.get_xp(server_id, user).await?;
+
+    db.set_exp(server_id, user, 0, level).await?;
+    let sync = sync_member(&ctx.http, db.as_ref(), server_id, user, mode).await?;
+    audit(db.as_ref(), server_id, cmd.author().id, Some(user), "level set",
+        format!("level {} ({} xp) → level {} (0 xp)", before.level, before.xp, level)).await?;
+
+    cmd.reply(&ctx.http, format!("<@{}> is now level {} (was level {}).{}", user, level, before.level, sync_note(&sync))).await?;
+
+    Ok(())
+}
+
+pub static RESET_COMMAND: CowCommand = CowCommand {
+    usage: Some("<user|all>"),
+    options: &[CommandOption::new("target", "Someone to reset, or all to reset everyone.", OptionKind::String).required()],
+    only_in_guilds: true,
+    permissions: Permissions::ADMINISTRATOR,
+    ..CowCommand::new("reset", "Wipe someone's experience, or everyone's, and take away their rank roles.", reset)
+};
+
+#[command]
+pub async fn reset(ctx: &Context, cmd: &Invocation) -> CommandResult {
+    let server_id = match cmd.guild_id() {
+        Some(server_id) => server_id,
+        None => {
+            cmd.reply(&ctx.http, "This command can only be run in a server.").await?;
+            return Ok(());
+        }
+    };
+
+    let db = db!(ctx);
+    let target = cmd.arg::<String>("target").unwrap_or_default();
+    let mode = GuildSettings::from_context(ctx).await.rank_mode(server_id).await?;
+
+    if target.trim().eq_ignore_ascii_case("all") {
+        if !cmd.confirm(ctx, "This wipes the experience of **everyone** on this server and takes away their rank roles. Are you sure?").await? {
+            return Ok(());
+        }
+
+        // Who had a rank has to be known before the rows are gone.
+        let users = db.get_users(server_id).await?;
+        let removed = db.reset_exp(server_id, None).await?;
+        audit(db.as_ref(), server_id, cmd.author().id, None, "reset", format!("{} members", removed)).await?;
+
+        let mut progress = cmd.send_message(&ctx.http, |m| m
+            .content(format!("Reset {} members. Now removing rank roles, please wait warmly...", removed))
+        ).await?;
+
+        let mut failed = 0;
+        for u in users.iter().filter(|u| u.role_id.is_some()) {
+            if let MemberSync::Failed = sync_member(&ctx.http, db.as_ref(), server_id, u.user, mode).await? {
+                failed += 1;
+            }
+        }
+
+        let note = if failed > 0 { format!(" I couldn't update the roles of {} of them.", failed) } else { String::new() };
+        progress.edit(&ctx.http, |m| m.content(format!("Reset {} members and removed their rank roles.{}", removed, note))).await?;
+    } else {
+        let user = match target.trim().parse::<UserId>() {
+            Ok(user) => user,
+            Err(_) => {
+                cmd.reply(&ctx.http, "That should be someone on this server, or all.").await?;
+                return Ok(());
+            }
+        };
+
+        if !cmd.confirm(ctx, format!("This wipes the experience of <@{}> and takes away their rank roles. Are you sure?", user)).await? {
+            return Ok(());
+        }
+
+        let before = db.get_xp(server_id, user).await?;
+        db.reset_exp(server_id, Some(user)).await?;
+        let sync = sync_member(&ctx.http, db.as_ref(), server_id, user, mode).await?;
+        audit(db.as_ref(), server_id, cmd.author().id, Some(user), "reset",
+            format!("level {} ({} xp) → nothing", before.level, before.xp)).await?;
+
+        cmd.reply(&ctx.http, format!("Reset <@{}>.{}", user, sync_note(&sync))).await?;
+    }
+
+    Ok(())
+}
+
+pub static AUDIT_COMMAND: CowCommand = CowCommand {
+    only_in_guilds: true,
+    permissions: Permissions::ADMINISTRATOR,
+    ..CowCommand::new("audit", "Shows the latest changes admins made to experience and levels by hand.", audit_log)
+};
+
+#[command]
+pub async fn audit_log(ctx: &Context, cmd: &Invocation) -> CommandResult {
+    if let Some(server_id) = cmd.guild_id() {
+        let db = db!(ctx);
+        let entries = db.get_audit(server_id, AUDIT_ENTRIES).await?;
+
+        let log = entries.iter()
+            .map(|entry| {
+                let when = entry.created_at.map(|at| at.format("`%Y-%m-%d %H:%M`").to_string()).unwrap_or_default();
+                let user = entry.user.map(|user| format!("<@{}>", user)).unwrap_or_else(|| "everyone".to_string());
+                format!("{} <@{}> **{}** {}: {}", when, entry.moderator, entry.action, user, entry.detail)
+            })
+            .reduce(|a, b| format!("{}\n{}", a, b))
+            .unwrap_or_else(|| "Nothing has been changed by hand yet.".to_string());
+
+        cmd.send_message(&ctx.http, |m| m.embed(|e| e
+            .title("Experience Audit Log")
+            .description(log)
+        )).await?;
+    } else {
+        cmd.reply(&ctx.http, "This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
@@ -1,15 +1,28 @@
 mod roles;
 mod diagnostics;
+mod xp;
+mod multipliers;
+mod manage;
+mod import;
+pub mod role_sync;
+pub mod reconciler;
+mod rewards;
 
-use serenity::framework::standard::macros::group;
-
+use crate::services::cow_framework::CowGroup;
 use roles::*;
 use diagnostics::*;
+use xp::XP_GROUP;
+use multipliers::MULTIPLIER_GROUP;
+use manage::{LEVEL_GROUP, RESET_COMMAND, AUDIT_COMMAND};
+use import::IMPORT_COMMAND;
+use rewards::REWARDS_GROUP;
 
-#[group]
-#[prefixes("rankconfig", "rc")]
-#[description = "Configuration to manage ranks and levelling on the server."]
-#[summary = "Rank configuration"]
-#[default_command(list)]
-#[commands(list, add, remove, scan, fix)]
-struct RankConfig;
\ No newline at end of file
+pub static RANKCONFIG_GROUP: CowGroup = CowGroup {
+    name: "RankConfig",
+    prefixes: &["rankconfig", "rc"],
+    description: "Configuration to manage ranks and levelling on the server.",
+    summary: "Rank configuration",
+    default_command: Some(&LIST_COMMAND),
+    commands: &[&LIST_COMMAND, &ADD_COMMAND, &REMOVE_COMMAND, &SCAN_COMMAND, &RESET_COMMAND, &AUDIT_COMMAND, &IMPORT_COMMAND],
+    sub_groups: &[&FIX_GROUP, &XP_GROUP, &MULTIPLIER_GROUP, &LEVEL_GROUP, &REWARDS_GROUP]
+};
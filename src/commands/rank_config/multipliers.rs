@@ -0,0 +1,157 @@
+use serenity::{
+    client::Context,
+    model::{
+        id::{
+            ChannelId,
+            RoleId
+        },
+        guild::Guild,
+        permissions::Permissions
+    },
+    framework::standard::CommandResult,
+    utils::MessageBuilder
+};
+use crate::models::db_models::{MultiplierTarget, XpMultiplier};
+use crate::services::cache::DbCache;
+use crate::services::cow_framework::{command, CowCommand, CowGroup, CommandOption, OptionKind, Invocation};
+
+pub static MULTIPLIER_GROUP: CowGroup = CowGroup {
+    name: "Multiplier",
+    prefixes: &["multiplier", "multipliers", "mult"],
+    description: "Give more or less experience in certain channels, or to members with certain roles.",
+    summary: "XP multipliers",
+    default_command: Some(&LIST_COMMAND),
+    commands: &[&LIST_COMMAND, &SET_COMMAND, &CLEAR_COMMAND],
+    sub_groups: &[]
+};
+
+const MAX_MULTIPLIER: f64 = 10.0;
+
+// Channels and roles both come in as mentions or IDs; names are only looked up for roles.
+fn find_target(guild: &Guild, input: &str) -> Option<MultiplierTarget> {
+    let input = input.trim();
+    if let Ok(channel) = input.parse::<ChannelId>() {
+        if guild.channels.contains_key(&channel) {
+            return Some(MultiplierTarget::Channel(channel));
+        }
+    }
+    if let Ok(role) = input.parse::<RoleId>() {
+        if guild.roles.contains_key(&role) {
+            return Some(MultiplierTarget::Role(role));
+        }
+    }
+
+    guild.role_by_name(input).map(|role| MultiplierTarget::Role(role.id))
+}
+
+fn mention(target: MultiplierTarget) -> String {
+    match target {
+        MultiplierTarget::Channel(channel) => format!("<#{}>", channel),
+        MultiplierTarget::Role(role) => format!("<@&{}>", role)
+    }
+}
+
+pub static LIST_COMMAND: CowCommand = CowCommand {
+    only_in_guilds: true,
+    permissions: Permissions::ADMINISTRATOR,
+    ..CowCommand::new("list", "List the experience multipliers on this server.", list)
+};
+
+#[command]
+pub async fn list(ctx: &Context, cmd: &Invocation) -> CommandResult {
+    let cache = DbCache::from_context(ctx).await;
+    if let Some(guild_id) = cmd.guild_id() {
+        let multipliers = cache.get_multipliers(guild_id).await?;
+        let section = |roles: bool| multipliers.iter()
+            .filter(|m| matches!(m.target, MultiplierTarget::Role(_)) == roles)
+            .map(|m| format!("{}: {}x", mention(m.target), m.multiplier))
+            .reduce(|a, b| format!("{}\n{}", a, b))
+            .unwrap_or_else(|| "None".to_string());
+
+        cmd.send_message(&ctx.http, |m| m.embed(|e| e
+            .title("Experience Multipliers")
+            .field("Channels", section(false), false)
+            .field("Roles", section(true), false)
+            .footer(|f| f.text("A message gets its channel's multiplier times the multiplier of every role its author has."))
+        )).await?;
+    } else {
+        cmd.reply(&ctx.http, "This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
+
+pub static SET_COMMAND: CowCommand = CowCommand {
+    usage: Some("<channel or role> <multiplier>"),
+    options: &[
+        CommandOption::new("target", "The channel or role, as a mention, ID or (for roles) name.", OptionKind::String).required(),
+        CommandOption::new("multiplier", "How much to multiply experience by, like 0.5 or 2.", OptionKind::Number).required()
+    ],
+    only_in_guilds: true,
+    permissions: Permissions::ADMINISTRATOR,
+    ..CowCommand::new("set", "Set the experience multiplier for a channel or role.", set)
+};
+
+#[command]
+pub async fn set(ctx: &Context, cmd: &Invocation) -> CommandResult {
+    let cache = DbCache::from_context(ctx).await;
+    if let Some(guild) = cmd.guild(ctx).await {
+        let input = cmd.arg::<String>("target").unwrap_or_default();
+        let target = match find_target(&guild, &input) {
+            Some(target) => target,
+            None => {
+                let content = MessageBuilder::new().push("Could not find a channel or role on this server matching \"").push_safe(input).push("\"!").build();
+                cmd.say(&ctx.http, content).await?;
+                return Ok(());
+            }
+        };
+
+        match cmd.arg::<f64>("multiplier") {
+            Some(multiplier) if (0.0..=MAX_MULTIPLIER).contains(&multiplier) => {
+                cache.set_multiplier(guild.id, &XpMultiplier { target, multiplier }).await?;
+                let place = if matches!(target, MultiplierTarget::Role(_)) { "for" } else { "in" };
+                cmd.say(&ctx.http, format!("Experience {} {} is now multiplied by {}.", place, mention(target), multiplier)).await?;
+            },
+            _ => {
+                cmd.say(&ctx.http, format!("The multiplier must be between 0 and {}.", MAX_MULTIPLIER)).await?;
+            }
+        }
+    } else {
+        cmd.reply(&ctx.http, "This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
+
+pub static CLEAR_COMMAND: CowCommand = CowCommand {
+    aliases: &["remove"],
+    usage: Some("<channel or role>"),
+    options: &[CommandOption::new("target", "The channel or role, as a mention, ID or (for roles) name.", OptionKind::String).required()],
+    only_in_guilds: true,
+    permissions: Permissions::ADMINISTRATOR,
+    ..CowCommand::new("clear", "Remove the experience multiplier from a channel or role.", clear)
+};
+
+#[command]
+pub async fn clear(ctx: &Context, cmd: &Invocation) -> CommandResult {
+    let cache = DbCache::from_context(ctx).await;
+    if let Some(guild) = cmd.guild(ctx).await {
+        let input = cmd.arg::<String>("target").unwrap_or_default();
+        // Deleted channels and roles can still be cleared by ID.
+        let target = find_target(&guild, &input)
+            .or_else(|| input.trim().parse::<u64>().ok().map(|id| MultiplierTarget::Channel(ChannelId(id))));
+
+        match target {
+            Some(target) if cache.remove_multiplier(guild.id, target).await? => {
+                cmd.say(&ctx.http, format!("Removed the multiplier from {}.", mention(target))).await?;
+            },
+            _ => {
+                cmd.say(&ctx.http, "There wasn't a multiplier for that channel or role.").await?;
+            }
+        }
+    } else {
+        cmd.reply(&ctx.http, "This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
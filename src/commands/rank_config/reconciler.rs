@@ -0,0 +1,231 @@
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use log::{error, info};
+use serenity::{
+    CacheAndHttp,
+    cache::Cache,
+    client::Context,
+    http::Http,
+    model::id::GuildId,
+    prelude::{RwLock, TypeMap, TypeMapKey}
+};
+use crate::Database;
+use crate::models::error::CowError;
+use crate::services::database::Storage;
+use crate::services::guild_settings::GuildSettings;
+use crate::services::supervisor::Supervisor;
+use super::role_sync::{apply_fix, check_member, describe_fix, expected_roles, role_levels, FixKind, FixOptions, MemberCheck};
+
+const RECONCILE_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+// Each member can take a couple of role changes, so this keeps a big server from queueing hundreds of requests at once.
+const RECONCILE_BATCH_SIZE: usize = 10;
+const RECONCILE_BATCH_PAUSE: Duration = Duration::from_secs(5);
+// Embed descriptions stop at 4096.
+const MODLOG_LIMIT: usize = 4000;
+
+// Which servers are being reconciled right now, so a run after rankconfig add can't overlap the scheduled one.
+#[derive(Default)]
+pub struct RoleReconciler {
+    running: Mutex<HashSet<GuildId>>
+}
+
+impl TypeMapKey for RoleReconciler {
+    type Value = Arc<RoleReconciler>;
+}
+
+struct RunGuard<'a> {
+    reconciler: &'a RoleReconciler,
+    guild_id: GuildId
+}
+
+impl Drop for RunGuard<'_> {
+    fn drop(&mut self) {
+        self.reconciler.running().remove(&self.guild_id);
+    }
+}
+
+impl RoleReconciler {
+    pub fn new() -> Self {
+        RoleReconciler::default()
+    }
+
+    fn running(&self) -> std::sync::MutexGuard<'_, HashSet<GuildId>> {
+        self.running.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn start(&self, guild_id: GuildId) -> Option<RunGuard<'_>> {
+        if self.running().insert(guild_id) {
+            Some(RunGuard { reconciler: self, guild_id })
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Default)]
+struct Summary {
+    trivial: usize,
+    multiple: usize,
+    remove: usize,
+    demote: usize,
+    // Wrong, but not something the server's options let us fix.
+    skipped: usize,
+    // Not in the cache, and Discord didn't have them either; usually because they left.
+    unreachable: usize,
+    failed: usize,
+    changes: Vec<String>
+}
+
+// Members missing from the cache (like everyone not seen since a restart) are fetched instead.
+async fn reconcile(http: &Http, cache: &Cache, db: &dyn Storage, settings: &GuildSettings, guild_id: GuildId, options: FixOptions) -> Result<Summary, CowError> {
+    let mut summary = Summary::default();
+
+    let role_levels = role_levels(db, guild_id).await?;
+    if role_levels.is_empty() {
+        return Ok(summary);
+    }
+
+    let mode = settings.rank_mode(guild_id).await?;
+    let users = db.get_users(guild_id).await?;
+
+    let mut fixes = Vec::new();
+    for u in users {
+        let member = match cache.member(guild_id, u.user).await {
+            Some(member) => member,
+            None => match guild_id.member(http, u.user).await {
+                Ok(member) => member,
+                Err(_) => {
+                    summary.unreachable += 1;
+                    continue;
+                }
+            }
+        };
+
+        let expected = expected_roles(u.exp.level, u.role_id, &role_levels, mode);
+        match check_member(&expected, &member.roles, &role_levels, mode, options) {
+            MemberCheck::Correct => {},
+            MemberCheck::Skipped => summary.skipped += 1,
+            MemberCheck::Fix(fix) => fixes.push((member, fix))
+        }
+    }
+
+    for (index, batch) in fixes.chunks_mut(RECONCILE_BATCH_SIZE).enumerate() {
+        if index > 0 {
+            tokio::time::sleep(RECONCILE_BATCH_PAUSE).await;
+        }
+
+        for (member, fix) in batch {
+            if apply_fix(http, member, fix).await.errors > 0 {
+                summary.changes.push(format!("{} (failed)", describe_fix(member.user.id, fix)));
+                summary.failed += 1;
+                continue;
+            }
+            summary.changes.push(describe_fix(member.user.id, fix));
+
+            match fix.kind {
+                FixKind::Trivial => summary.trivial += 1,
+                FixKind::Multiple => summary.multiple += 1,
+                FixKind::Remove => summary.remove += 1,
+                FixKind::Demote => summary.demote += 1
+            }
+        }
+    }
+
+    Ok(summary)
+}
+
+async fn post_modlog(http: &Http, settings: &GuildSettings, guild_id: GuildId, summary: &Summary) -> Result<(), CowError> {
+    let channel_id = match settings.modlog_channel(guild_id).await? {
+        Some(channel_id) => channel_id,
+        None => return Ok(())
+    };
+
+    let mut content = String::new();
+    for (index, line) in summary.changes.iter().enumerate() {
+        if content.len() + line.len() + 1 > MODLOG_LIMIT {
+            content += &format!("...and {} more.", summary.changes.len() - index);
+            break;
+        }
+        content += line;
+        content += "\n";
+    }
+
+    channel_id.send_message(http, |m| m.embed(|e| e
+        .title("Automatic Role Fix")
+        .description(content)
+        .footer(|f| f.text(format!("Trivial: {}, multiple: {}, removed: {}, demoted: {}, failed: {}, left for a manual fix: {}, couldn't be found: {}",
+            summary.trivial, summary.multiple, summary.remove, summary.demote, summary.failed, summary.skipped, summary.unreachable)))
+    )).await?;
+
+    Ok(())
+}
+
+// Does nothing for servers that haven't turned on auto_fix, or that are already being reconciled.
+pub async fn reconcile_guild(data: &Arc<RwLock<TypeMap>>, http: &Http, cache: &Cache, guild_id: GuildId) {
+    let (db, settings, reconciler) = {
+        let data = data.read().await;
+        (
+            data.get::<Database>().expect("Couldn't find database").clone(),
+            data.get::<GuildSettings>().expect("Expected GuildSettings in TypeMap.").clone(),
+            data.get::<RoleReconciler>().expect("Expected RoleReconciler in TypeMap.").clone()
+        )
+    };
+
+    let options = match settings.auto_fix(guild_id).await {
+        Ok(Some(options)) => options,
+        Ok(None) => return,
+        Err(ex) => {
+            error!("Failed getting the auto fix settings for {}: {}", guild_id, ex);
+            return;
+        }
+    };
+
+    let _running = match reconciler.start(guild_id) {
+        Some(guard) => guard,
+        None => {
+            info!("Skipped fixing roles in {}, since it's already in progress.", guild_id);
+            return;
+        }
+    };
+
+    let summary = match reconcile(http, cache, db.as_ref(), &settings, guild_id, options).await {
+        Ok(summary) => summary,
+        Err(ex) => {
+            error!("Failed fixing roles in {}: {}", guild_id, ex);
+            return;
+        }
+    };
+
+    if summary.changes.is_empty() {
+        return;
+    }
+
+    info!("Fixed roles for {} member(s) in {}, and couldn't find {}.", summary.changes.len(), guild_id, summary.unreachable);
+    if let Err(ex) = post_modlog(http, &settings, guild_id, &summary).await {
+        error!("Failed posting role fixes to the mod log for {}: {}", guild_id, ex);
+    }
+}
+
+// For after the ranks themselves change. Runs in the background, since big servers take a while,
+// but holds a WorkGuard so shutting down waits for it.
+pub async fn after_rank_change(ctx: &Context, guild_id: GuildId) {
+    let work = Supervisor::from_context(ctx).await.begin_work();
+    let data = ctx.data.clone();
+    let http = ctx.http.clone();
+    let cache = ctx.cache.clone();
+    tokio::spawn(async move {
+        let _work = work;
+        reconcile_guild(&data, &http, &cache, guild_id).await;
+    });
+}
+
+pub async fn reconcile_roles(data: Arc<RwLock<TypeMap>>, cache_and_http: Arc<CacheAndHttp>) {
+    loop {
+        tokio::time::sleep(RECONCILE_INTERVAL).await;
+
+        for guild_id in cache_and_http.cache.guilds().await {
+            reconcile_guild(&data, &cache_and_http.http, &cache_and_http.cache, guild_id).await;
+        }
+    }
+}
@@ -0,0 +1,210 @@
+use serenity::{
+    client::Context,
+    model::{
+        id::ChannelId,
+        permissions::Permissions
+    },
+    framework::standard::CommandResult
+};
+use crate::models::level_up::validate_template;
+use crate::models::rewards::{RewardKind, MAX_BOOST_MINUTES, MAX_BOOST_MULTIPLIER};
+use crate::services::cache::DbCache;
+use crate::services::cow_framework::{command, CowCommand, CowGroup, CommandOption, OptionKind, Invocation};
+
+pub static REWARDS_GROUP: CowGroup = CowGroup {
+    name: "Rewards",
+    prefixes: &["rewards", "reward"],
+    description: "Send a message or give an experience boost when someone reaches a level.",
+    summary: "Level rewards",
+    default_command: Some(&LIST_COMMAND),
+    commands: &[&LIST_COMMAND, &DM_COMMAND, &POST_COMMAND, &BOOST_COMMAND, &REMOVE_COMMAND],
+    sub_groups: &[]
+};
+
+// Saves the reward and tells them its ID, which is what remove takes.
+async fn add(ctx: &Context, cmd: &Invocation, min_level: i32, kind: RewardKind) -> CommandResult {
+    let cache = DbCache::from_context(ctx).await;
+    if let Some(guild_id) = cmd.guild_id() {
+        let id = cache.add_reward(guild_id, min_level, &kind).await?;
+        cmd.say(&ctx.http, format!("Added reward #{} for level {}: {}", id, min_level, kind)).await?;
+    } else {
+        cmd.reply(&ctx.http, "This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
+
+// Levels start at 0, but nobody reaches that one.
+fn level_arg(cmd: &Invocation) -> Option<i32> {
+    cmd.arg::<i32>("level").filter(|level| *level > 0)
+}
+
+pub static LIST_COMMAND: CowCommand = CowCommand {
+    only_in_guilds: true,
+    permissions: Permissions::ADMINISTRATOR,
+    ..CowCommand::new("list", "List the level rewards on this server.", list)
+};
+
+#[command]
+pub async fn list(ctx: &Context, cmd: &Invocation) -> CommandResult {
+    let cache = DbCache::from_context(ctx).await;
+    if let Some(guild_id) = cmd.guild_id() {
+        let content = cache.get_rewards(guild_id).await?
+            .iter()
+            .map(|r| format!("`#{}` Level {} - {}", r.id, r.min_level, r.kind))
+            .reduce(|a, b| format!("{}\n{}", a, b))
+            .unwrap_or_else(|| "There are no level rewards on this server.".to_string());
+
+        cmd.send_message(&ctx.http, |m| m.embed(|e| e
+            .title("Level Rewards")
+            .description(content)
+            .footer(|f| f.text("Messages can use {user}, {level}, {old_level} and {role}. Sentences with {role} are left out when there's no new rank."))
+        )).await?;
+    } else {
+        cmd.reply(&ctx.http, "This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
+
+pub static DM_COMMAND: CowCommand = CowCommand {
+    usage: Some("<level> <message>"),
+    options: &[
+        CommandOption::new("level", "The level that earns this reward.", OptionKind::Integer).required(),
+        CommandOption::new("message", "What to send them. You can use {user}, {level}, {old_level} and {role}.", OptionKind::String).required()
+    ],
+    only_in_guilds: true,
+    permissions: Permissions::ADMINISTRATOR,
+    ..CowCommand::new("dm", "DM a message to members when they reach a level.", dm)
+};
+
+#[command]
+pub async fn dm(ctx: &Context, cmd: &Invocation) -> CommandResult {
+    let min_level = match level_arg(cmd) {
+        Some(level) => level,
+        None => {
+            cmd.say(&ctx.http, "The level must be at least 1.").await?;
+            return Ok(());
+        }
+    };
+
+    match validate_template(&cmd.arg::<String>("message").unwrap_or_default()) {
+        Ok(message) => add(ctx, cmd, min_level, RewardKind::DirectMessage(message)).await,
+        Err(reason) => {
+            cmd.say(&ctx.http, reason).await?;
+            Ok(())
+        }
+    }
+}
+
+pub static POST_COMMAND: CowCommand = CowCommand {
+    usage: Some("<level> <channel> <message>"),
+    options: &[
+        CommandOption::new("level", "The level that earns this reward.", OptionKind::Integer).required(),
+        CommandOption::new("channel", "Where to post it.", OptionKind::Channel).required(),
+        CommandOption::new("message", "What to post. You can use {user}, {level}, {old_level} and {role}.", OptionKind::String).required()
+    ],
+    only_in_guilds: true,
+    permissions: Permissions::ADMINISTRATOR,
+    ..CowCommand::new("post", "Post a message in a channel when someone reaches a level.", post)
+};
+
+#[command]
+pub async fn post(ctx: &Context, cmd: &Invocation) -> CommandResult {
+    let min_level = match level_arg(cmd) {
+        Some(level) => level,
+        None => {
+            cmd.say(&ctx.http, "The level must be at least 1.").await?;
+            return Ok(());
+        }
+    };
+
+    let channel = cmd.arg::<ChannelId>("channel");
+    let in_guild = match (cmd.guild(ctx).await, channel) {
+        (Some(guild), Some(channel)) => guild.channels.contains_key(&channel),
+        _ => false
+    };
+    let channel = match channel {
+        Some(channel) if in_guild => channel,
+        _ => {
+            cmd.say(&ctx.http, "That channel isn't on this server.").await?;
+            return Ok(());
+        }
+    };
+
+    match validate_template(&cmd.arg::<String>("message").unwrap_or_default()) {
+        Ok(message) => add(ctx, cmd, min_level, RewardKind::ChannelPost { channel, message }).await,
+        Err(reason) => {
+            cmd.say(&ctx.http, reason).await?;
+            Ok(())
+        }
+    }
+}
+
+pub static BOOST_COMMAND: CowCommand = CowCommand {
+    usage: Some("<level> <multiplier> <minutes>"),
+    options: &[
+        CommandOption::new("level", "The level that earns this reward.", OptionKind::Integer).required(),
+        CommandOption::new("multiplier", "How much to multiply their experience by, like 1.5 or 2.", OptionKind::Number).required(),
+        CommandOption::new("minutes", "How long the boost lasts.", OptionKind::Integer).required()
+    ],
+    only_in_guilds: true,
+    permissions: Permissions::ADMINISTRATOR,
+    ..CowCommand::new("boost", "Multiply the experience members earn for a while after they reach a level.", boost)
+};
+
+#[command]
+pub async fn boost(ctx: &Context, cmd: &Invocation) -> CommandResult {
+    let min_level = match level_arg(cmd) {
+        Some(level) => level,
+        None => {
+            cmd.say(&ctx.http, "The level must be at least 1.").await?;
+            return Ok(());
+        }
+    };
+
+    let multiplier = match cmd.arg::<f64>("multiplier") {
+        Some(multiplier) if multiplier > 1.0 && multiplier <= MAX_BOOST_MULTIPLIER => multiplier,
+        _ => {
+            cmd.say(&ctx.http, format!("The multiplier must be more than 1 and at most {}.", MAX_BOOST_MULTIPLIER)).await?;
+            return Ok(());
+        }
+    };
+
+    match cmd.arg::<i32>("minutes") {
+        Some(minutes) if (1..=MAX_BOOST_MINUTES).contains(&minutes) =>
+            add(ctx, cmd, min_level, RewardKind::XpBoost { multiplier, minutes }).await,
+        _ => {
+            cmd.say(&ctx.http, format!("The boost must last between 1 and {} minutes.", MAX_BOOST_MINUTES)).await?;
+            Ok(())
+        }
+    }
+}
+
+pub static REMOVE_COMMAND: CowCommand = CowCommand {
+    aliases: &["delete"],
+    usage: Some("<id>"),
+    options: &[CommandOption::new("id", "The reward's number, from the list.", OptionKind::Integer).required()],
+    only_in_guilds: true,
+    permissions: Permissions::ADMINISTRATOR,
+    ..CowCommand::new("remove", "Remove a level reward.", remove)
+};
+
+#[command]
+pub async fn remove(ctx: &Context, cmd: &Invocation) -> CommandResult {
+    let cache = DbCache::from_context(ctx).await;
+    if let Some(guild_id) = cmd.guild_id() {
+        match cmd.arg::<i32>("id") {
+            Some(id) if cache.remove_reward(guild_id, id).await? => {
+                cmd.say(&ctx.http, format!("Removed reward #{}.", id)).await?;
+            },
+            _ => {
+                cmd.say(&ctx.http, "There isn't a reward with that number on this server.").await?;
+            }
+        }
+    } else {
+        cmd.reply(&ctx.http, "This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
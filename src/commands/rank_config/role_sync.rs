@@ -0,0 +1,249 @@
+use std::collections::{HashMap, HashSet};
+use log::error;
+use serenity::{
+    http::Http,
+    model::{
+        guild::Member,
+        id::{GuildId, RoleId, UserId}
+    }
+};
+use crate::models::db_models::RoleChange;
+use crate::models::error::CowError;
+use crate::models::rank_mode::RankMode;
+use crate::services::database::Storage;
+
+/*
+    There are several invalid cases we have to worry about:
+    - The user shouldn't have the role, and yet they do have conflicting roles (non-trivial) -> remove
+    - The user should have the role, and:
+      - they *do not* have any conflicting roles (trivial)
+      - they have one conflicting role
+        - and they should be higher up (trivial)
+        - and they should be lower down (non-trivial) -> demote
+      - they have multiple conflicting roles (non-trivial) -> multiple
+
+    When ranks stack, holding several is the point, so it comes down to:
+    - they're missing some they've reached (trivial)
+    - they have some they haven't reached (non-trivial) -> demote, or remove if they shouldn't have any
+
+     The trivial cases are always fixed, and the non-trivial cases only when asked to.
+ */
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FixOptions {
+    pub multiple: bool,
+    pub remove: bool,
+    pub demote: bool
+}
+
+impl FixOptions {
+    pub fn parse(options: &str) -> Self {
+        let mut out = FixOptions::default();
+        for arg in options.to_lowercase().split_whitespace() {
+            out.multiple |= arg.contains("multiple");
+            out.remove |= arg.contains("remove");
+            out.demote |= arg.contains("demote");
+        }
+        out
+    }
+
+    // For when an admin changed someone's level on purpose, so whatever they end up with is right.
+    pub fn all() -> Self {
+        FixOptions { multiple: true, remove: true, demote: true }
+    }
+
+    // Whether a fix checked with every option on would still go ahead with these ones.
+    pub fn allows(&self, kind: FixKind) -> bool {
+        match kind {
+            FixKind::Trivial => true,
+            FixKind::Multiple => self.multiple,
+            FixKind::Remove => self.remove,
+            FixKind::Demote => self.demote
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FixKind {
+    Trivial,
+    Multiple,
+    Remove,
+    Demote
+}
+
+pub struct RoleFix {
+    pub kind: FixKind,
+    pub add: Vec<RoleId>,
+    pub remove: Vec<RoleId>
+}
+
+pub enum MemberCheck {
+    Correct,
+    // Wrong, but fixing it needs an option that wasn't given.
+    Skipped,
+    Fix(RoleFix)
+}
+
+// Rank roles to the level they start at.
+pub type RoleLevels = HashMap<RoleId, i32>;
+
+pub async fn role_levels(db: &dyn Storage, guild_id: GuildId) -> Result<RoleLevels, CowError> {
+    Ok(db.get_roles(guild_id).await?.into_iter()
+        .filter_map(|r| Some((r.role_id?, r.min_level)))
+        .collect())
+}
+
+// The rank roles someone at this level should have. The highest one comes from the database, since that's what
+// decides it when ranks don't stack.
+pub fn expected_roles(level: i32, highest: Option<RoleId>, role_levels: &RoleLevels, mode: RankMode) -> Vec<RoleId> {
+    match mode {
+        RankMode::Exclusive => highest.into_iter().collect(),
+        RankMode::Stacking if level > 0 => {
+            let mut roles = role_levels.iter().filter(|(_, min_level)| **min_level <= level).collect::<Vec<_>>();
+            roles.sort_by_key(|(role, min_level)| (**min_level, **role));
+            roles.into_iter().map(|(role, _)| *role).collect()
+        },
+        RankMode::Stacking => Vec::new()
+    }
+}
+
+pub fn check_member(expected: &[RoleId], member_roles: &[RoleId], role_levels: &RoleLevels, mode: RankMode, options: FixOptions) -> MemberCheck {
+    let ranks = member_roles.iter().filter(|r| role_levels.contains_key(r)).cloned().collect::<HashSet<_>>();
+
+    match mode {
+        RankMode::Exclusive => check_exclusive(expected.first().copied(), ranks, role_levels, options),
+        RankMode::Stacking => check_stacking(expected, ranks, options)
+    }
+}
+
+fn check_exclusive(expected: Option<RoleId>, ranks: HashSet<RoleId>, role_levels: &RoleLevels, options: FixOptions) -> MemberCheck {
+    match expected {
+        Some(expected_role) => {
+            if ranks.contains(&expected_role) && ranks.len() == 1 {
+                return MemberCheck::Correct; // One role and it's the expected one
+            }
+
+            if ranks.is_empty() { // They do not have the role, and need it
+                MemberCheck::Fix(RoleFix { kind: FixKind::Trivial, add: vec![expected_role], remove: Vec::new() })
+            } else if ranks.len() == 1 { // They have another role in place
+                let existing_role = ranks.into_iter().next().unwrap();
+                let promote = role_levels[&existing_role] < role_levels[&expected_role];
+                if promote || options.demote {
+                    let kind = if promote { FixKind::Trivial } else { FixKind::Demote };
+                    MemberCheck::Fix(RoleFix { kind, add: vec![expected_role], remove: vec![existing_role] })
+                } else {
+                    MemberCheck::Skipped
+                }
+            } else if options.multiple { // We have multiple to deal with
+                let add = if ranks.contains(&expected_role) { Vec::new() } else { vec![expected_role] };
+                let remove = ranks.into_iter().filter(|r| *r != expected_role).collect();
+                MemberCheck::Fix(RoleFix { kind: FixKind::Multiple, add, remove })
+            } else {
+                MemberCheck::Skipped
+            }
+        },
+        None => {
+            if ranks.is_empty() {
+                MemberCheck::Correct // No roles
+            } else if options.remove { // Has a role, when they shouldn't
+                MemberCheck::Fix(RoleFix { kind: FixKind::Remove, add: Vec::new(), remove: ranks.into_iter().collect() })
+            } else {
+                MemberCheck::Skipped
+            }
+        }
+    }
+}
+
+fn check_stacking(expected: &[RoleId], ranks: HashSet<RoleId>, options: FixOptions) -> MemberCheck {
+    let add = expected.iter().filter(|r| !ranks.contains(r)).cloned().collect::<Vec<_>>();
+    let remove = ranks.into_iter().filter(|r| !expected.contains(r)).collect::<Vec<_>>();
+
+    if remove.is_empty() {
+        if add.is_empty() {
+            MemberCheck::Correct
+        } else {
+            MemberCheck::Fix(RoleFix { kind: FixKind::Trivial, add, remove })
+        }
+    } else if expected.is_empty() { // Has roles, when they shouldn't have any
+        if options.remove { MemberCheck::Fix(RoleFix { kind: FixKind::Remove, add, remove }) } else { MemberCheck::Skipped }
+    } else if options.demote { // Has roles above their level
+        MemberCheck::Fix(RoleFix { kind: FixKind::Demote, add, remove })
+    } else {
+        MemberCheck::Skipped
+    }
+}
+
+// What a fix actually did, since some of it can fail.
+pub struct FixOutcome {
+    pub applied: Vec<RoleChange>,
+    pub errors: usize
+}
+
+// Removes before adding, so nobody is left with two ranks if adding fails.
+pub async fn apply_fix(http: &Http, member: &mut Member, fix: &RoleFix) -> FixOutcome {
+    let mut outcome = FixOutcome { applied: Vec::new(), errors: 0 };
+    let user = member.user.id;
+
+    for role in &fix.remove {
+        match member.remove_role(http, role).await {
+            Ok(_) => outcome.applied.push(RoleChange { user, role: *role, added: false }),
+            Err(ex) => {
+                error!("Failed to remove role {} from {}: {}", role, user, ex);
+                outcome.errors += 1;
+            }
+        }
+    }
+
+    for role in &fix.add {
+        match member.add_role(http, role).await {
+            Ok(_) => outcome.applied.push(RoleChange { user, role: *role, added: true }),
+            Err(ex) => {
+                error!("Failed to add role {} to {}: {}", role, user, ex);
+                outcome.errors += 1;
+            }
+        }
+    }
+
+    outcome
+}
+
+// One line per member, like "@someone: +@Rank 2 -@Rank 1".
+pub fn describe_fix(user_id: UserId, fix: &RoleFix) -> String {
+    let mut line = format!("<@{}>:", user_id);
+    fix.add.iter().for_each(|r| line += &format!(" +<@&{}>", r));
+    fix.remove.iter().for_each(|r| line += &format!(" -<@&{}>", r));
+    line
+}
+
+pub enum MemberSync {
+    Unchanged,
+    // Not in the server anymore, so there's nothing to change.
+    Left,
+    Fixed,
+    Failed
+}
+
+// Puts one member's rank roles in line with their level the same way fix does, with every option on.
+pub async fn sync_member(http: &Http, db: &dyn Storage, guild_id: GuildId, user_id: UserId, mode: RankMode) -> Result<MemberSync, CowError> {
+    let mut member = match guild_id.member(http, user_id).await {
+        Ok(member) => member,
+        Err(_) => return Ok(MemberSync::Left)
+    };
+
+    // Anyone without a row (like after a reset) shouldn't have a rank at all.
+    let experience = db.get_xp(guild_id, user_id).await?;
+    let highest = if experience.level > 0 { db.get_highest_role(guild_id, experience.level).await? } else { None };
+    let role_levels = role_levels(db, guild_id).await?;
+    let expected = expected_roles(experience.level, highest, &role_levels, mode);
+    let roles = member.roles.clone();
+
+    match check_member(&expected, &roles, &role_levels, mode, FixOptions::all()) {
+        MemberCheck::Fix(fix) => {
+            if apply_fix(http, &mut member, &fix).await.errors > 0 {
+                Ok(MemberSync::Failed)
+            } else {
+                Ok(MemberSync::Fixed)
+            }
+        },
+        _ => Ok(MemberSync::Unchanged)
+    }
+}
@@ -1,50 +1,46 @@
 use serenity::{
     client::Context,
     model::{
-        channel::Message,
         id::{
             RoleId
         },
-        guild::Guild
-    },
-    framework::standard::{
-        CommandResult,
-        macros::{
-            command
-        },
-        Args
+        guild::Guild,
+        permissions::Permissions
     },
+    framework::standard::CommandResult,
     utils::{
         MessageBuilder
     }
 };
 use crate::{Database, db};
+use crate::services::cow_framework::{command, CowCommand, CommandOption, OptionKind, Invocation};
 use log::{error};
+use super::reconciler;
 
 // Parameters: rankconfig add [min_level] [rank]
 
-async fn get_role(ctx: &Context, msg: &Message, guild: &Guild, args: &Args) -> Option<(RoleId, String)> {
+async fn get_role(ctx: &Context, cmd: &Invocation, guild: &Guild, input: &str) -> Option<(RoleId, String)> {
     let role_id: RoleId;
     let mut role_text: String;
 
-    if let Ok(role) = args.parse::<RoleId>() {
+    if let Ok(role) = input.parse::<RoleId>() {
         role_id = role;
         if let Some(role) = guild.roles.get(&role) {
             role_text = role.name.clone();
         } else {
-            if let Err(ex) = msg.channel_id.say(&ctx.http, format!("Could not find a role on this server matching <@&{}>!", role_id.as_u64())).await {
+            if let Err(ex) = cmd.say(&ctx.http, format!("Could not find a role on this server matching <@&{}>!", role_id.as_u64())).await {
                 error!("Failed to send message: {}", ex);
             }
             return None
         }
     } else {
-        role_text = args.rest().to_string();
+        role_text = input.to_string();
         if let Some(role) = guild.role_by_name(&*role_text) {
             role_id = role.id;
             role_text = role.name.clone(); // Just to make it exact.
         } else {
             let content = MessageBuilder::new().push("Could not find a role on this server matching \"").push_safe(role_text).push("\"!").build();
-            if let Err(ex) = msg.channel_id.say(&ctx.http, content).await {
+            if let Err(ex) = cmd.say(&ctx.http, content).await {
                 error!("Failed to send message: {}", ex);
             }
             return None
@@ -54,102 +50,96 @@ async fn get_role(ctx: &Context, msg: &Message, guild: &Guild, args: &Args) -> O
     Some((role_id, role_text))
 }
 
+pub static ADD_COMMAND: CowCommand = CowCommand {
+    usage: Some("<level> <role id or name>"),
+    options: &[
+        CommandOption::new("level", "The minimum level for this rank.", OptionKind::Integer).required(),
+        CommandOption::new("role", "The role, as a mention, ID or name.", OptionKind::String).required()
+    ],
+    only_in_guilds: true,
+    permissions: Permissions::ADMINISTRATOR,
+    ..CowCommand::new("add", "Add a rank to the configuration.", add)
+};
+
 #[command]
-#[description = "Add a rank to the configuration."]
-#[only_in(guilds)]
-#[usage = "<level> <role id or name>"]
-#[required_permissions("ADMINISTRATOR")]
-pub async fn add(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+pub async fn add(ctx: &Context, cmd: &Invocation) -> CommandResult {
     let db = db!(ctx);
     // So much nesting...
-    if let Some(guild) = msg.guild(&ctx.cache).await {
-        if let Ok(min_level) = args.single::<i32>() {
-            if let Some((role_id, role_text)) = get_role(ctx, msg, &guild, &args).await {
+    if let Some(guild) = cmd.guild(ctx).await {
+        if let Some(min_level) = cmd.arg::<i32>("level") {
+            if let Some((role_id, role_text)) = get_role(ctx, cmd, &guild, &cmd.arg::<String>("role").unwrap_or_default()).await {
                 // Both min_level and role_id are initialized by this point
-                match db.add_role(guild.id, &role_text, role_id, min_level).await {
-                    Ok(success) => {
-                        if success {
-                            msg.channel_id.say(&ctx.http, format!("Successfully added <@&{}> with minimum level {}.", role_id.as_u64(), min_level)).await?;
-                        } else {
-                            msg.channel_id.say(&ctx.http, format!("There is a duplicate role with minimum level {}.", min_level)).await?;
-                        }
-                    }
-                    Err(ex) => {
-                        error!("Failed to add role for server: {}", ex);
-                        msg.channel_id.say(&ctx.http, "Failed to add role to the server.").await?;
-                    }
+                if db.add_role(guild.id, &role_text, role_id, min_level).await? {
+                    cmd.say(&ctx.http, format!("Successfully added <@&{}> with minimum level {}.", role_id.as_u64(), min_level)).await?;
+                    reconciler::after_rank_change(ctx, guild.id).await;
+                } else {
+                    cmd.say(&ctx.http, format!("There is a duplicate role with minimum level {}.", min_level)).await?;
                 }
             }
         } else {
-            msg.channel_id.say(&ctx.http, "The first argument should be a positive integer, representing the minimum level for this rank.").await?;
+            cmd.say(&ctx.http, "The first argument should be a positive integer, representing the minimum level for this rank.").await?;
         }
     } else {
-        msg.reply(&ctx.http, "This command can only be run in a server.").await?;
+        cmd.reply(&ctx.http, "This command can only be run in a server.").await?;
     }
 
     Ok(())
 }
 
+pub static REMOVE_COMMAND: CowCommand = CowCommand {
+    options: &[CommandOption::new("role", "The role, as a mention, ID or name.", OptionKind::String).required()],
+    only_in_guilds: true,
+    permissions: Permissions::ADMINISTRATOR,
+    ..CowCommand::new("remove", "Remove a rank from the configuration.", remove)
+};
+
 #[command]
-#[description = "Remove a rank from the configuration."]
-#[only_in(guilds)]
-#[required_permissions("ADMINISTRATOR")]
-pub async fn remove(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+pub async fn remove(ctx: &Context, cmd: &Invocation) -> CommandResult {
     let db = db!(ctx);
     // So much nesting...
-    if let Some(guild) = msg.guild(&ctx.cache).await {
-        if let Some((role_id, _)) = get_role(ctx, msg, &guild, &args).await {
-            match db.remove_role(guild.id, role_id).await {
-                Ok(success) => {
-                    if success {
-                        msg.channel_id.say(&ctx.http, format!("Successfully removed <@&{}>.", role_id.as_u64())).await?;
-                    } else {
-                        msg.channel_id.say(&ctx.http, "A rank didn't exist for this role.".to_string()).await?;
-                    }
-                }
-                Err(ex) => {
-                    error!("Failed to remove role for server: {}", ex);
-                    msg.channel_id.say(&ctx.http, "Failed to remove role from the server.").await?;
-                }
+    if let Some(guild) = cmd.guild(ctx).await {
+        if let Some((role_id, _)) = get_role(ctx, cmd, &guild, &cmd.arg::<String>("role").unwrap_or_default()).await {
+            if db.remove_role(guild.id, role_id).await? {
+                cmd.say(&ctx.http, format!("Successfully removed <@&{}>.", role_id.as_u64())).await?;
+                reconciler::after_rank_change(ctx, guild.id).await;
+            } else {
+                cmd.say(&ctx.http, "A rank didn't exist for this role.".to_string()).await?;
             }
         }
     } else {
-        msg.reply(&ctx.http, "This command can only be run in a server.").await?;
+        cmd.reply(&ctx.http, "This command can only be run in a server.").await?;
     }
 
     Ok(())
 }
 
+pub static LIST_COMMAND: CowCommand = CowCommand {
+    only_in_guilds: true,
+    permissions: Permissions::ADMINISTRATOR,
+    ..CowCommand::new("list", "List the current ranks on this server.", list)
+};
+
 #[command]
-#[description = "List the current ranks on this server."]
-#[only_in(guilds)]
-#[required_permissions("ADMINISTRATOR")]
-pub async fn list(ctx: &Context, msg: &Message) -> CommandResult {
+pub async fn list(ctx: &Context, cmd: &Invocation) -> CommandResult {
     let db = db!(ctx);
-    if let Some(guild_id) = msg.guild_id {
-        match db.get_roles(guild_id).await {
-            Ok(items) => {
-                if let Err(ex) = msg.channel_id.send_message(&ctx.http, |m| {m.embed(|e| {
-                    e.title("Rank to Level Mapping")
-                        .description(
-                            items.into_iter()
-                                .map(|i| {
-                                    let mut content = format!("{}: <no role> at level {}", i.name, i.min_level);
-                                    if let Some(role_id) = i.role_id {
-                                        content = format!("{}: <@&{}> at level {}", i.name, role_id, i.min_level);
-                                    }
-                                    content
-                                })
-                                .reduce(|a, b| {format!("{}\n{}", a, b)})
-                                .unwrap_or_else(|| "No roles are registered on this server.".to_string())
-                        )})}).await {
-                    error!("Failed to send message to server: {}", ex);
-                }
-            },
-            Err(ex) => error!("Failed to get roles for server: {}", ex)
-        }
+    if let Some(guild_id) = cmd.guild_id() {
+        let items = db.get_roles(guild_id).await?;
+        cmd.send_message(&ctx.http, |m| {m.embed(|e| {
+            e.title("Rank to Level Mapping")
+                .description(
+                    items.into_iter()
+                        .map(|i| {
+                            let mut content = format!("{}: <no role> at level {}", i.name, i.min_level);
+                            if let Some(role_id) = i.role_id {
+                                content = format!("{}: <@&{}> at level {}", i.name, role_id, i.min_level);
+                            }
+                            content
+                        })
+                        .reduce(|a, b| {format!("{}\n{}", a, b)})
+                        .unwrap_or_else(|| "No roles are registered on this server.".to_string())
+                )})}).await?;
     } else {
-        msg.reply(&ctx.http, "This command can only be run in a server.").await?;
+        cmd.reply(&ctx.http, "This command can only be run in a server.").await?;
     }
 
     Ok(())
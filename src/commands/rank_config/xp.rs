@@ -0,0 +1,100 @@
+use serenity::{
+    client::Context,
+    model::permissions::Permissions,
+    framework::standard::CommandResult
+};
+use crate::services::cow_framework::{command, CowCommand, CowGroup, CommandOption, OptionKind, Invocation};
+use crate::services::guild_settings::GuildSettings;
+use crate::services::quality_filter::{QualityFilter, CHECKS};
+use super::manage::{GIVE_COMMAND, TAKE_COMMAND, SET_XP_COMMAND};
+
+pub static XP_GROUP: CowGroup = CowGroup {
+    name: "Xp",
+    prefixes: &["xp"],
+    description: "See how experience and levels work on this server, or change someone's experience.",
+    summary: "Experience settings",
+    default_command: Some(&PREVIEW_COMMAND),
+    commands: &[&PREVIEW_COMMAND, &FILTER_COMMAND, &GIVE_COMMAND, &TAKE_COMMAND, &SET_XP_COMMAND],
+    sub_groups: &[]
+};
+
+const MAX_PREVIEW_LEVELS: i32 = 50;
+
+pub static PREVIEW_COMMAND: CowCommand = CowCommand {
+    usage: Some("[levels]"),
+    options: &[CommandOption::new("levels", "How many levels to show, up to 50.", OptionKind::Integer)],
+    only_in_guilds: true,
+    ..CowCommand::new("preview", "Shows how much experience each level takes on this server.", preview)
+};
+
+#[command]
+pub async fn preview(ctx: &Context, cmd: &Invocation) -> CommandResult {
+    if let Some(server_id) = cmd.guild_id() {
+        let settings = GuildSettings::from_context(ctx).await.xp_settings(server_id).await?;
+        let levels = cmd.arg::<i32>("levels").unwrap_or(10).clamp(1, MAX_PREVIEW_LEVELS);
+
+        // Levels start at 1, and there's no next level once someone is at the cap.
+        let mut total: i64 = 0;
+        let mut table = (1..=levels)
+            .take_while(|level| !settings.at_cap(*level))
+            .map(|level| {
+                let needed = settings.curve.experience_for_level(level);
+                total += needed as i64;
+                format!("`{:>3} → {:<3}` {} xp ({} total)", level, level + 1, needed, total)
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        if table.is_empty() {
+            table = "Nobody can level up past the level cap.".to_string();
+        }
+
+        let cap = settings.level_cap.map(|cap| cap.to_string()).unwrap_or_else(|| "None".to_string());
+
+        cmd.send_message(&ctx.http, |m| m.embed(|e| e
+            .title("Experience Curve")
+            .description(table)
+            .field("Curve", format!("{} ({})", settings.curve.formula(), settings.curve.kind.name()), false)
+            .field("Per message", format!("{}-{} xp", settings.min_xp.min(settings.max_xp), settings.min_xp.max(settings.max_xp)), true)
+            .field("Level cap", cap, true)
+            .footer(|f| f.text("Change these with config set xp_curve, xp_min, xp_max or level_cap."))
+        )).await?;
+    } else {
+        cmd.reply(&ctx.http, "This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
+
+pub static FILTER_COMMAND: CowCommand = CowCommand {
+    aliases: &["quality"],
+    only_in_guilds: true,
+    permissions: Permissions::ADMINISTRATOR,
+    ..CowCommand::new("filter", "Shows how low-effort messages are kept from giving experience, and how many were caught.", filter)
+};
+
+#[command]
+pub async fn filter(ctx: &Context, cmd: &Invocation) -> CommandResult {
+    if let Some(server_id) = cmd.guild_id() {
+        let settings = GuildSettings::from_context(ctx).await.quality_settings(server_id).await?;
+        let rejections = QualityFilter::from_context(ctx).await.rejections(server_id);
+
+        let off = |disabled: bool, value: String| if disabled { "Off".to_string() } else { value };
+        let counts = CHECKS.iter()
+            .map(|check| format!("{}: {}", check.name(), rejections.get(check.name()).copied().unwrap_or(0)))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        cmd.send_message(&ctx.http, |m| m.embed(|e| e
+            .title("Message Filter")
+            .field("Minimum length", off(settings.min_length == 0, format!("{} characters", settings.min_length)), true)
+            .field("Max similarity", off(settings.max_similarity == 0, format!("{}%", settings.max_similarity)), true)
+            .field("Max emoji/mentions", off(settings.max_emoji_ratio >= 100, format!("{}%", settings.max_emoji_ratio)), true)
+            .field("Rejected since restart", counts, false)
+            .footer(|f| f.text("Change these with config set xp_min_length, xp_max_similarity or xp_max_emoji_ratio."))
+        )).await?;
+    } else {
+        cmd.reply(&ctx.http, "This command can only be run in a server.").await?;
+    }
+
+    Ok(())
+}
@@ -1,14 +1,14 @@
 mod timeout_config;
 
-use serenity::framework::standard::macros::group;
-
+use crate::services::cow_framework::CowGroup;
 use timeout_config::*;
 
-#[group]
-#[prefixes("timeout")]
-#[description = "Commands for viewing and settinge the cooldown for chat xp."]
-#[summary = "Timeouts"]
-#[default_command(get)]
-#[commands(set, get)]
-struct Timeout;
-
+pub static TIMEOUT_GROUP: CowGroup = CowGroup {
+    name: "Timeout",
+    prefixes: &["timeout"],
+    description: "Commands for viewing and settinge the cooldown for chat xp.",
+    summary: "Timeouts",
+    default_command: Some(&GET_COMMAND),
+    commands: &[&SET_COMMAND, &GET_COMMAND],
+    sub_groups: &[]
+};
@@ -1,60 +1,55 @@
-use log::error;
 use serenity::{
-    framework::standard::{
-        macros::command, Args, CommandResult, 
-    }, 
-    model::channel::Message, client::Context
+    framework::standard::CommandResult,
+    model::permissions::Permissions, client::Context
 };
 
-use crate::{Database, db};
+use crate::services::cache::DbCache;
+use crate::services::cow_framework::{command, CowCommand, CommandOption, OptionKind, Invocation};
 use crate::util::{ to_ms, from_ms };
 
+pub static SET_COMMAND: CowCommand = CowCommand {
+    usage: Some("<#m#d#s#h> in any order"),
+    options: &[CommandOption::new("timeout", "The cooldown, like 1m30s.", OptionKind::String).required()],
+    only_in_guilds: true,
+    permissions: Permissions::ADMINISTRATOR,
+    ..CowCommand::new("set", "Sets server-wide cooldown for messaging xp gain.", set)
+};
+
 #[command]
-#[description = "Sets server-wide cooldown for messaging xp gain."]
-#[usage = "<#m#d#s#h> in any order"]
-#[only_in(guilds)]
-#[required_permissions("ADMINISTRATOR")]
-pub async fn set(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
-    let db = db!(ctx);
+pub async fn set(ctx: &Context, cmd: &Invocation) -> CommandResult {
+    let cache = DbCache::from_context(ctx).await;
     // nesting part 2
-    if let Some(server_id) = msg.guild_id {
-        if let Ok(timeout) = args.single::<String>() {
+    if let Some(server_id) = cmd.guild_id() {
+        if let Some(timeout) = cmd.arg::<String>("timeout") {
             if let Some(timeout) = to_ms(timeout) {
-                match db.set_timeout(server_id, timeout).await {
-                    Ok(_) => { msg.reply(&ctx.http, format!("Set timeout to {}.", from_ms(timeout as u64))).await?; }
-                    Err(err) => {
-                        msg.reply(&ctx.http, "Could not set timeout").await?;
-                        error!("Could not set timeout: {}", err);
-                    }
-                }
+                cache.set_timeout(server_id, timeout).await?;
+                cmd.reply(&ctx.http, format!("Set timeout to {}.", from_ms(timeout as u64))).await?;
             } else {
-                msg.reply(&ctx.http, "The timeout must be in the form #s#m#h#d").await?;
+                cmd.reply(&ctx.http, "The timeout must be in the form #s#m#h#d").await?;
             }
         } else {
-            msg.reply(&ctx.http, "The timeout must be in the form #s#m#h#d").await?;
+            cmd.reply(&ctx.http, "The timeout must be in the form #s#m#h#d").await?;
         }
     } else {
-        msg.reply(&ctx.http, "This command can only be run in a server.").await?;
+        cmd.reply(&ctx.http, "This command can only be run in a server.").await?;
     }
 
     Ok(())
 }
 
+pub static GET_COMMAND: CowCommand = CowCommand {
+    only_in_guilds: true,
+    ..CowCommand::new("get", "Gets the server-wide cooldown for messaging xp gain.", get)
+};
+
 #[command]
-#[description = "Gets the server-wide cooldown for messaging xp gain."]
-#[only_in(guilds)]
-pub async fn get(ctx: &Context, msg: &Message) -> CommandResult {
-    let db = db!(ctx);
-    if let Some(server_id) = msg.guild_id {
-        match db.get_timeout(server_id).await {
-            Ok(timeout) => { msg.reply(&ctx.http, format!("The timeout is {}.", from_ms(timeout as u64))).await?; }
-            Err(err) => {
-                msg.reply(&ctx.http, "Could not set timeout").await?;
-                error!("Could not get timeout: {}", err);
-            }
-        }
+pub async fn get(ctx: &Context, cmd: &Invocation) -> CommandResult {
+    let cache = DbCache::from_context(ctx).await;
+    if let Some(server_id) = cmd.guild_id() {
+        let timeout = cache.get_timeout(server_id).await?;
+        cmd.reply(&ctx.http, format!("The timeout is {}.", from_ms(timeout as u64))).await?;
     } else {
-        msg.reply(&ctx.http, "This command can only be run in a server.").await?;
+        cmd.reply(&ctx.http, "This command can only be run in a server.").await?;
     }
 
     Ok(())
@@ -2,18 +2,10 @@ use chrono::{Datelike, Local};
 use log::error;
 use serenity::{
     client::Context,
-    model::{
-        channel::Message
-    },
-    framework::standard::{
-        CommandResult,
-        macros::{
-            command
-        }
-    }
+    framework::standard::CommandResult
 };
 use scraper::{Html, Selector};
-use serenity::framework::standard::Args;
+use crate::services::cow_framework::{command, CowCommand, CommandOption, OptionKind, Invocation};
 
 pub struct Semester {
     pub name: String,
@@ -80,8 +72,8 @@ fn process_calendar(data: &str) -> Option<AcademicCalendar> {
     Some(AcademicCalendar { name: page_name.unwrap().unwrap(), semesters })
 }
 
-async fn print_schedule(ctx: &Context, msg: &Message, schedule: &AcademicCalendar) -> CommandResult {
-    msg.channel_id.send_message(&ctx.http, |m| m.embed(|e| {
+async fn print_schedule(ctx: &Context, cmd: &Invocation, schedule: &AcademicCalendar) -> CommandResult {
+    cmd.send_message(&ctx.http, |m| m.embed(|e| {
         e.title(&schedule.name);
 
         for semester in &schedule.semesters {
@@ -102,10 +94,14 @@ async fn print_schedule(ctx: &Context, msg: &Message, schedule: &AcademicCalenda
     Ok(())
 }
 
+pub static CALENDAR_COMMAND: CowCommand = CowCommand {
+    aliases: &["cal", "academiccalendar"],
+    options: &[CommandOption::new("year", "The year the academic year starts in.", OptionKind::Integer)],
+    ..CowCommand::new("calendar", "Get the academic calendar for the year.", calendar)
+};
+
 #[command]
-#[aliases(cal, academiccalendar)]
-#[description = "Get the academic calendar for the year."]
-pub async fn calendar(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+pub async fn calendar(ctx: &Context, cmd: &Invocation) -> CommandResult {
     let now = Local::now();
     let mut year = now.year();
 
@@ -113,11 +109,9 @@ pub async fn calendar(ctx: &Context, msg: &Message, mut args: Args) -> CommandRe
         year -= 1;
     }
 
-    while !args.is_empty() {
-        if let Ok(maybe_year) = args.single::<i32>() {
-            if maybe_year >= 2005 {
-                year = maybe_year;
-            }
+    if let Some(maybe_year) = cmd.arg::<i32>("year") {
+        if maybe_year >= 2005 {
+            year = maybe_year;
         }
     }
 
@@ -128,19 +122,19 @@ pub async fn calendar(ctx: &Context, msg: &Message, mut args: Args) -> CommandRe
                 Ok(data) => {
                     let schedules = process_calendar(&*data);
                     if let Some(calendar) = schedules {
-                        print_schedule(ctx, msg, &calendar).await?;
+                        print_schedule(ctx, cmd, &calendar).await?;
                     } else {
-                        msg.channel_id.say(&ctx.http, "Either you inputted an invalid year, or the website did not give us reasonable data.").await?;
+                        cmd.say(&ctx.http, "Either you inputted an invalid year, or the website did not give us reasonable data.").await?;
                     }
                 }
                 Err(ex) => {
-                    msg.channel_id.say(&ctx.http, "UC Merced gave us weird data, try again later?").await?;
+                    cmd.say(&ctx.http, "UC Merced gave us weird data, try again later?").await?;
                     error!("Failed to process calendar: {}", ex);
                 }
             }
         }
         Err(ex) => {
-            msg.channel_id.say(&ctx.http, "Failed to connect to the UC Merced website, try again later?").await?;
+            cmd.say(&ctx.http, "Failed to connect to the UC Merced website, try again later?").await?;
             error!("Failed to get food truck schedule: {}", ex);
         }
     }
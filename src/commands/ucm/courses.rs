@@ -1,19 +1,15 @@
 use chrono::{Datelike, DateTime, Local, TimeZone, Utc};
-use log::error;
 use serenity::{
+    builder::CreateEmbed,
     client::Context,
-    model::{
-        channel::Message
-    },
-    framework::standard::{
-        CommandResult,
-        macros::{
-            command
-        }, Args
-    }
+    framework::standard::{CommandResult, Args, Delimiter}
 };
 use crate::commands::ucm::courses_db_models::*;
 use crate::{Database, db};
+use crate::services::cow_framework::{command, paginate, CowCommand, CommandOption, OptionKind, Invocation, StaticPages};
+use crate::models::error::CowError;
+
+const SEARCH_PAGE_SIZE: usize = 10;
 
 fn fix_time(time: &str) -> String {
     let hour_str = &time[..2];
@@ -52,13 +48,13 @@ pub fn semester_from_text(input: &str) -> Option<i32> {
     }
 }
 
-async fn course_embed(ctx: &Context, msg: &Message, class: &Class) -> CommandResult {
+async fn course_embed(ctx: &Context, cmd: &Invocation, class: &Class) -> Result<(), CowError> {
     let db = db!(ctx);
     let professors = db.get_professors_for_class(class.id).await;
     let meetings = db.get_meetings_for_class(class.id).await;
     let stats = db.get_stats().await;
 
-    msg.channel_id.send_message(&ctx.http, |m| m.embed(|e| {
+    cmd.send_message(&ctx.http, |m| m.embed(|e| {
         e.title(format!("{}: {}", &class.course_number, class.course_title.clone().unwrap_or_else(|| "<unknown class name>".to_string())));
         e.description("Enrollment and Waitlist are in terms of seats available/seats taken/max seats.");
         e.field("CRN", class.course_reference_number, true);
@@ -110,15 +106,16 @@ async fn course_embed(ctx: &Context, msg: &Message, class: &Class) -> CommandRes
     Ok(())
 }
 
+pub static COURSES_COMMAND: CowCommand = CowCommand {
+    aliases: &["course"],
+    usage: Some("<CRN, Course Number, or Name> [Semester] [Year]"),
+    options: &[CommandOption::new("query", "The CRN, course number, or name of the class, optionally with a semester and year.", OptionKind::String).required()],
+    ..CowCommand::new("courses", "Search for courses in a term.", courses)
+};
+
 #[command]
-#[description = "Search for courses in a term."]
-#[aliases("course")]
-#[usage = "<CRN, Course Number, or Name> [Semester] [Year]"]
-pub async fn courses(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
-    if args.is_empty() {
-        msg.channel_id.say(&ctx.http, "Type the CRN, course number, or name of the class to look it up.").await?;
-        return Ok(());
-    }
+pub async fn courses(ctx: &Context, cmd: &Invocation) -> CommandResult {
+    let mut args = Args::new(&cmd.arg::<String>("query").unwrap_or_default(), &[Delimiter::Single(' ')]);
 
     let current_date = Local::now().date();
     let mut year = current_date.year();
@@ -131,18 +128,10 @@ pub async fn courses(ctx: &Context, msg: &Message, mut args: Args) -> CommandRes
             // Make sure it's not a year lol
             if numeric >= 10000 {
                 let db = db!(ctx);
-                match db.get_class(numeric).await {
-                    Ok(option_class) => {
-                        if let Some(class) = option_class {
-                            course_embed(ctx, msg, &class).await?;
-                        } else {
-                            msg.channel_id.say(&ctx.http, format!("Could not find a class with the CRN `{}`.", numeric)).await?;
-                        }
-                    }
-                    Err(ex) => {
-                        error!("Failed to get class: {}", ex);
-                        msg.channel_id.say(&ctx.http, "Failed to query our database... try again later?").await?;
-                    }
+                if let Some(class) = db.get_class(numeric).await? {
+                    course_embed(ctx, cmd, &class).await?;
+                } else {
+                    cmd.say(&ctx.http, format!("Could not find a class with the CRN `{}`.", numeric)).await?;
                 }
                 return Ok(())
             } else if numeric >= 2005 {
@@ -162,67 +151,54 @@ pub async fn courses(ctx: &Context, msg: &Message, mut args: Args) -> CommandRes
     }
 
     let term = year * 100 + semester;
-    match search_course_by_number(ctx, msg, &search_query, term).await {
-        Ok(any) => {
-            if !any {
-                match search_course_by_name(ctx, msg, &search_query, term).await {
-                    Ok(any) => {
-                        if !any {
-                            msg.channel_id.say(&ctx.http, "Failed to find any classes with the given query. Did you mistype the input?").await?;
-                        }
-                    }
-                    Err(ex) => {
-                        error!("Failed to search by name: {}", ex);
-                        msg.channel_id.say(&ctx.http, "Failed to search for classes... try again later?").await?;
-                    }
-                }
-            }
-        }
-        Err(ex) => {
-            error!("Failed to search by name: {}", ex);
-            msg.channel_id.say(&ctx.http, "Failed to search for classes... try again later?").await?;
-        }
+    if !search_course_by_number(ctx, cmd, &search_query, term).await? && !search_course_by_name(ctx, cmd, &search_query, term).await? {
+        cmd.say(&ctx.http, "Failed to find any classes with the given query. Did you mistype the input?").await?;
     }
 
     Ok(())
 }
 
-async fn search_course_by_number(ctx: &Context, msg: &Message, search_query: &str, term: i32) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+async fn search_course_by_number(ctx: &Context, cmd: &Invocation, search_query: &str, term: i32) -> Result<bool, CowError> {
     let db = db!(ctx);
     let classes = db.search_class_by_number(search_query, term).await?;
-    print_matches(ctx, msg, &classes).await?;
+    print_matches(ctx, cmd, &classes).await?;
 
     Ok(!classes.is_empty())
 }
 
-async fn search_course_by_name(ctx: &Context, msg: &Message, search_query: &str, term: i32) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+async fn search_course_by_name(ctx: &Context, cmd: &Invocation, search_query: &str, term: i32) -> Result<bool, CowError> {
     let db = db!(ctx);
     let classes = db.search_class_by_name(search_query, term).await?;
-    print_matches(ctx, msg, &classes).await?;
+    print_matches(ctx, cmd, &classes).await?;
 
     Ok(!classes.is_empty())
 }
 
-async fn print_matches(ctx: &Context, msg: &Message, classes: &[PartialClass]) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+async fn print_matches(ctx: &Context, cmd: &Invocation, classes: &[PartialClass]) -> Result<(), CowError> {
     if classes.is_empty() { return Ok(()); }
 
     if classes.len() == 1 {
         let db = db!(ctx);
-        let class = db.get_class(classes[0].course_reference_number).await?.unwrap();
-        course_embed(ctx, msg, &class).await?;
+        match db.get_class(classes[0].course_reference_number).await? {
+            Some(class) => course_embed(ctx, cmd, &class).await?,
+            // Only happens if the scraper removes it between the two queries.
+            None => { cmd.say(&ctx.http, format!("Could not find a class with the CRN `{}`.", classes[0].course_reference_number)).await?; }
+        }
     } else {
-        msg.channel_id.send_message(&ctx.http, |m| m.embed(|e| {
-            e.title("Class Search").description("Multiple results were found for your query. Search again using the CRN for a particular class.");
-            e.field(format!("Classes Matched (totalling {})", classes.len()),
-                    classes
-                            .iter()
-                            .take(10)
-                            .map(|o| format!("`{}` - {}: {}", o.course_reference_number, o.course_number, o.course_title.clone().unwrap_or_else(|| "<unknown class name>".to_string())))
-                            .reduce(|a, b| format!("{}\n{}", a, b))
-                            .unwrap(),
-                    false);
-            e
-        })).await?;
+        let lines = classes
+            .iter()
+            .map(|o| format!("`{}` - {}: {}", o.course_reference_number, o.course_number, o.course_title.clone().unwrap_or_else(|| "<unknown class name>".to_string())))
+            .collect::<Vec<_>>();
+        let pages = lines
+            .chunks(SEARCH_PAGE_SIZE)
+            .map(|chunk| {
+                let mut e = CreateEmbed::default();
+                e.title("Class Search").description("Multiple results were found for your query. Search again using the CRN for a particular class.");
+                e.field(format!("Classes Matched (totalling {})", classes.len()), chunk.join("\n"), false);
+                e
+            })
+            .collect();
+        paginate(ctx, cmd, &StaticPages(pages), 0).await?;
     }
 
     Ok(())
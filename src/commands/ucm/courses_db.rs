@@ -1,23 +1,94 @@
 use std::collections::HashMap;
 use chrono::NaiveDateTime;
-use num_traits::ToPrimitive;
 use serenity::{
     model::id::{
         UserId
     }
 };
-use rust_decimal::{
-    Decimal,
-    prelude::FromPrimitive
-};
-
-use crate::Database;
+use rust_decimal::Decimal;
+use async_trait::async_trait;
+use rusqlite::{OptionalExtension, params, params_from_iter, types::Value};
+
+use crate::models::error::CowError;
+use crate::services::database::{SqlServerDatabase, SqliteDatabase};
+use crate::services::database::sql_server::{to_decimal, from_decimal, column, nullable};
+use crate::services::database::sqlite::{to_sql_id, from_sql_id};
 use crate::commands::ucm::courses_db_models::*;
 
-impl Database {
-    pub async fn get_user_reminders(&self, user_id: UserId) -> Result<Vec<Reminder>, Box<dyn std::error::Error + Send + Sync>> {
+#[async_trait]
+pub trait CourseStore {
+    async fn get_user_reminders(&self, user_id: UserId) -> Result<Vec<Reminder>, CowError>;
+    async fn add_reminder(&self, reminder: &Reminder) -> Result<(), CowError>;
+    async fn remove_reminder(&self, user_id: UserId, course_reference_number: i32) -> Result<bool, CowError>;
+    // Marks reminders with enough open seats as triggered, and returns them.
+    async fn trigger_reminders(&self) -> Result<Vec<Trigger>, CowError>;
+    async fn get_class(&self, course_reference_number: i32) -> Result<Option<Class>, CowError>;
+    // Note: class_id is referring to an ID stored in the database, not the CRN. Fetch this through get_class.
+    async fn get_professors_for_class(&self, class_id: i32) -> Result<Vec<Professor>, CowError>;
+    // Note: class_id is referring to an ID stored in the database, not the CRN. Fetch this through get_class.
+    async fn get_meetings_for_class(&self, class_id: i32) -> Result<Vec<Meeting>, CowError>;
+    // Course number is like CSE-031.
+    async fn search_class_by_number(&self, course_number: &str, term: i32) -> Result<Vec<PartialClass>, CowError>;
+    // Course name is like Computer Organization and Assembly.
+    async fn search_class_by_name(&self, course_name: &str, term: i32) -> Result<Vec<PartialClass>, CowError>;
+    async fn search_professor(&self, search_query: &str) -> Result<Vec<Professor>, CowError>;
+    async fn get_classes_for_professor(&self, professor_id: i32, term: i32) -> Result<Vec<PartialClass>, CowError>;
+    async fn get_stats(&self) -> Result<HashMap<String, NaiveDateTime>, CowError>;
+}
+
+// If one of the results is exactly what was asked for, only return that one.
+fn exact_match(search_query: &str, classes: Vec<PartialClass>) -> Vec<PartialClass> {
+    if let Some(index) = classes.iter().position(|o| search_query == o.course_number || o.course_title.as_ref().map(|t| t == search_query).unwrap_or(false)) {
+        return classes.into_iter().skip(index).take(1).collect();
+    }
+
+    classes
+}
+
+impl SqlServerDatabase {
+    fn create_full_text_query(&self, search_query: &str) -> String {
+        search_query
+            .trim()
+            .split(' ')
+            .map(|o| o.replace('(', "").replace(')', "").replace('\"', "").replace('\'', "")) // *unqueries your query*
+            .map(|o| format!("\"*{}*\"", o)) // Wildcards
+            .reduce(|a, b| format!("{} AND {}", a, b))
+            .unwrap_or_default()
+    }
+
+    async fn general_class_search(&self, search_query: &str, term: i32, sql: &str) -> Result<Vec<PartialClass>, CowError> {
+        let mut conn = self.pool.get().await?;
+
+        let input = self.create_full_text_query(search_query);
+
+        let res = conn.query(sql, &[&term, &input])
+            .await?
+            .into_first_result()
+            .await?;
+
+        let mut out: Vec<PartialClass> = Vec::new();
+
+        for class in res {
+            let course_number: &str = column(&class, 2)?;
+            let course_title: Option<&str> = nullable(&class, 3)?;
+
+            out.push(PartialClass {
+                id: column(&class, 0)?,
+                course_reference_number: column(&class, 1)?,
+                course_number: course_number.to_string(),
+                course_title: course_title.map(|o| o.to_string())
+            });
+        }
+
+        Ok(exact_match(search_query, out))
+    }
+}
+
+#[async_trait]
+impl CourseStore for SqlServerDatabase {
+    async fn get_user_reminders(&self, user_id: UserId) -> Result<Vec<Reminder>, CowError> {
         let mut conn = self.pool.get().await?;
-        let user_decimal = Decimal::from_u64(user_id.0).unwrap();
+        let user_decimal = to_decimal(user_id.0);
         let res = conn.query(
             "SELECT course_reference_number, min_trigger, for_waitlist, triggered FROM [UniScraper].[UCM].[reminder] WHERE user_id = @P1",
             &[&user_decimal])
@@ -30,19 +101,19 @@ impl Database {
         for reminder in res {
             out.push(Reminder {
                 user_id: user_id.0,
-                course_reference_number: reminder.get(0).unwrap(),
-                min_trigger: reminder.get(1).unwrap(),
-                for_waitlist: reminder.get(2).unwrap(),
-                triggered: reminder.get(3).unwrap()
+                course_reference_number: column(&reminder, 0)?,
+                min_trigger: column(&reminder, 1)?,
+                for_waitlist: column(&reminder, 2)?,
+                triggered: column(&reminder, 3)?
             });
         }
 
         Ok(out)
     }
 
-    pub async fn add_reminder(&self, reminder: &Reminder) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    async fn add_reminder(&self, reminder: &Reminder) -> Result<(), CowError> {
         let mut conn = self.pool.get().await?;
-        let user_decimal = Decimal::from_u64(reminder.user_id).unwrap();
+        let user_decimal = to_decimal(reminder.user_id);
 
         // Will panic if there is a duplicate, since I have uniqueness set.
         conn.execute(
@@ -53,9 +124,9 @@ impl Database {
         Ok(())
     }
 
-    pub async fn remove_reminder(&self, user_id: UserId, course_reference_number: i32) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+    async fn remove_reminder(&self, user_id: UserId, course_reference_number: i32) -> Result<bool, CowError> {
         let mut conn = self.pool.get().await?;
-        let user_decimal = Decimal::from_u64(user_id.0).unwrap();
+        let user_decimal = to_decimal(user_id.0);
 
         let total = conn.execute(
             "DELETE FROM [UniScraper].[UCM].[reminder] WHERE user_id = @P1 AND course_reference_number = @P2",
@@ -65,7 +136,7 @@ impl Database {
         Ok(total > 0)
     }
 
-    pub async fn trigger_reminders(&self) -> Result<Vec<Trigger>, Box<dyn std::error::Error + Send + Sync>> {
+    async fn trigger_reminders(&self) -> Result<Vec<Trigger>, CowError> {
         let mut conn = self.pool.get().await?;
 
         let res = conn.simple_query(
@@ -77,18 +148,18 @@ impl Database {
         let mut out: Vec<Trigger> = Vec::new();
 
         for reminder in res {
-            let user_id: Decimal = reminder.get(0).unwrap();
+            let user_id: Decimal = column(&reminder, 0)?;
             out.push(Trigger {
-                user_id: user_id.to_u64().unwrap(),
-                course_reference_number: reminder.get(1).unwrap(),
-                min_trigger: reminder.get(2).unwrap()
+                user_id: from_decimal(user_id)?,
+                course_reference_number: column(&reminder, 1)?,
+                min_trigger: column(&reminder, 2)?
             });
         }
 
         Ok(out)
     }
 
-    pub async fn get_class(&self, course_reference_number: i32) -> Result<Option<Class>, Box<dyn std::error::Error + Send + Sync>> {
+    async fn get_class(&self, course_reference_number: i32) -> Result<Option<Class>, CowError> {
         let mut conn = self.pool.get().await?;
         let res = conn.query(
             "SELECT id, term, course_number, campus_description, course_title, credit_hours, maximum_enrollment, enrollment, seats_available, wait_capacity, wait_available FROM [UniScraper].[UCM].[class] WHERE course_reference_number = @P1",
@@ -100,30 +171,29 @@ impl Database {
         let mut out: Option<Class> = None;
 
         if let Some(class) = res {
-            let course_number: &str = class.get(2).unwrap();
-            let campus_description: Option<&str> = class.get(3);
-            let course_title: Option<&str> = class.get(4);
+            let course_number: &str = column(&class, 2)?;
+            let campus_description: Option<&str> = nullable(&class, 3)?;
+            let course_title: Option<&str> = nullable(&class, 4)?;
             out = Some(Class {
-                id: class.get(0).unwrap(),
-                term: class.get(1).unwrap(),
+                id: column(&class, 0)?,
+                term: column(&class, 1)?,
                 course_reference_number,
                 course_number: course_number.to_string(),
                 campus_description: campus_description.map(|o| o.to_string()),
                 course_title: course_title.map(|o| o.to_string()),
-                credit_hours: class.get(5).unwrap(),
-                maximum_enrollment: class.get(6).unwrap(),
-                enrollment: class.get(7).unwrap(),
-                seats_available: class.get(8).unwrap(),
-                wait_capacity: class.get(9).unwrap(),
-                wait_available: class.get(10).unwrap()
+                credit_hours: column(&class, 5)?,
+                maximum_enrollment: column(&class, 6)?,
+                enrollment: column(&class, 7)?,
+                seats_available: column(&class, 8)?,
+                wait_capacity: column(&class, 9)?,
+                wait_available: column(&class, 10)?
             });
         }
 
         Ok(out)
     }
 
-    // Note: class_id is referring to an ID stored in the database, not the CRN. Fetch this through get_class.
-    pub async fn get_professors_for_class(&self, class_id: i32) -> Result<Vec<Professor>, Box<dyn std::error::Error + Send + Sync>> {
+    async fn get_professors_for_class(&self, class_id: i32) -> Result<Vec<Professor>, CowError> {
         let mut conn = self.pool.get().await?;
         let res = conn.query(
             "SELECT professor.id, rmp_id, last_name, first_name, middle_name, email, department, num_ratings, rating, full_name FROM [UniScraper].[UCM].[professor] INNER JOIN [UniScraper].[UCM].[faculty] ON professor.id = faculty.professor_id WHERE class_id = @P1;",
@@ -135,22 +205,22 @@ impl Database {
         let mut out: Vec<Professor> = Vec::new();
 
         for professor in res {
-            let last_name: &str = professor.get(2).unwrap();
-            let first_name: &str = professor.get(3).unwrap();
-            let middle_name: Option<&str> = professor.get(4);
-            let email: Option<&str> = professor.get(5);
-            let department: Option<&str> = professor.get(6);
-            let full_name: &str = professor.get(9).unwrap();
+            let last_name: &str = column(&professor, 2)?;
+            let first_name: &str = column(&professor, 3)?;
+            let middle_name: Option<&str> = nullable(&professor, 4)?;
+            let email: Option<&str> = nullable(&professor, 5)?;
+            let department: Option<&str> = nullable(&professor, 6)?;
+            let full_name: &str = column(&professor, 9)?;
             out.push(Professor {
-                id: professor.get(0).unwrap(),
-                rmp_id: professor.get(1),
+                id: column(&professor, 0)?,
+                rmp_id: nullable(&professor, 1)?,
                 last_name: last_name.to_string(),
                 first_name: first_name.to_string(),
                 middle_name: middle_name.map(|o| o.to_string()),
                 email: email.map(|o| o.to_string()),
                 department: department.map(|o| o.to_string()),
-                num_ratings: professor.get(7).unwrap(),
-                rating: professor.get(8).unwrap(),
+                num_ratings: column(&professor, 7)?,
+                rating: column(&professor, 8)?,
                 full_name: full_name.to_string()
             });
         }
@@ -158,8 +228,7 @@ impl Database {
         Ok(out)
     }
 
-    // Note: class_id is referring to an ID stored in the database, not the CRN. Fetch this through get_class.
-    pub async fn get_meetings_for_class(&self, class_id: i32) -> Result<Vec<Meeting>, Box<dyn std::error::Error + Send + Sync>> {
+    async fn get_meetings_for_class(&self, class_id: i32) -> Result<Vec<Meeting>, CowError> {
         let mut conn = self.pool.get().await?;
         let res = conn.query(
             "SELECT begin_time, end_time, begin_date, end_date, building, building_description, campus, campus_description, room, credit_hour_session, hours_per_week, in_session, meeting_type FROM [UniScraper].[UCM].[meeting] WHERE class_id = @P1;",
@@ -171,16 +240,16 @@ impl Database {
         let mut out: Vec<Meeting> = Vec::new();
 
         for meeting in res {
-            let begin_time: Option<&str> = meeting.get(0);
-            let end_time: Option<&str> = meeting.get(1);
-            let begin_date: &str = meeting.get(2).unwrap();
-            let end_date: &str = meeting.get(3).unwrap();
-            let building: Option<&str> = meeting.get(4);
-            let building_description: Option<&str> = meeting.get(5);
-            let campus: Option<&str> = meeting.get(6);
-            let campus_description: Option<&str> = meeting.get(7);
-            let room: Option<&str> = meeting.get(8);
-            let meeting_type: u8 = meeting.get(12).unwrap();
+            let begin_time: Option<&str> = nullable(&meeting, 0)?;
+            let end_time: Option<&str> = nullable(&meeting, 1)?;
+            let begin_date: &str = column(&meeting, 2)?;
+            let end_date: &str = column(&meeting, 3)?;
+            let building: Option<&str> = nullable(&meeting, 4)?;
+            let building_description: Option<&str> = nullable(&meeting, 5)?;
+            let campus: Option<&str> = nullable(&meeting, 6)?;
+            let campus_description: Option<&str> = nullable(&meeting, 7)?;
+            let room: Option<&str> = nullable(&meeting, 8)?;
+            let meeting_type: u8 = column(&meeting, 12)?;
             out.push(Meeting {
                 class_id,
                 begin_time: begin_time.map(|o| o.to_string()),
@@ -192,26 +261,24 @@ impl Database {
                 campus: campus.map(|o| o.to_string()),
                 campus_description: campus_description.map(|o| o.to_string()),
                 room: room.map(|o| o.to_string()),
-                credit_hour_session: meeting.get(9).unwrap(),
-                hours_per_week: meeting.get(10).unwrap(),
-                in_session: Days::from_bits(meeting.get(11).unwrap()).unwrap(),
-                meeting_type: MeetingType::try_from(meeting_type).unwrap()
+                credit_hour_session: column(&meeting, 9)?,
+                hours_per_week: column(&meeting, 10)?,
+                in_session: Days::from_bits(column(&meeting, 11)?).ok_or_else(|| CowError::decode("unknown meeting days"))?,
+                meeting_type: MeetingType::try_from(meeting_type).map_err(|_| CowError::decode(format!("unknown meeting type {}", meeting_type)))?
             });
         }
 
         Ok(out)
     }
 
-    // Course number is like CSE-031.
-    pub async fn search_class_by_number(&self, course_number: &str, term: i32) -> Result<Vec<PartialClass>, Box<dyn std::error::Error + Send + Sync>> {
+    async fn search_class_by_number(&self, course_number: &str, term: i32) -> Result<Vec<PartialClass>, CowError> {
         self.general_class_search(course_number, term,
                                   "SELECT id, course_reference_number, course_number, course_title \
                                   FROM UniScraper.UCM.class \
                                   WHERE term = @P1 AND CONTAINS(course_number, @P2);").await
     }
 
-    // Course name is like Computer Organization and Assembly.
-    pub async fn search_class_by_name(&self, course_name: &str, term: i32) -> Result<Vec<PartialClass>, Box<dyn std::error::Error + Send + Sync>> {
+    async fn search_class_by_name(&self, course_name: &str, term: i32) -> Result<Vec<PartialClass>, CowError> {
         self.general_class_search(course_name, term,
           "SELECT id, course_reference_number, course_number, course_title FROM \
                     (SELECT id, course_reference_number, course_number, course_title, term, ROW_NUMBER() \
@@ -220,51 +287,7 @@ impl Database {
                     WHERE mukyu.RowNumber = 1;").await
     }
 
-    fn create_full_text_query(&self, search_query: &str) -> String {
-        search_query
-            .trim()
-            .split(' ')
-            .map(|o| o.replace('(', "").replace(')', "").replace('\"', "").replace('\'', "")) // *unqueries your query*
-            .map(|o| format!("\"*{}*\"", o)) // Wildcards
-            .reduce(|a, b| format!("{} AND {}", a, b))
-            .unwrap()
-    }
-
-    async fn general_class_search(&self, search_query: &str, term: i32, sql: &str) -> Result<Vec<PartialClass>, Box<dyn std::error::Error + Send + Sync>> {
-        let mut conn = self.pool.get().await?;
-
-        let input = self.create_full_text_query(search_query);
-
-        let res = conn.query(sql, &[&term, &input])
-            .await?
-            .into_first_result()
-            .await?;
-
-        let mut out: Vec<PartialClass> = Vec::new();
-
-        for class in res {
-            let course_number: &str = class.get(2).unwrap();
-            let course_title: Option<&str> = class.get(3);
-
-            let item = PartialClass {
-                id: class.get(0).unwrap(),
-                course_reference_number: class.get(1).unwrap(),
-                course_number: course_number.to_string(),
-                course_title: course_title.map(|o| o.to_string())
-            };
-
-            if search_query == course_number || course_title.map(|o| o == search_query).unwrap_or(false) {
-                // Return early with one item
-                return Ok(vec![item]);
-            } else {
-                out.push(item);
-            }
-        }
-
-        Ok(out)
-    }
-
-    pub async fn search_professor(&self, search_query: &str) -> Result<Vec<Professor>, Box<dyn std::error::Error + Send + Sync>> {
+    async fn search_professor(&self, search_query: &str) -> Result<Vec<Professor>, CowError> {
         let mut conn = self.pool.get().await?;
 
         let input = self.create_full_text_query(search_query);
@@ -277,22 +300,22 @@ impl Database {
         let mut out: Vec<Professor> = Vec::new();
 
         for professor in res {
-            let last_name: &str = professor.get(2).unwrap();
-            let first_name: &str = professor.get(3).unwrap();
-            let middle_name: Option<&str> = professor.get(4);
-            let email: Option<&str> = professor.get(5);
-            let department: Option<&str> = professor.get(6);
-            let full_name: &str = professor.get(9).unwrap();
+            let last_name: &str = column(&professor, 2)?;
+            let first_name: &str = column(&professor, 3)?;
+            let middle_name: Option<&str> = nullable(&professor, 4)?;
+            let email: Option<&str> = nullable(&professor, 5)?;
+            let department: Option<&str> = nullable(&professor, 6)?;
+            let full_name: &str = column(&professor, 9)?;
             out.push(Professor {
-                id: professor.get(0).unwrap(),
-                rmp_id: professor.get(1),
+                id: column(&professor, 0)?,
+                rmp_id: nullable(&professor, 1)?,
                 last_name: last_name.to_string(),
                 first_name: first_name.to_string(),
                 middle_name: middle_name.map(|o| o.to_string()),
                 email: email.map(|o| o.to_string()),
                 department: department.map(|o| o.to_string()),
-                num_ratings: professor.get(7).unwrap(),
-                rating: professor.get(8).unwrap(),
+                num_ratings: column(&professor, 7)?,
+                rating: column(&professor, 8)?,
                 full_name: full_name.to_string()
             });
         }
@@ -300,7 +323,7 @@ impl Database {
         Ok(out)
     }
 
-    pub async fn get_classes_for_professor(&self, professor_id: i32, term: i32) -> Result<Vec<PartialClass>, Box<dyn std::error::Error + Send + Sync>> {
+    async fn get_classes_for_professor(&self, professor_id: i32, term: i32) -> Result<Vec<PartialClass>, CowError> {
         let mut conn = self.pool.get().await?;
 
         let res = conn.query("SELECT class.id, class.course_reference_number, class.course_number, class.course_title FROM [UniScraper].[UCM].[professor] \
@@ -314,12 +337,12 @@ impl Database {
         let mut out: Vec<PartialClass> = Vec::new();
 
         for class in res {
-            let course_number: &str = class.get(2).unwrap();
-            let course_title: Option<&str> = class.get(3);
+            let course_number: &str = column(&class, 2)?;
+            let course_title: Option<&str> = nullable(&class, 3)?;
 
             let item = PartialClass {
-                id: class.get(0).unwrap(),
-                course_reference_number: class.get(1).unwrap(),
+                id: column(&class, 0)?,
+                course_reference_number: column(&class, 1)?,
                 course_number: course_number.to_string(),
                 course_title: course_title.map(|o| o.to_string())
             };
@@ -330,7 +353,7 @@ impl Database {
         Ok(out)
     }
 
-    pub async fn get_stats(&self) -> Result<HashMap<String, NaiveDateTime>, Box<dyn std::error::Error + Send + Sync>> {
+    async fn get_stats(&self) -> Result<HashMap<String, NaiveDateTime>, CowError> {
         let mut conn = self.pool.get().await?;
         let res = conn.simple_query(
             "SELECT table_name, last_update FROM [UniScraper].[UCM].[stats];")
@@ -341,11 +364,231 @@ impl Database {
         let mut out = HashMap::new();
 
         for meeting in res {
-            let table_name: &str = meeting.get(0).unwrap();
-            let last_update: NaiveDateTime = meeting.get(1).unwrap();
+            let table_name: &str = column(&meeting, 0)?;
+            let last_update: NaiveDateTime = column(&meeting, 1)?;
             out.insert(table_name.to_string(), last_update);
         }
 
         Ok(out)
     }
-}
\ No newline at end of file
+}
+const SQLITE_PROFESSOR_COLUMNS: &str = "ucm_professor.id, rmp_id, last_name, first_name, middle_name, email, department, num_ratings, rating, full_name";
+
+fn professor_from_row(row: &rusqlite::Row) -> Result<Professor, rusqlite::Error> {
+    Ok(Professor {
+        id: row.get(0)?,
+        rmp_id: row.get(1)?,
+        last_name: row.get(2)?,
+        first_name: row.get(3)?,
+        middle_name: row.get(4)?,
+        email: row.get(5)?,
+        department: row.get(6)?,
+        num_ratings: row.get(7)?,
+        rating: row.get::<_, f64>(8)? as f32,
+        full_name: row.get(9)?
+    })
+}
+
+fn partial_class_from_row(row: &rusqlite::Row) -> Result<PartialClass, rusqlite::Error> {
+    Ok(PartialClass {
+        id: row.get(0)?,
+        course_reference_number: row.get(1)?,
+        course_number: row.get(2)?,
+        course_title: row.get(3)?
+    })
+}
+
+impl SqliteDatabase {
+    // SQLite has no full-text CONTAINS, so every word has to show up somewhere in the column instead.
+    fn like_query(&self, column: &str, search_query: &str, first_param: usize) -> (String, Vec<Value>) {
+        let words = search_query
+            .split_whitespace()
+            .map(|o| format!("%{}%", o.replace(['%', '_'], "")))
+            .collect::<Vec<_>>();
+
+        if words.is_empty() {
+            return ("1 = 0".to_string(), Vec::new());
+        }
+
+        let clause = (0..words.len())
+            .map(|i| format!("{} LIKE ?{}", column, first_param + i))
+            .reduce(|a, b| format!("{} AND {}", a, b))
+            .unwrap();
+
+        (clause, words.into_iter().map(Value::Text).collect())
+    }
+
+    fn general_class_search(&self, search_query: &str, term: i32, column: &str, distinct_titles: bool) -> Result<Vec<PartialClass>, CowError> {
+        let conn = self.conn();
+        let (clause, mut values) = self.like_query(column, search_query, 2);
+        values.insert(0, Value::Integer(term as i64));
+
+        // SQLite takes the bare columns from the row with the smallest CRN.
+        let sql = if distinct_titles {
+            format!("SELECT id, MIN(course_reference_number), course_number, course_title FROM ucm_class WHERE term = ?1 AND {} GROUP BY course_title", clause)
+        } else {
+            format!("SELECT id, course_reference_number, course_number, course_title FROM ucm_class WHERE term = ?1 AND {}", clause)
+        };
+
+        let mut statement = conn.prepare(&sql)?;
+        let out = statement.query_map(params_from_iter(values), partial_class_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(exact_match(search_query, out))
+    }
+}
+
+#[async_trait]
+impl CourseStore for SqliteDatabase {
+    async fn get_user_reminders(&self, user_id: UserId) -> Result<Vec<Reminder>, CowError> {
+        let conn = self.conn();
+        let mut statement = conn.prepare(
+            "SELECT course_reference_number, min_trigger, for_waitlist, triggered FROM ucm_reminder WHERE user_id = ?1")?;
+        let out = statement.query_map(params![to_sql_id(user_id.0)], |row| Ok(Reminder {
+                user_id: user_id.0,
+                course_reference_number: row.get(0)?,
+                min_trigger: row.get(1)?,
+                for_waitlist: row.get(2)?,
+                triggered: row.get(3)?
+            }))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(out)
+    }
+
+    async fn add_reminder(&self, reminder: &Reminder) -> Result<(), CowError> {
+        let conn = self.conn();
+        conn.execute(
+            "INSERT INTO ucm_reminder (user_id, course_reference_number, min_trigger, for_waitlist, triggered) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![to_sql_id(reminder.user_id), reminder.course_reference_number, reminder.min_trigger, reminder.for_waitlist, reminder.triggered])?;
+
+        Ok(())
+    }
+
+    async fn remove_reminder(&self, user_id: UserId, course_reference_number: i32) -> Result<bool, CowError> {
+        let conn = self.conn();
+        let total = conn.execute(
+            "DELETE FROM ucm_reminder WHERE user_id = ?1 AND course_reference_number = ?2",
+            params![to_sql_id(user_id.0), course_reference_number])?;
+
+        Ok(total > 0)
+    }
+
+    // Replaces [UniScraper].[UCM].[TriggerReminders].
+    async fn trigger_reminders(&self) -> Result<Vec<Trigger>, CowError> {
+        let conn = self.conn();
+        let mut statement = conn.prepare(
+            "UPDATE ucm_reminder SET triggered = 1 \
+            WHERE triggered = 0 AND EXISTS (\
+                SELECT 1 FROM ucm_class c WHERE c.course_reference_number = ucm_reminder.course_reference_number \
+                AND ((ucm_reminder.for_waitlist = 0 AND c.seats_available >= ucm_reminder.min_trigger) \
+                    OR (ucm_reminder.for_waitlist = 1 AND c.wait_available >= ucm_reminder.min_trigger))) \
+            RETURNING user_id, course_reference_number, min_trigger")?;
+        let out = statement.query_map([], |row| Ok(Trigger {
+                user_id: from_sql_id(row.get(0)?),
+                course_reference_number: row.get(1)?,
+                min_trigger: row.get(2)?
+            }))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(out)
+    }
+
+    async fn get_class(&self, course_reference_number: i32) -> Result<Option<Class>, CowError> {
+        let conn = self.conn();
+        let out = conn.query_row(
+            "SELECT id, term, course_number, campus_description, course_title, credit_hours, maximum_enrollment, enrollment, seats_available, wait_capacity, wait_available FROM ucm_class WHERE course_reference_number = ?1",
+            params![course_reference_number],
+            |row| Ok(Class {
+                id: row.get(0)?,
+                term: row.get(1)?,
+                course_reference_number,
+                course_number: row.get(2)?,
+                campus_description: row.get(3)?,
+                course_title: row.get(4)?,
+                credit_hours: row.get(5)?,
+                maximum_enrollment: row.get(6)?,
+                enrollment: row.get(7)?,
+                seats_available: row.get(8)?,
+                wait_capacity: row.get(9)?,
+                wait_available: row.get(10)?
+            }))
+            .optional()?;
+
+        Ok(out)
+    }
+
+    async fn get_professors_for_class(&self, class_id: i32) -> Result<Vec<Professor>, CowError> {
+        let conn = self.conn();
+        let mut statement = conn.prepare(&format!(
+            "SELECT {} FROM ucm_professor INNER JOIN ucm_faculty ON ucm_professor.id = ucm_faculty.professor_id WHERE class_id = ?1", SQLITE_PROFESSOR_COLUMNS))?;
+        let out = statement.query_map(params![class_id], professor_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(out)
+    }
+
+    async fn get_meetings_for_class(&self, class_id: i32) -> Result<Vec<Meeting>, CowError> {
+        let conn = self.conn();
+        let mut statement = conn.prepare(
+            "SELECT begin_time, end_time, begin_date, end_date, building, building_description, campus, campus_description, room, credit_hour_session, hours_per_week, in_session, meeting_type FROM ucm_meeting WHERE class_id = ?1")?;
+        let out = statement.query_map(params![class_id], |row| Ok(Meeting {
+                class_id,
+                begin_time: row.get(0)?,
+                end_time: row.get(1)?,
+                begin_date: row.get(2)?,
+                end_date: row.get(3)?,
+                building: row.get(4)?,
+                building_description: row.get(5)?,
+                campus: row.get(6)?,
+                campus_description: row.get(7)?,
+                room: row.get(8)?,
+                credit_hour_session: row.get::<_, f64>(9)? as f32,
+                hours_per_week: row.get::<_, f64>(10)? as f32,
+                in_session: Days::from_bits_truncate(row.get(11)?),
+                meeting_type: MeetingType::try_from(row.get::<_, u8>(12)?).unwrap_or(MeetingType::Lecture)
+            }))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(out)
+    }
+
+    async fn search_class_by_number(&self, course_number: &str, term: i32) -> Result<Vec<PartialClass>, CowError> {
+        self.general_class_search(course_number, term, "course_number", false)
+    }
+
+    async fn search_class_by_name(&self, course_name: &str, term: i32) -> Result<Vec<PartialClass>, CowError> {
+        self.general_class_search(course_name, term, "course_title", true)
+    }
+
+    async fn search_professor(&self, search_query: &str) -> Result<Vec<Professor>, CowError> {
+        let conn = self.conn();
+        let (clause, values) = self.like_query("full_name", search_query, 1);
+        let mut statement = conn.prepare(&format!("SELECT {} FROM ucm_professor WHERE {}", SQLITE_PROFESSOR_COLUMNS, clause))?;
+        let out = statement.query_map(params_from_iter(values), professor_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(out)
+    }
+
+    async fn get_classes_for_professor(&self, professor_id: i32, term: i32) -> Result<Vec<PartialClass>, CowError> {
+        let conn = self.conn();
+        let mut statement = conn.prepare(
+            "SELECT ucm_class.id, ucm_class.course_reference_number, ucm_class.course_number, ucm_class.course_title FROM ucm_faculty \
+            INNER JOIN ucm_class ON ucm_class.id = ucm_faculty.class_id \
+            WHERE ucm_class.term = ?1 AND ucm_faculty.professor_id = ?2")?;
+        let out = statement.query_map(params![term, professor_id], partial_class_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(out)
+    }
+
+    async fn get_stats(&self) -> Result<HashMap<String, NaiveDateTime>, CowError> {
+        let conn = self.conn();
+        let mut statement = conn.prepare("SELECT table_name, last_update FROM ucm_stats")?;
+        let out = statement.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<HashMap<_, _>, _>>()?;
+
+        Ok(out)
+    }
+}
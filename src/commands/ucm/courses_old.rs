@@ -1,29 +1,32 @@
 use log::error;
 use serenity::{
     client::Context,
-    model::{
-        channel::Message
-    },
-    framework::standard::{
-        CommandResult,
-        macros::{
-            command
-        }, Args
-    }
+    framework::standard::CommandResult
 };
 use chrono::Datelike;
 use crate::commands::ucm::course_models::{CourseList};
+use crate::services::cow_framework::{command, CowCommand, CommandOption, OptionChoice, OptionKind, Invocation};
+
+pub static COURSES_OLD_COMMAND: CowCommand = CowCommand {
+    options: &[
+        CommandOption::new("semester", "The semester to look in.", OptionKind::String).required().choices(&[
+            OptionChoice::string("Fall", "fall"),
+            OptionChoice::string("Spring", "spring"),
+            OptionChoice::string("Summer", "summer")
+        ]),
+        CommandOption::new("major", "The major's subject code.", OptionKind::String)
+    ],
+    ..CowCommand::new("courses_old", "Get the course list for a major", courses_old)
+};
 
 #[command]
-#[description = "Get the course list for a major"]
-#[usage = "<semester> <major>"]
-pub async fn courses_old(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+pub async fn courses_old(ctx: &Context, cmd: &Invocation) -> CommandResult {
     let client = reqwest::Client::builder()
         .cookie_store(true)
         .build()?;
     
-    let term = match args.single::<String>() {
-        Ok(selected_sem) => {
+    let term = match cmd.arg::<String>("semester") {
+        Some(selected_sem) => {
             let now = chrono::Utc::now();
             let sem_code = match selected_sem.to_lowercase().as_str() {
                 "fall" => "10",
@@ -35,8 +38,8 @@ pub async fn courses_old(ctx: &Context, msg: &Message, mut args: Args) -> Comman
             format!("{}{}", now.year(), sem_code)
         },
 
-        Err(_) => {
-            msg.channel_id.say(&ctx.http, "Please use the semester names 'fall', 'spring', or 'summer'.").await?;
+        None => {
+            cmd.say(&ctx.http, "Please use the semester names 'fall', 'spring', or 'summer'.").await?;
             return Ok(());
         }
     };
@@ -54,8 +57,8 @@ pub async fn courses_old(ctx: &Context, msg: &Message, mut args: Args) -> Comman
     client.get(term_url).send().await?;
     client.get(search_url).send().await?;
 
-    let major = args.single::<String>()
-        .unwrap_or_else(|_| "".into())
+    let major = cmd.arg::<String>("major")
+        .unwrap_or_default()
         .to_uppercase();
     
     let url = format!("https://reg-prod.ec.ucmerced.edu/StudentRegistrationSsb/ssb/courseSearchResults/courseSearchResults?\
@@ -73,7 +76,7 @@ pub async fn courses_old(ctx: &Context, msg: &Message, mut args: Args) -> Comman
             // TODO: add pagination for courses
             match response.json::<CourseList>().await {
                 Ok(course_list) => {
-                    msg.channel_id.send_message(&ctx.http, |m| {
+                    cmd.send_message(&ctx.http, |m| {
                         m.embed(|e| {
                             e
                                 .title("Course List")
@@ -90,13 +93,13 @@ pub async fn courses_old(ctx: &Context, msg: &Message, mut args: Args) -> Comman
                     }).await?;
                 }
                 Err(ex) => {
-                    msg.channel_id.say(&ctx.http, "The course search gave us weird data, try again later?").await?;
+                    cmd.say(&ctx.http, "The course search gave us weird data, try again later?").await?;
                     error!("Failed to process course search: {}", ex);
                 }
             }
         }
         Err(ex) => {
-            msg.channel_id.say(&ctx.http, "Failed to connect to the course search API, try again later?").await?;
+            cmd.say(&ctx.http, "Failed to connect to the course search API, try again later?").await?;
             error!("Failed to get course search: {}", ex);
         }
     }
@@ -2,17 +2,10 @@ use chrono::{Local, NaiveDate};
 use log::error;
 use serenity::{
     client::Context,
-    model::{
-        channel::Message
-    },
-    framework::standard::{
-        CommandResult,
-        macros::{
-            command
-        }
-    }
+    framework::standard::CommandResult
 };
 use scraper::{Html, Selector};
+use crate::services::cow_framework::{command, CowCommand, Invocation};
 
 pub struct FoodTruckSchedule {
     pub date: NaiveDate,
@@ -163,8 +156,8 @@ async fn pick_best_schedule(schedules: &Vec<FoodTruckSchedule>) -> Option<&FoodT
     return Some(schedules.last().unwrap())
 }
 
-async fn print_schedule(ctx: &Context, msg: &Message, schedule: &FoodTruckSchedule) -> CommandResult {
-    msg.channel_id.send_message(&ctx.http, |m| m.embed(|e| {
+async fn print_schedule(ctx: &Context, cmd: &Invocation, schedule: &FoodTruckSchedule) -> CommandResult {
+    cmd.send_message(&ctx.http, |m| m.embed(|e| {
         e
             .title("Food Truck Schedule")
             .description(format!("For the week of {}", schedule.date.format("%B %d, %Y")))
@@ -178,10 +171,13 @@ async fn print_schedule(ctx: &Context, msg: &Message, schedule: &FoodTruckSchedu
     Ok(())
 }
 
+pub static FOODTRUCKS_COMMAND: CowCommand = CowCommand {
+    aliases: &["foodtruck"],
+    ..CowCommand::new("foodtrucks", "Get the current food truck schedule.", foodtrucks)
+};
+
 #[command]
-#[aliases(foodtruck)]
-#[description = "Get the current food truck schedule."]
-pub async fn foodtrucks(ctx: &Context, msg: &Message) -> CommandResult {
+pub async fn foodtrucks(ctx: &Context, cmd: &Invocation) -> CommandResult {
     const URL: &str = "https://dining.ucmerced.edu/food-trucks";
     match reqwest::get(URL).await {
         Ok(response) => {
@@ -190,19 +186,19 @@ pub async fn foodtrucks(ctx: &Context, msg: &Message) -> CommandResult {
                     let schedules = process_schedules(&*data);
                     let best_schedule = pick_best_schedule(&schedules).await;
                     if let Some(schedule) = best_schedule {
-                        print_schedule(ctx, msg, schedule).await?;
+                        print_schedule(ctx, cmd, schedule).await?;
                     } else {
-                        msg.channel_id.say(&ctx.http, "Could not get any valid schedules... Did the website change layout?").await?;
+                        cmd.say(&ctx.http, "Could not get any valid schedules... Did the website change layout?").await?;
                     }
                 }
                 Err(ex) => {
-                    msg.channel_id.say(&ctx.http, "UC Merced gave us weird data, try again later?").await?;
+                    cmd.say(&ctx.http, "UC Merced gave us weird data, try again later?").await?;
                     error!("Failed to process calendar: {}", ex);
                 }
             }
         }
         Err(ex) => {
-            msg.channel_id.say(&ctx.http, "Failed to connect to the UC Merced website, try again later?").await?;
+            cmd.say(&ctx.http, "Failed to connect to the UC Merced website, try again later?").await?;
             error!("Failed to get food truck schedule: {}", ex);
         }
     }
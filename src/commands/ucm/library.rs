@@ -2,28 +2,22 @@ use chrono::Datelike;
 use log::error;
 use serenity::{
     client::Context,
-    model::{
-        channel::Message
-    },
-    framework::standard::{
-        CommandResult,
-        macros::{
-            command
-        }
-    }
+    framework::standard::CommandResult
 };
 use crate::commands::ucm::libcal_models::Calendar;
+use crate::services::cow_framework::{command, CowCommand, Invocation};
+
+pub static LIBRARY_COMMAND: CowCommand = CowCommand::new("library", "Get the hours for the Kolligian Library.", library);
 
 #[command]
-#[description = "Get the hours for the Kolligian Library."]
-pub async fn library(ctx: &Context, msg: &Message) -> CommandResult {
+pub async fn library(ctx: &Context, cmd: &Invocation) -> CommandResult {
     let date = chrono::offset::Local::now();
     let url = format!("https://api3.libcal.com/api_hours_grid.php?iid=4052&lid=0&format=json&date={}-{:0>2}-{:0>2}", date.year(), date.month(), date.day());
     match reqwest::get(url).await {
         Ok(response) => {
             match response.json::<Calendar>().await {
                 Ok(data) => {
-                    msg.channel_id.send_message(&ctx.http, |m| {
+                    cmd.send_message(&ctx.http, |m| {
                         let library = &data.locations[0].weeks[0];
                         let start_date = chrono::NaiveDate::parse_from_str(&*library.sunday.date, "%Y-%m-%d").unwrap();
                         m.embed(|e| {
@@ -41,13 +35,13 @@ pub async fn library(ctx: &Context, msg: &Message) -> CommandResult {
                     }).await?;
                 }
                 Err(ex) => {
-                    msg.channel_id.say(&ctx.http, "The library gave us weird data, try again later?").await?;
+                    cmd.say(&ctx.http, "The library gave us weird data, try again later?").await?;
                     error!("Failed to process calendar: {}", ex);
                 }
             }
         }
         Err(ex) => {
-            msg.channel_id.say(&ctx.http, "Failed to connect to the library API, try again later?").await?;
+            cmd.say(&ctx.http, "Failed to connect to the library API, try again later?").await?;
             error!("Failed to get calendar: {}", ex);
         }
     }
@@ -7,13 +7,12 @@ mod course_models;
 mod pavilion;
 mod pav_models;
 pub mod reminders;
-mod courses_db;
-mod courses_db_models;
+pub mod courses_db;
+pub mod courses_db_models;
 mod foodtrucks;
 mod calendar;
 
-use serenity::framework::standard::macros::group;
-
+use crate::services::cow_framework::CowGroup;
 use crate::commands::ucm::reminders::REMINDERS_GROUP;
 
 use library::*;
@@ -24,10 +23,12 @@ use professors::*;
 use foodtrucks::*;
 use calendar::*;
 
-#[group]
-#[prefixes("ucm", "ucmerced")]
-#[description = "Get information about UC Merced's services and facilities."]
-#[summary = "UC Merced info"]
-#[commands(library, courses, courses_old, pavilion, professors, foodtrucks, calendar)]
-#[sub_groups(reminders)]
-struct UCM;
\ No newline at end of file
+pub static UCM_GROUP: CowGroup = CowGroup {
+    name: "UCM",
+    prefixes: &["ucm", "ucmerced"],
+    description: "Get information about UC Merced's services and facilities.",
+    summary: "UC Merced info",
+    default_command: None,
+    commands: &[&LIBRARY_COMMAND, &COURSES_COMMAND, &COURSES_OLD_COMMAND, &PAVILION_COMMAND, &PROFESSORS_COMMAND, &FOODTRUCKS_COMMAND, &CALENDAR_COMMAND],
+    sub_groups: &[&REMINDERS_GROUP]
+};
\ No newline at end of file
@@ -1,21 +1,16 @@
 use reqwest::{Url, Client};
 use serenity::{
     client::Context,
-    model::{channel::Message},
-    framework::standard::{
-        CommandResult,
-        macros::{
-            command
-        }
-    },
+    framework::standard::{CommandResult, Args, Delimiter},
     Error
 };
 use crate::commands::ucm::pav_models::*;
 use log::error;
-use serenity::framework::standard::Args;
+use crate::services::cow_framework::{command, CowCommand, CommandOption, OptionKind, Invocation};
+use crate::models::error::CowError;
 
 // Probably can be hard-coded to be 61bd7ecd8c760e0011ac0fac.
-async fn fetch_pavilion_company_info(client: &Client) -> Result<Company, Box<dyn std::error::Error + Send + Sync>> {
+async fn fetch_pavilion_company_info(client: &Client) -> Result<Company, CowError> {
     let response = client
         .get("https://widget.api.eagle.bigzpoon.com/company")
         .header("x-comp-id", "uc-merced-the-pavilion")
@@ -28,7 +23,7 @@ async fn fetch_pavilion_company_info(client: &Client) -> Result<Company, Box<dyn
     Ok(result.data)
 }
 
-async fn fetch_pavilion_groups(client: &Client, company: &Company) -> Result<MenuGroups, Box<dyn std::error::Error + Send + Sync>> {
+async fn fetch_pavilion_groups(client: &Client, company: &Company) -> Result<MenuGroups, CowError> {
     let url = format!("https://widget.api.eagle.bigzpoon.com/locations/menugroups?locationId={}", company.location_info.id);
 
     let response = client
@@ -43,11 +38,12 @@ async fn fetch_pavilion_groups(client: &Client, company: &Company) -> Result<Men
     Ok(result.data)
 }
 
-async fn fetch_pavilion_menu(client: &Client, company: &Company, category: &str, group: &str) -> Result<MenuItems, Box<dyn std::error::Error + Send + Sync>> {
+async fn fetch_pavilion_menu(client: &Client, company: &Company, category: &str, group: &str) -> Result<MenuItems, CowError> {
     // I still can't believe someone thought putting JSON in a GET query was a good idea.
     let url = Url::parse_with_params("https://widget.api.eagle.bigzpoon.com/menuitems",
     &[("categoryId", category), ("isPreview", "false"), ("locationId", company.location_info.id.as_str()), ("menuGroupId", group),
-        ("userPreferences", r#"{"allergies":[],"lifestyleChoices":[],"medicalGoals":[],"preferenceApplyStatus":false}"#)])?;
+        ("userPreferences", r#"{"allergies":[],"lifestyleChoices":[],"medicalGoals":[],"preferenceApplyStatus":false}"#)])
+        .map_err(CowError::external)?;
 
     let response = client
         .get(url)
@@ -61,8 +57,8 @@ async fn fetch_pavilion_menu(client: &Client, company: &Company, category: &str,
     Ok(result.data)
 }
 
-async fn print_pavilion_times(ctx: &Context, msg: &Message) -> Result<(), Error> {
-    msg.channel_id.send_message(&ctx.http, |m| m.embed(|e| e
+async fn print_pavilion_times(ctx: &Context, cmd: &Invocation) -> Result<(), Error> {
+    cmd.send_message(&ctx.http, |m| m.embed(|e| e
         .title("Pavilion Times")
         .field("Weekdays", format!("Breakfast: {} - {}\nLunch: {} - {}\nDinner: {} - {}",
             PavilionTime::breakfast_weekday_start().format("%l:%M %p"), PavilionTime::breakfast_end().format("%l:%M %p"),
@@ -77,20 +73,25 @@ async fn print_pavilion_times(ctx: &Context, msg: &Message) -> Result<(), Error>
     Ok(())
 }
 
+pub static PAVILION_COMMAND: CowCommand = CowCommand {
+    aliases: &["pav"],
+    options: &[CommandOption::new("query", "A day and/or meal, or \"hours\" for the opening times.", OptionKind::String)],
+    ..CowCommand::new("pavilion", "Get the current menu at the UCM Pavilion.", pavilion)
+};
+
 #[command]
-#[description = "Get the current menu at the UCM Pavilion."]
-#[aliases("pav")]
-pub async fn pavilion(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+pub async fn pavilion(ctx: &Context, cmd: &Invocation) -> CommandResult {
     let date = chrono::offset::Local::now();
     let (mut day, mut meal) = PavilionTime::next_meal(&date);
 
     let mut custom_meal = String::new();
+    let mut args = Args::new(&cmd.arg::<String>("query").unwrap_or_default(), &[Delimiter::Single(' ')]);
 
     if args.len() == 1 {
         // Peek at first element to check if it's asking for the hours.
         let input_lower = args.parse::<String>().unwrap().to_lowercase();
         if input_lower.contains("time") || input_lower.contains("hour") {
-            print_pavilion_times(ctx, msg).await?;
+            print_pavilion_times(ctx, cmd).await?;
             return Ok(())
         }
     }
@@ -120,7 +121,7 @@ pub async fn pavilion(ctx: &Context, msg: &Message, mut args: Args) -> CommandRe
         title = format!("{} at the Pavilion for {}", meal, day);
     }
 
-    let mut message = msg.channel_id.send_message(&ctx.http, |m| m.embed(|e| {
+    let mut message = cmd.send_message(&ctx.http, |m| m.embed(|e| {
         e
             .title(&title)
             .description("Loading data, please wait warmly...")
@@ -1,21 +1,17 @@
 use chrono::{Datelike, DateTime, Local, TimeZone, Utc};
-use log::error;
 use serenity::{
+    builder::CreateEmbed,
     client::Context,
-    model::{
-        channel::Message
-    },
-    framework::standard::{
-        CommandResult,
-        macros::{
-            command
-        }, Args
-    }
+    framework::standard::CommandResult
 };
 use crate::commands::ucm::courses_db_models::*;
 use crate::{Database, db};
+use crate::services::cow_framework::{command, paginate, CowCommand, CommandOption, OptionKind, Invocation, StaticPages};
+use crate::models::error::CowError;
+
+const SEARCH_PAGE_SIZE: usize = 10;
 
-async fn professor_embed(ctx: &Context, msg: &Message, professor: &Professor) -> CommandResult {
+async fn professor_embed(ctx: &Context, cmd: &Invocation, professor: &Professor) -> Result<(), CowError> {
     let db = db!(ctx);
 
     let current_date = Local::now().date();
@@ -25,7 +21,7 @@ async fn professor_embed(ctx: &Context, msg: &Message, professor: &Professor) ->
 
     let classes = db.get_classes_for_professor(professor.id, term).await;
     let stats = db.get_stats().await;
-    msg.channel_id.send_message(&ctx.http, |m| m.embed(|e| {
+    cmd.send_message(&ctx.http, |m| m.embed(|e| {
         e.title(&professor.full_name);
         e.description("Note: this uses Rate My Professor, which may be off at times~");
         e.field("Rating Score", professor.rating, true);
@@ -57,45 +53,44 @@ async fn professor_embed(ctx: &Context, msg: &Message, professor: &Professor) ->
     Ok(())
 }
 
+pub static PROFESSORS_COMMAND: CowCommand = CowCommand {
+    aliases: &["professor"],
+    usage: Some("<Professor's Name>"),
+    options: &[CommandOption::new("name", "The professor's name.", OptionKind::String).required()],
+    ..CowCommand::new("professors", "Search for a professor.", professors)
+};
+
 #[command]
-#[description = "Search for a professor."]
-#[aliases("professor")]
-#[usage = "<Professor's Name>"]
-pub async fn professors(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
-    let search_query = args.message();
+pub async fn professors(ctx: &Context, cmd: &Invocation) -> CommandResult {
+    let search_query = cmd.arg::<String>("name").unwrap_or_default();
 
     let db = db!(ctx);
-    match db.search_professor(search_query).await {
-        Ok(professors) => {
-            print_matches(ctx, msg, &professors).await?;
-        }
-        Err(ex) => {
-            error!("Failed to search by name: {}", ex);
-            msg.channel_id.say(&ctx.http, "Failed to search for professors... try again later?").await?;
-        }
-    }
+    let professors = db.search_professor(&search_query).await?;
+    print_matches(ctx, cmd, &professors).await?;
 
     Ok(())
 }
 
-async fn print_matches(ctx: &Context, msg: &Message, professors: &[Professor]) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+async fn print_matches(ctx: &Context, cmd: &Invocation, professors: &[Professor]) -> Result<(), CowError> {
     if professors.is_empty() {
-        msg.channel_id.say(&ctx.http, "No matches were found. Check your query for typos, or generalize it. Or, we may not have the person logged.").await?;
+        cmd.say(&ctx.http, "No matches were found. Check your query for typos, or generalize it. Or, we may not have the person logged.").await?;
     } else if professors.len() == 1 {
-        professor_embed(ctx, msg, professors.get(0).unwrap()).await?;
+        professor_embed(ctx, cmd, &professors[0]).await?;
     } else {
-        msg.channel_id.send_message(&ctx.http, |m| m.embed(|e| {
-            e.title("Professor Search").description("Multiple results were found for your query. Try refining your input.");
-            e.field(format!("Professors Matched (totalling {})", professors.len()),
-                    professors
-                        .iter()
-                        .take(10)
-                        .map(|o| format!("`{}` - {}", o.full_name, o.department.clone().unwrap_or_else(|| "<unknown department>".to_string())))
-                        .reduce(|a, b| format!("{}\n{}", a, b))
-                        .unwrap(),
-                    false);
-            e
-        })).await?;
+        let lines = professors
+            .iter()
+            .map(|o| format!("`{}` - {}", o.full_name, o.department.clone().unwrap_or_else(|| "<unknown department>".to_string())))
+            .collect::<Vec<_>>();
+        let pages = lines
+            .chunks(SEARCH_PAGE_SIZE)
+            .map(|chunk| {
+                let mut e = CreateEmbed::default();
+                e.title("Professor Search").description("Multiple results were found for your query. Try refining your input.");
+                e.field(format!("Professors Matched (totalling {})", professors.len()), chunk.join("\n"), false);
+                e
+            })
+            .collect();
+        paginate(ctx, cmd, &StaticPages(pages), 0).await?;
     }
 
     Ok(())
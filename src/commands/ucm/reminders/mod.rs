@@ -1,7 +1,5 @@
 mod course_reminders;
 
-use serenity::framework::standard::macros::group;
-
 use course_reminders::*;
 use std::sync::Arc;
 use std::time::Duration;
@@ -13,14 +11,17 @@ use serenity::{
 use tokio::sync::RwLock;
 use tokio::time;
 use crate::{Database};
+use crate::services::cow_framework::CowGroup;
 
-#[group]
-#[prefixes("reminders", "reminder", "remind")]
-#[description = "Set up reminders for class registration, based off seats or waitlist."]
-#[summary = "UCM Course Waitlist"]
-#[default_command(list)]
-#[commands(add, remove, list)]
-struct Reminders;
+pub static REMINDERS_GROUP: CowGroup = CowGroup {
+    name: "Reminders",
+    prefixes: &["reminders", "reminder", "remind"],
+    description: "Set up reminders for class registration, based off seats or waitlist.",
+    summary: "UCM Course Waitlist",
+    default_command: Some(&LIST_COMMAND),
+    commands: &[&ADD_COMMAND, &REMOVE_COMMAND, &LIST_COMMAND],
+    sub_groups: &[]
+};
 
 pub async fn check_reminders(data: Arc<RwLock<TypeMap>>, ctx: Arc<CacheAndHttp>) {
     let mut interval_min = time::interval(Duration::from_secs(60));
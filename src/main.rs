@@ -4,28 +4,28 @@ mod services;
 mod util;
 
 use std::collections::{HashSet};
-use commands::{get_framework};
+use commands::{get_framework, rank_config::reconciler::{self, RoleReconciler}};
 use models::config::Config;
-use services::{*, database::Database};
-use std::fs;
+use services::{*, cow_framework::{CowFramework, SharedFramework}, database::{Database, Storage}, guild_settings::GuildSettings, cache::DbCache, supervisor::Supervisor, voice_xp::VoiceTracker, quality_filter::QualityFilter};
 use std::sync::Arc;
 use std::env;
+use std::time::Duration;
 use env_logger::Env;
 use lavalink_rs::{LavalinkClient, gateway::LavalinkEventHandler};
 use serenity::{
     async_trait,
-    client::{Client, Context, EventHandler, bridge::gateway::GatewayIntents},
-    model::{channel::{Message, Reaction}, gateway::Ready, interactions::Interaction, id::{UserId, GuildId, ChannelId, MessageId}, guild::Member},
+    client::{Client, Context, EventHandler, bridge::gateway::{GatewayIntents, ShardManager}},
+    model::{channel::{Message, Reaction}, gateway::Ready, interactions::Interaction, id::{UserId, GuildId, ChannelId, MessageId}, guild::{Guild, Member}, voice::VoiceState},
     http::Http,
-    framework::Framework,
-    prelude::TypeMapKey
+    prelude::{Mutex, RwLock, TypeMap, TypeMapKey}
 };
 use log::{error, info};
 use songbird::SerenityInit;
 
 struct Handler {
-    framework: Arc<Box<dyn Framework + Sync + std::marker::Send>>,
-    database: Arc<Database>
+    framework: Arc<CowFramework>,
+    dev_guilds: Vec<GuildId>,
+    database: Arc<dyn Storage>
 }
 
 struct Lavalink;
@@ -61,8 +61,16 @@ impl EventHandler for Handler {
         crate::commands::cowboard::cowboard_handler::reaction_remove_all(&ctx, channel_id, removed_from_message_id).await;
     }
 
+    async fn guild_create(&self, ctx: Context, guild: Guild, _is_new: bool) {
+        VoiceTracker::from_context(&ctx).await.add_guild(&guild);
+    }
+
+    async fn voice_state_update(&self, ctx: Context, guild_id: Option<GuildId>, _old: Option<VoiceState>, new: VoiceState) {
+        voice_xp::voice_state_update(&ctx, guild_id, &new).await;
+    }
+
     async fn ready(&self, ctx: Context, ready: Ready) {
-        bot_init::ready(&ctx, &ready).await;
+        bot_init::ready(&ctx, &ready, &self.framework, &self.dev_guilds).await;
     }
 
     async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
@@ -105,22 +113,90 @@ async fn fetch_bot_info(token: &str) -> (UserId, HashSet<UserId>) {
     (app_id, owners)
 }
 
+async fn wait_for_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {},
+                    _ = terminate.recv() => {}
+                }
+                return;
+            },
+            Err(ex) => error!("Failed to listen for SIGTERM: {}", ex)
+        }
+    }
+
+    if let Err(ex) = tokio::signal::ctrl_c().await {
+        error!("Failed to listen for Ctrl+C: {}", ex);
+        // Don't shut down just because we can't listen for it.
+        std::future::pending::<()>().await;
+    }
+}
+
+// Stops things in the order they depend on each other: the background jobs first, then voice (which still needs
+// the gateway to leave properly), then the shards. Whatever is still running finishes up in main.
+async fn shutdown(shard_manager: Arc<Mutex<ShardManager>>, data: Arc<RwLock<TypeMap>>, supervisor: Arc<Supervisor>) {
+    wait_for_signal().await;
+    info!("Shutting down...");
+
+    supervisor.stop_jobs().await;
+
+    {
+        let data = data.read().await;
+        if let Some(lava_client) = data.get::<Lavalink>() {
+            let guilds = lava_client.nodes().await.iter().map(|node| *node.key()).collect::<Vec<_>>();
+            let voice = data.get::<songbird::SongbirdKey>();
+
+            for guild in guilds {
+                if let Some(voice) = voice {
+                    if let Err(ex) = voice.remove(GuildId(guild)).await {
+                        error!("Failed to leave voice in {}: {}", guild, ex);
+                    }
+                }
+                if let Err(ex) = lava_client.destroy(guild).await {
+                    error!("Failed to destroy the Lavalink session for {}: {}", guild, ex);
+                }
+            }
+        }
+    }
+
+    shard_manager.lock().await.shutdown_all().await;
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>>  {
     if let Err(ex) = init_logger().await {
         error!("Failed to initialize logger: {}", ex);
     }
 
-    let config_json = fs::read_to_string("config.json").expect("config.json not found");
-    let config : Config = serde_json::from_str(&config_json).expect("config.json is malformed");
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(ex) => {
+            error!("{}", ex);
+            std::process::exit(1);
+        }
+    };
+
+    // Only apply migrations and exit, for deployments that don't migrate on startup.
+    let migrate_only = env::args().any(|arg| arg == "--migrate");
+    if migrate_only {
+        Database::connect(&config, true).await.expect("Failed to migrate the database");
+        info!("Database is up to date.");
+        return Ok(());
+    }
 
-    let token = config.token;
+    let token = config.token.clone();
     let (app_id, owners) = fetch_bot_info(&token).await;
-    let framework = get_framework(&config.cmd_prefix, app_id, owners).await;
+    let framework = get_framework(&config.cmd_prefix, app_id, owners);
 
     let event_handler = Handler {
         framework: framework.clone(),
-        database: Arc::new(Database::new(&*config.sql_server_ip, config.sql_server_port, &*config.sql_server_username, &*config.sql_server_password).await.unwrap())
+        dev_guilds: config.dev_guilds.iter().map(|id| GuildId(*id)).collect(),
+        database: Database::connect(&config, config.auto_migrate).await.expect("Failed to connect to the database")
     };
 
     let db_clone = event_handler.database.clone();
@@ -128,7 +204,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>>  {
     let mut client = Client::builder(&token)
         .event_handler(event_handler)
         .application_id(*app_id.as_u64())
-        .framework_arc(framework)
+        .framework(SharedFramework(framework))
         .intents(GatewayIntents::all())
         .register_songbird()
         .await
@@ -157,15 +233,52 @@ async fn main() -> Result<(), Box<dyn std::error::Error>>  {
     {
         let mut data = client.data.write().await;
         // Should I wrap it with an RwLock? ...it's pooled and async is nice, but...
+        data.insert::<GuildSettings>(Arc::new(GuildSettings::new(db_clone.clone())));
+        data.insert::<DbCache>(Arc::new(DbCache::new(db_clone.clone())));
+        data.insert::<VoiceTracker>(Arc::new(VoiceTracker::new()));
+        data.insert::<QualityFilter>(Arc::new(QualityFilter::new()));
+        data.insert::<RoleReconciler>(Arc::new(RoleReconciler::new()));
         data.insert::<Database>(db_clone);
     }
 
-    // Start our reminder task and forget about it.
-    let _ = tokio::task::spawn(crate::commands::ucm::reminders::check_reminders(client.data.clone(), client.cache_and_http.clone()));
+    let supervisor = Arc::new(Supervisor::new());
+    client.data.write().await.insert::<Supervisor>(supervisor.clone());
+
+    {
+        let data = client.data.clone();
+        let cache_and_http = client.cache_and_http.clone();
+        supervisor.spawn("Course reminders", move || crate::commands::ucm::reminders::check_reminders(data.clone(), cache_and_http.clone()));
+    }
+
+    {
+        let data = client.data.clone();
+        let cache_and_http = client.cache_and_http.clone();
+        supervisor.spawn("Voice XP", move || voice_xp::credit_voice(data.clone(), cache_and_http.clone()));
+    }
+
+    {
+        let data = client.data.clone();
+        let cache_and_http = client.cache_and_http.clone();
+        supervisor.spawn("Weekly top", move || weekly_top::post_weekly_top(data.clone(), cache_and_http.clone()));
+    }
+
+    {
+        let data = client.data.clone();
+        let cache_and_http = client.cache_and_http.clone();
+        supervisor.spawn("Role fixes", move || reconciler::reconcile_roles(data.clone(), cache_and_http.clone()));
+    }
+
+    tokio::spawn(shutdown(client.shard_manager.clone(), client.data.clone(), supervisor.clone()));
 
     if let Err(ex) = client.start().await {
         error!("Discord bot client error: {:?}", ex);
     }
 
+    // However the client stopped, let everything finish before the database goes away with the client.
+    supervisor.stop_jobs().await;
+    supervisor.drain(Duration::from_secs(30)).await;
+    drop(client);
+    info!("Goodbye!");
+
     Ok(())
 }
\ No newline at end of file
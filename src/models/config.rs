@@ -1,13 +1,268 @@
+use std::{env, fmt, fs};
 use serde::Deserialize;
+use serde_json::{Map, Value};
+
+#[derive(Debug, Default, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DatabaseBackend {
+    #[default]
+    SqlServer,
+    Sqlite
+}
+
+fn default_sql_server_port() -> u16 {
+    1433
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_sqlite_path() -> String {
+    "cow.db".to_string()
+}
 
 #[derive(Debug, Deserialize)]
 pub struct Config {
+    #[serde(default)]
     pub token: String,
+    #[serde(default)]
+    pub database: DatabaseBackend,
+    #[serde(default)]
     pub sql_server_ip: String,
+    #[serde(default = "default_sql_server_port")]
     pub sql_server_port: u16,
+    #[serde(default)]
     pub sql_server_username: String,
+    #[serde(default)]
     pub sql_server_password: String,
+    #[serde(default = "default_sqlite_path")]
+    pub sqlite_path: String,
+    // Apply pending migrations on startup; otherwise run with --migrate.
+    #[serde(default = "default_true")]
+    pub auto_migrate: bool,
+    // Register slash commands to these guilds only, which update instantly; leave empty to register globally.
+    #[serde(default)]
+    pub dev_guilds: Vec<u64>,
+    #[serde(default)]
     pub cmd_prefix: String,
+    #[serde(default)]
     pub lavalink_ip: String,
+    #[serde(default)]
     pub lavalink_password: String,
-}
\ No newline at end of file
+}
+
+// Every problem found while loading, so they can all be fixed in one go.
+#[derive(Debug)]
+pub struct ConfigError(pub Vec<String>);
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid configuration:")?;
+        for problem in &self.0 {
+            write!(f, "\n - {}", problem)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+#[derive(Clone, Copy)]
+enum SettingKind {
+    Text,
+    Port,
+    Boolean,
+    IdList
+}
+
+// Everything that can be overridden by a COW_* environment variable or a --flag.
+const SETTINGS: [(&str, SettingKind); 12] = [
+    ("token", SettingKind::Text),
+    ("database", SettingKind::Text),
+    ("sql_server_ip", SettingKind::Text),
+    ("sql_server_port", SettingKind::Port),
+    ("sql_server_username", SettingKind::Text),
+    ("sql_server_password", SettingKind::Text),
+    ("sqlite_path", SettingKind::Text),
+    ("auto_migrate", SettingKind::Boolean),
+    ("dev_guilds", SettingKind::IdList),
+    ("cmd_prefix", SettingKind::Text),
+    ("lavalink_ip", SettingKind::Text),
+    ("lavalink_password", SettingKind::Text)
+];
+
+// Flags that aren't settings, but shouldn't be reported as unknown either.
+const OTHER_FLAGS: [&str; 1] = ["--migrate"];
+
+const DEFAULT_PATH: &str = "config.json";
+
+// Environment variables and flags are always text, so turn them into what the file would have had.
+fn parse_setting(name: &str, kind: SettingKind, raw: &str, source: &str, problems: &mut Vec<String>) -> Option<Value> {
+    match kind {
+        SettingKind::Text => Some(Value::String(raw.to_string())),
+        SettingKind::Port => match raw.trim().parse::<u16>() {
+            Ok(port) => Some(Value::from(port)),
+            Err(_) => {
+                problems.push(format!("{} from {} must be a port between 1 and 65535, got \"{}\".", name, source, raw));
+                None
+            }
+        },
+        SettingKind::Boolean => match raw.trim().to_lowercase().as_str() {
+            "true" | "1" | "yes" => Some(Value::Bool(true)),
+            "false" | "0" | "no" => Some(Value::Bool(false)),
+            _ => {
+                problems.push(format!("{} from {} must be true or false, got \"{}\".", name, source, raw));
+                None
+            }
+        },
+        SettingKind::IdList => {
+            let ids = raw.split(',')
+                .map(str::trim)
+                .filter(|id| !id.is_empty())
+                .map(|id| id.parse::<u64>())
+                .collect::<Result<Vec<_>, _>>();
+            match ids {
+                Ok(ids) => Some(Value::from(ids)),
+                Err(_) => {
+                    problems.push(format!("{} from {} must be a comma-separated list of IDs, got \"{}\".", name, source, raw));
+                    None
+                }
+            }
+        }
+    }
+}
+
+fn find_setting(name: &str) -> Option<(&'static str, SettingKind)> {
+    SETTINGS.iter().find(|(setting, _)| *setting == name).copied()
+}
+
+// Splits "--flag value" and "--flag=value" into pairs; the config path is pulled out separately.
+fn parse_args(args: &[String], problems: &mut Vec<String>) -> (Option<String>, Vec<(String, String)>) {
+    let mut path = None;
+    let mut flags = Vec::new();
+    let mut iter = args.iter();
+
+    while let Some(arg) = iter.next() {
+        if OTHER_FLAGS.contains(&arg.as_str()) {
+            continue;
+        }
+
+        let flag = match arg.strip_prefix("--") {
+            Some(flag) => flag,
+            None => {
+                problems.push(format!("Unexpected argument \"{}\".", arg));
+                continue;
+            }
+        };
+
+        let (name, value) = match flag.split_once('=') {
+            Some((name, value)) => (name.to_string(), Some(value.to_string())),
+            None => (flag.to_string(), iter.next().cloned())
+        };
+
+        match value {
+            Some(value) if name == "config" => path = Some(value),
+            Some(value) => flags.push((name.replace('-', "_"), value)),
+            None => problems.push(format!("--{} needs a value.", name))
+        }
+    }
+
+    (path, flags)
+}
+
+impl Config {
+    // Settings are layered: the config file, then COW_* environment variables, then command line flags.
+    pub fn load() -> Result<Config, ConfigError> {
+        let mut problems = Vec::new();
+        let args = env::args().skip(1).collect::<Vec<_>>();
+        let (path, flags) = parse_args(&args, &mut problems);
+
+        let mut settings = Map::new();
+
+        // The file is optional unless it was asked for, since containers may only use environment variables.
+        let explicit_path = path.is_some();
+        let path = path.unwrap_or_else(|| DEFAULT_PATH.to_string());
+        match fs::read_to_string(&path) {
+            Ok(json) => match serde_json::from_str::<Map<String, Value>>(&json) {
+                Ok(file) => settings = file,
+                Err(ex) => problems.push(format!("{} is malformed: {}", path, ex))
+            },
+            Err(ex) if explicit_path => problems.push(format!("Could not read {}: {}", path, ex)),
+            Err(_) => {}
+        }
+
+        for (name, kind) in SETTINGS {
+            let var = format!("COW_{}", name.to_uppercase());
+            if let Ok(raw) = env::var(&var) {
+                if let Some(value) = parse_setting(name, kind, &raw, &var, &mut problems) {
+                    settings.insert(name.to_string(), value);
+                }
+            }
+        }
+
+        for (name, raw) in flags {
+            match find_setting(&name) {
+                Some((name, kind)) => {
+                    let source = format!("--{}", name.replace('_', "-"));
+                    if let Some(value) = parse_setting(name, kind, &raw, &source, &mut problems) {
+                        settings.insert(name.to_string(), value);
+                    }
+                },
+                None => problems.push(format!("Unknown flag --{}.", name.replace('_', "-")))
+            }
+        }
+
+        let config = match serde_json::from_value::<Config>(Value::Object(settings)) {
+            Ok(config) => Some(config),
+            Err(ex) => {
+                problems.push(format!("Could not read the settings: {}", ex));
+                None
+            }
+        };
+
+        if let Some(config) = &config {
+            problems.extend(config.validate());
+        }
+
+        match config {
+            Some(config) if problems.is_empty() => Ok(config),
+            _ => Err(ConfigError(problems))
+        }
+    }
+
+    fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        if self.token.trim().is_empty() {
+            problems.push("token must be set.".to_string());
+        }
+
+        if self.cmd_prefix.is_empty() {
+            problems.push("cmd_prefix must be set.".to_string());
+        }
+
+        match self.database {
+            DatabaseBackend::SqlServer => {
+                if self.sql_server_ip.trim().is_empty() {
+                    problems.push("sql_server_ip must be set when using SQL Server.".to_string());
+                }
+                if self.sql_server_port == 0 {
+                    problems.push("sql_server_port must be between 1 and 65535.".to_string());
+                }
+            },
+            DatabaseBackend::Sqlite => {
+                if self.sqlite_path.trim().is_empty() {
+                    problems.push("sqlite_path must be set when using SQLite.".to_string());
+                }
+            }
+        }
+
+        // Music needs both, and having only one is probably a typo.
+        if self.lavalink_ip.is_empty() != self.lavalink_password.is_empty() {
+            problems.push("lavalink_ip and lavalink_password must be set together (or both left empty to disable music).".to_string());
+        }
+
+        problems
+    }
+}
@@ -1,7 +1,9 @@
-use serenity::model::id::{RoleId, UserId};
+use chrono::NaiveDateTime;
+use serenity::model::id::{ChannelId, RoleId, UserId};
 
 pub struct LevelUp {
     pub level: i32,
+    pub old_level: i32,
     pub old_rank: Option<u64>,
     pub new_rank: Option<u64>
 }
@@ -10,6 +12,7 @@ impl LevelUp {
     pub fn new() -> Self {
         LevelUp {
             level: 0,
+            old_level: 0,
             old_rank: None,
             new_rank: None
         }
@@ -51,4 +54,61 @@ pub struct MemberPagination {
     pub members: Vec<Member>,
     pub current_page: i32,
     pub last_page: i32
-}
\ No newline at end of file
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum MultiplierTarget {
+    Channel(ChannelId),
+    Role(RoleId)
+}
+
+#[derive(Clone)]
+pub struct XpMultiplier {
+    pub target: MultiplierTarget,
+    pub multiplier: f64
+}
+
+impl XpMultiplier {
+    // The channel's multiplier times every role the member has one for. Anything without one counts as 1.
+    pub fn combine(multipliers: &[XpMultiplier], channel_id: ChannelId, roles: &[RoleId]) -> f64 {
+        multipliers.iter()
+            .filter(|m| match m.target {
+                MultiplierTarget::Channel(channel) => channel == channel_id,
+                MultiplierTarget::Role(role) => roles.contains(&role)
+            })
+            .map(|m| m.multiplier)
+            .product()
+    }
+}
+
+pub struct AuditEntry {
+    pub moderator: UserId,
+    // None when it applied to everyone.
+    pub user: Option<UserId>,
+    pub action: String,
+    pub detail: String,
+    // UTC. Set by the database when the entry is added.
+    pub created_at: Option<NaiveDateTime>
+}
+
+// Experience someone earned over some stretch of time.
+pub struct XpGain {
+    pub user: UserId,
+    pub gain: i64
+}
+
+// One role a fix added or took away.
+#[derive(Debug, Clone, Copy)]
+pub struct RoleChange {
+    pub user: UserId,
+    pub role: RoleId,
+    pub added: bool
+}
+
+// Everything one run of fix changed, so it can be undone.
+pub struct FixChangeset {
+    pub id: i32,
+    // UTC.
+    pub created_at: NaiveDateTime,
+    pub changes: Vec<RoleChange>
+}
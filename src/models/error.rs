@@ -0,0 +1,109 @@
+use std::fmt;
+
+// Anything that can go wrong in a database or command path, split up by who we'd blame.
+#[derive(Debug)]
+pub enum CowError {
+    // Couldn't get a connection in the first place.
+    Pool(Box<dyn std::error::Error + Send + Sync>),
+    // The database got the query, but it failed.
+    Query(Box<dyn std::error::Error + Send + Sync>),
+    // The query worked, but what came back wasn't what we expected (NULLs, wrong types, schema drift...)
+    Decode(String),
+    Discord(serenity::Error),
+    // The UCM sites and anything else we scrape.
+    ExternalApi(Box<dyn std::error::Error + Send + Sync>)
+}
+
+impl CowError {
+    pub fn decode(what: impl fmt::Display) -> Self {
+        CowError::Decode(what.to_string())
+    }
+
+    pub fn external(what: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> Self {
+        CowError::ExternalApi(what.into())
+    }
+
+    // What the user gets told, so every command says the same thing for the same problem.
+    pub fn user_message(&self) -> &'static str {
+        match self {
+            CowError::Pool(_) => "I couldn't reach the database right now... try again later?",
+            CowError::Query(_) | CowError::Decode(_) => "Something went wrong talking to the database... try again later?",
+            CowError::Discord(_) => "Discord didn't let me do that; maybe I'm missing permissions?",
+            CowError::ExternalApi(_) => "The site I get that from isn't responding properly... try again later?"
+        }
+    }
+}
+
+impl fmt::Display for CowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CowError::Pool(ex) => write!(f, "Could not get a database connection: {}", ex),
+            CowError::Query(ex) => write!(f, "Database query failed: {}", ex),
+            CowError::Decode(what) => write!(f, "Unexpected data from the database: {}", what),
+            CowError::Discord(ex) => write!(f, "Discord request failed: {}", ex),
+            CowError::ExternalApi(ex) => write!(f, "External API request failed: {}", ex)
+        }
+    }
+}
+
+impl std::error::Error for CowError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CowError::Pool(ex) | CowError::Query(ex) | CowError::ExternalApi(ex) => Some(ex.as_ref()),
+            CowError::Discord(ex) => Some(ex),
+            CowError::Decode(_) => None
+        }
+    }
+}
+
+impl From<bb8::RunError<bb8_tiberius::Error>> for CowError {
+    fn from(ex: bb8::RunError<bb8_tiberius::Error>) -> Self {
+        CowError::Pool(Box::new(ex))
+    }
+}
+
+impl From<bb8_tiberius::Error> for CowError {
+    fn from(ex: bb8_tiberius::Error) -> Self {
+        CowError::Pool(Box::new(ex))
+    }
+}
+
+impl From<tiberius::error::Error> for CowError {
+    fn from(ex: tiberius::error::Error) -> Self {
+        match ex {
+            tiberius::error::Error::Conversion(_) | tiberius::error::Error::Utf8 | tiberius::error::Error::Utf16 => CowError::Decode(ex.to_string()),
+            _ => CowError::Query(Box::new(ex))
+        }
+    }
+}
+
+impl From<rusqlite::Error> for CowError {
+    fn from(ex: rusqlite::Error) -> Self {
+        match ex {
+            rusqlite::Error::FromSqlConversionFailure(..)
+            | rusqlite::Error::IntegralValueOutOfRange(..)
+            | rusqlite::Error::InvalidColumnIndex(_)
+            | rusqlite::Error::InvalidColumnName(_)
+            | rusqlite::Error::InvalidColumnType(..) => CowError::Decode(ex.to_string()),
+            _ => CowError::Query(Box::new(ex))
+        }
+    }
+}
+
+impl From<serenity::Error> for CowError {
+    fn from(ex: serenity::Error) -> Self {
+        CowError::Discord(ex)
+    }
+}
+
+impl From<reqwest::Error> for CowError {
+    fn from(ex: reqwest::Error) -> Self {
+        CowError::ExternalApi(Box::new(ex))
+    }
+}
+
+impl From<serde_json::Error> for CowError {
+    fn from(ex: serde_json::Error) -> Self {
+        CowError::ExternalApi(Box::new(ex))
+    }
+}
@@ -0,0 +1,128 @@
+use std::fmt;
+use serenity::model::id::{ChannelId, UserId};
+use crate::models::db_models::LevelUp;
+
+pub const DEFAULT_TEMPLATE: &str = "{user} leveled up from {old_level} to {level}.";
+
+pub const PLACEHOLDERS: &[&str] = &["user", "level", "old_level", "role"];
+
+// Where level-up messages go.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LevelUpRoute {
+    // Wherever the experience came from.
+    SameChannel,
+    Channel(ChannelId),
+    DirectMessage,
+    Nowhere
+}
+
+impl LevelUpRoute {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.trim().to_lowercase().as_str() {
+            "same" | "here" => Ok(LevelUpRoute::SameChannel),
+            "dm" => Ok(LevelUpRoute::DirectMessage),
+            "none" | "off" => Ok(LevelUpRoute::Nowhere),
+            other => other.parse::<ChannelId>()
+                .map(LevelUpRoute::Channel)
+                .map_err(|_| "This should be same, dm, none, or a channel.".to_string())
+        }
+    }
+}
+
+impl fmt::Display for LevelUpRoute {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LevelUpRoute::SameChannel => write!(f, "same"),
+            LevelUpRoute::Channel(channel_id) => write!(f, "{}", channel_id),
+            LevelUpRoute::DirectMessage => write!(f, "dm"),
+            LevelUpRoute::Nowhere => write!(f, "none")
+        }
+    }
+}
+
+// Every {name} has to be one we know about, so typos show up when the template is saved instead of in every announcement.
+pub fn validate_template(template: &str) -> Result<String, String> {
+    let template = template.trim();
+    if template.is_empty() || template.chars().count() > 1500 {
+        return Err("The message must be between 1 and 1500 characters.".to_string());
+    }
+
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let end = rest[start..].find('}')
+            .ok_or_else(|| "There is a { without a matching }.".to_string())?;
+        let name = &rest[start + 1..start + end];
+        if !PLACEHOLDERS.contains(&name) {
+            return Err(format!("{{{}}} isn't something I can fill in. You can use {}.", name,
+                PLACEHOLDERS.iter().map(|p| format!("{{{}}}", p)).collect::<Vec<_>>().join(", ")));
+        }
+        rest = &rest[start + end + 1..];
+    }
+
+    Ok(template.to_string())
+}
+
+// Takes out every sentence that mentions {role}, for level-ups that don't reach a new rank.
+fn without_role(template: &str) -> String {
+    let mut out = String::new();
+    let mut sentence = String::new();
+    for c in template.chars() {
+        sentence.push(c);
+        if matches!(c, '.' | '!' | '?' | '\n') {
+            if !sentence.contains("{role}") {
+                out += &sentence;
+            }
+            sentence.clear();
+        }
+    }
+    if !sentence.contains("{role}") {
+        out += &sentence;
+    }
+
+    out.trim().to_string()
+}
+
+// Fills in everything validate_template lets through. {role} only applies when they reached a new rank;
+// otherwise the sentences using it are left out, and the default message is used if that leaves nothing.
+pub fn fill(template: &str, user_id: UserId, data: &LevelUp) -> String {
+    let role = data.new_rank.map(|role| format!("<@&{}>", role)).unwrap_or_default();
+    let template = match data.new_rank {
+        Some(_) => template.to_string(),
+        None => Some(without_role(template)).filter(|t| !t.is_empty()).unwrap_or_else(|| DEFAULT_TEMPLATE.to_string())
+    };
+    template
+        .replace("{user}", &format!("<@{}>", user_id))
+        .replace("{level}", &data.level.to_string())
+        .replace("{old_level}", &data.old_level.to_string())
+        .replace("{role}", &role)
+}
+
+pub struct LevelUpSettings {
+    pub template: String,
+    pub route: LevelUpRoute,
+    // Nothing is announced for levels below this, though roles still change.
+    pub min_level: i32
+}
+
+impl Default for LevelUpSettings {
+    fn default() -> Self {
+        LevelUpSettings {
+            template: DEFAULT_TEMPLATE.to_string(),
+            route: LevelUpRoute::SameChannel,
+            min_level: 0
+        }
+    }
+}
+
+impl LevelUpSettings {
+    // {role} is the rank they just got, if they got one. Templates without it still say so on a new line.
+    pub fn render(&self, user_id: UserId, data: &LevelUp) -> String {
+        let mut content = fill(&self.template, user_id, data);
+        let role = data.new_rank.map(|role| format!("<@&{}>", role)).unwrap_or_default();
+        if !self.template.contains("{role}") && !role.is_empty() {
+            content += &format!("\nYou are now a {}.", role);
+        }
+
+        content
+    }
+}
@@ -1,3 +1,9 @@
 pub mod config;
 pub mod macros;
-pub mod db_models;
\ No newline at end of file
+pub mod db_models;
+pub mod error;
+pub mod xp_settings;
+pub mod level_up;
+pub mod xp_import;
+pub mod rank_mode;
+pub mod rewards;
@@ -0,0 +1,30 @@
+use std::fmt;
+
+// What happens to someone's old rank role when they reach a new one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RankMode {
+    // Only the highest rank role they've reached.
+    #[default]
+    Exclusive,
+    // Every rank role they've reached, so lower ones can keep unlocking things.
+    Stacking
+}
+
+impl RankMode {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.trim().to_lowercase().as_str() {
+            "exclusive" | "replace" => Ok(RankMode::Exclusive),
+            "stacking" | "stack" | "cumulative" => Ok(RankMode::Stacking),
+            _ => Err("This should be exclusive (only the highest rank role) or stacking (every rank role reached).".to_string())
+        }
+    }
+}
+
+impl fmt::Display for RankMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RankMode::Exclusive => write!(f, "exclusive"),
+            RankMode::Stacking => write!(f, "stacking")
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use std::fmt;
+use chrono::NaiveDateTime;
+use serenity::model::id::ChannelId;
+
+pub const MAX_BOOST_MULTIPLIER: f64 = 10.0;
+// A week.
+pub const MAX_BOOST_MINUTES: i32 = 7 * 24 * 60;
+
+// What reaching a level gives besides a rank role. Messages can use the same {names} as level-up messages.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RewardKind {
+    // Sent to whoever reached the level.
+    DirectMessage(String),
+    ChannelPost { channel: ChannelId, message: String },
+    // Multiplies all the experience they earn for a while.
+    XpBoost { multiplier: f64, minutes: i32 }
+}
+
+impl RewardKind {
+    // What goes in the kind column.
+    pub fn name(&self) -> &'static str {
+        match self {
+            RewardKind::DirectMessage(_) => "dm",
+            RewardKind::ChannelPost { .. } => "post",
+            RewardKind::XpBoost { .. } => "boost"
+        }
+    }
+
+    // None if the row is missing something its kind needs, which only happens if the database was edited by hand.
+    pub fn from_columns(kind: &str, channel: Option<u64>, message: Option<String>, multiplier: Option<f64>, minutes: Option<i32>) -> Option<Self> {
+        match kind {
+            "dm" => Some(RewardKind::DirectMessage(message?)),
+            "post" => Some(RewardKind::ChannelPost { channel: ChannelId(channel?), message: message? }),
+            "boost" => Some(RewardKind::XpBoost { multiplier: multiplier?, minutes: minutes? }),
+            _ => None
+        }
+    }
+}
+
+impl fmt::Display for RewardKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RewardKind::DirectMessage(message) => write!(f, "DM: {}", message),
+            RewardKind::ChannelPost { channel, message } => write!(f, "Post in <#{}>: {}", channel, message),
+            RewardKind::XpBoost { multiplier, minutes } => write!(f, "{}x experience for {} minutes", multiplier, minutes)
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LevelReward {
+    pub id: i32,
+    pub min_level: i32,
+    pub kind: RewardKind
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct XpBoost {
+    pub multiplier: f64,
+    // UTC.
+    pub expires_at: NaiveDateTime
+}
@@ -0,0 +1,237 @@
+use std::collections::HashMap;
+use std::fmt;
+use serde_json::Value;
+use serenity::model::id::UserId;
+use crate::models::db_models::Experience;
+use crate::models::xp_settings::XpSettings;
+
+// Nobody needs more than this, and it keeps a bad export from spinning through the curve forever.
+pub const MAX_IMPORT_LEVEL: i32 = 10_000;
+
+// What the leaderboard exports call things. MEE6 uses id/xp/level, Tatsu user_id/score, Arcane userId/xp/level.
+const ID_FIELDS: &[&str] = &["id", "user_id", "userid", "user", "discord_id", "member_id"];
+const XP_FIELDS: &[&str] = &["xp", "exp", "experience", "total_xp", "score", "points"];
+const LEVEL_FIELDS: &[&str] = &["level", "lvl"];
+// Where the list of members is when the export is an object instead of a list.
+const LIST_FIELDS: &[&str] = &["players", "users", "members", "levels", "leaderboard", "rankings", "data"];
+
+pub struct ImportRow {
+    pub user: UserId,
+    // In total, not towards the next level.
+    pub xp: i64,
+    pub level: Option<i32>
+}
+
+pub struct ImportFile {
+    pub rows: Vec<ImportRow>,
+    // Rows we couldn't make sense of, like ones without an ID.
+    pub skipped: usize
+}
+
+// Other bots use other curves, so their xp and their levels can't both be kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportMode {
+    // Same level they had before, at the start of it. Anyone without a level is placed by xp.
+    Level,
+    // Same total xp, wherever that lands on our curve.
+    Experience
+}
+
+impl ImportMode {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "level" | "levels" => Some(ImportMode::Level),
+            "xp" | "exp" | "experience" => Some(ImportMode::Experience),
+            _ => None
+        }
+    }
+}
+
+impl fmt::Display for ImportMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportMode::Level => write!(f, "keeping levels"),
+            ImportMode::Experience => write!(f, "keeping total xp")
+        }
+    }
+}
+
+fn normalize_key(key: &str) -> String {
+    key.trim().trim_matches('"').to_lowercase().replace(' ', "_")
+}
+
+// IDs are usually strings, since they don't fit in a JavaScript number, but xp and levels could be either.
+fn as_number(value: &Value) -> Option<i64> {
+    match value {
+        Value::Number(n) => n.as_i64().or_else(|| n.as_f64().map(|f| f as i64)),
+        Value::String(s) => s.trim().parse::<f64>().ok().map(|f| f as i64),
+        _ => None
+    }
+}
+
+fn as_user(value: &Value) -> Option<UserId> {
+    match value {
+        Value::String(s) => s.trim().parse::<u64>().ok().map(UserId),
+        Value::Number(n) => n.as_u64().map(UserId),
+        // Some exports nest the user, like "user": { "id": "..." }
+        Value::Object(obj) => obj.get("id").and_then(as_user),
+        _ => None
+    }
+}
+
+fn row(user: Option<UserId>, xp: Option<i64>, level: Option<i64>) -> Option<ImportRow> {
+    let user = user.filter(|u| u.0 > 0)?;
+    let level = level.map(|l| l.clamp(0, MAX_IMPORT_LEVEL as i64) as i32);
+    if xp.is_none() && level.is_none() {
+        return None;
+    }
+
+    Some(ImportRow { user, xp: xp.unwrap_or(0).max(0), level })
+}
+
+fn parse_json(content: &str) -> Result<ImportFile, String> {
+    let value: Value = serde_json::from_str(content).map_err(|ex| format!("That isn't valid JSON: {}", ex))?;
+    let list = match &value {
+        Value::Array(list) => list,
+        Value::Object(obj) => LIST_FIELDS.iter()
+            .find_map(|field| obj.get(*field).and_then(Value::as_array))
+            .ok_or_else(|| "I couldn't find the list of members in that JSON.".to_string())?,
+        _ => return Err("I couldn't find the list of members in that JSON.".to_string())
+    };
+
+    let mut rows = Vec::new();
+    let mut skipped = 0;
+    for entry in list {
+        let fields = match entry.as_object() {
+            Some(obj) => obj.iter().map(|(k, v)| (normalize_key(k), v)).collect::<HashMap<_, _>>(),
+            None => {
+                skipped += 1;
+                continue;
+            }
+        };
+        let find = |names: &[&str]| names.iter().find_map(|name| fields.get(*name).copied());
+
+        match row(find(ID_FIELDS).and_then(as_user), find(XP_FIELDS).and_then(as_number), find(LEVEL_FIELDS).and_then(as_number)) {
+            Some(row) => rows.push(row),
+            None => skipped += 1
+        }
+    }
+
+    Ok(ImportFile { rows, skipped })
+}
+
+// Splits CSV into rows of cells. Quoted cells can hold commas, line breaks and doubled quotes, which is how export writes them.
+fn csv_records(content: &str) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+
+    let mut chars = content.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            },
+            '"' => quoted = !quoted,
+            ',' if !quoted => record.push(std::mem::take(&mut field)),
+            '\n' if !quoted => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            },
+            '\r' if !quoted => {},
+            _ => field.push(c)
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+
+    records.into_iter()
+        .map(|record| record.iter().map(|cell| cell.trim().to_string()).collect::<Vec<_>>())
+        .filter(|record| record.iter().any(|cell| !cell.is_empty()))
+        .collect()
+}
+
+// Plain comma separated values. Without a header row the columns are taken to be ID, xp, then level.
+fn parse_csv(content: &str) -> Result<ImportFile, String> {
+    let mut lines = csv_records(content).into_iter().peekable();
+
+    let header = lines.peek().cloned().unwrap_or_default();
+    let has_header = header.first().map(|cell| cell.parse::<u64>().is_err()).unwrap_or(false);
+    let (id_col, xp_col, level_col) = if has_header {
+        lines.next();
+        let header = header.iter().map(|h| normalize_key(h)).collect::<Vec<_>>();
+        let column = |names: &[&str]| header.iter().position(|h| names.contains(&h.as_str()));
+        (
+            column(ID_FIELDS).ok_or_else(|| "I couldn't find a column with user IDs in that CSV.".to_string())?,
+            column(XP_FIELDS),
+            column(LEVEL_FIELDS)
+        )
+    } else {
+        (0, Some(1), Some(2))
+    };
+
+    let mut rows = Vec::new();
+    let mut skipped = 0;
+    for cells in lines {
+        let cell = |col: Option<usize>| col.and_then(|c| cells.get(c)).and_then(|v| v.parse::<f64>().ok()).map(|f| f as i64);
+        let user = cells.get(id_col).and_then(|id| id.parse::<u64>().ok()).map(UserId);
+
+        match row(user, cell(xp_col), cell(level_col)) {
+            Some(row) => rows.push(row),
+            None => skipped += 1
+        }
+    }
+
+    Ok(ImportFile { rows, skipped })
+}
+
+impl ImportFile {
+    // JSON if it looks like JSON, otherwise CSV. The file name isn't trusted since people rename these.
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let content = content.trim_start_matches('\u{feff}').trim();
+        let mut file = if content.starts_with('{') || content.starts_with('[') {
+            parse_json(content)?
+        } else {
+            parse_csv(content)?
+        };
+
+        // Someone showing up twice keeps their last row.
+        let mut seen = HashMap::new();
+        for (i, row) in file.rows.iter().enumerate() {
+            seen.insert(row.user, i);
+        }
+        if seen.len() < file.rows.len() {
+            file.skipped += file.rows.len() - seen.len();
+            file.rows = file.rows.into_iter().enumerate()
+                .filter(|(i, row)| seen[&row.user] == *i)
+                .map(|(_, row)| row)
+                .collect();
+        }
+
+        if file.rows.is_empty() {
+            return Err("There wasn't anyone in that file I could import.".to_string());
+        }
+
+        Ok(file)
+    }
+
+    // Where everyone ends up on this server's curve.
+    pub fn map(&self, settings: &XpSettings, mode: ImportMode) -> Vec<(UserId, Experience)> {
+        let max_level = settings.level_cap.unwrap_or(MAX_IMPORT_LEVEL).min(MAX_IMPORT_LEVEL);
+        self.rows.iter()
+            .map(|row| {
+                let (xp, level) = match (mode, row.level) {
+                    (ImportMode::Level, Some(level)) => (0, level.max(1)),
+                    _ => settings.split_total(row.xp, max_level)
+                };
+                // Past the cap there's nothing left to work towards.
+                let (xp, level) = if level >= max_level { (0, max_level) } else { (xp, level) };
+                (row.user, Experience { level, xp })
+            })
+            .collect()
+    }
+}
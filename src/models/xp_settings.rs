@@ -0,0 +1,250 @@
+use std::fmt;
+use crate::util::random_between;
+
+// Curves are checked this far up, which is as high as anyone can be put by hand or by an import.
+const MAX_CURVE_LEVEL: i32 = 10_000;
+const MIN_LEVEL_EXPERIENCE: f64 = 10.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CurveKind {
+    Linear,
+    Quadratic,
+    Exponential,
+    Polynomial
+}
+
+impl CurveKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            CurveKind::Linear => "linear",
+            CurveKind::Quadratic => "quadratic",
+            CurveKind::Exponential => "exponential",
+            CurveKind::Polynomial => "polynomial"
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        [CurveKind::Linear, CurveKind::Quadratic, CurveKind::Exponential, CurveKind::Polynomial].into_iter()
+            .find(|kind| kind.name().eq_ignore_ascii_case(name))
+    }
+
+    // Used when only the name is given; polynomial has to be spelled out.
+    fn default_coefficients(&self) -> &'static [f64] {
+        match self {
+            CurveKind::Linear => &[50.0, 100.0],
+            CurveKind::Quadratic => &[5.0, 50.0, 100.0],
+            CurveKind::Exponential => &[100.0, 1.2],
+            CurveKind::Polynomial => &[]
+        }
+    }
+}
+
+// How much experience it takes to get from one level to the next. Polynomial coefficients are written
+// highest power first, the same way you would write them out (so quadratic 5 50 100 is 5L² + 50L + 100).
+// Exponential is base * growth^L.
+#[derive(Debug, Clone, PartialEq)]
+pub struct XpCurve {
+    pub kind: CurveKind,
+    pub coefficients: Vec<f64>
+}
+
+impl Default for XpCurve {
+    fn default() -> Self {
+        XpCurve { kind: CurveKind::Quadratic, coefficients: CurveKind::Quadratic.default_coefficients().to_vec() }
+    }
+}
+
+impl XpCurve {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let mut words = value.split_whitespace();
+        let kind = words.next()
+            .and_then(CurveKind::from_name)
+            .ok_or_else(|| "The curve should start with linear, quadratic, exponential or polynomial.".to_string())?;
+
+        let mut coefficients = words
+            .map(|w| w.parse::<f64>().ok().filter(|c| c.is_finite()))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| "The coefficients should all be numbers.".to_string())?;
+
+        if coefficients.is_empty() {
+            coefficients = kind.default_coefficients().to_vec();
+        }
+
+        let expected = match kind {
+            CurveKind::Linear => 2..=2,
+            CurveKind::Quadratic => 3..=3,
+            CurveKind::Exponential => 2..=2,
+            // SQL Server has to work these out too, so cubic is as high as it goes.
+            CurveKind::Polynomial => 1..=4
+        };
+        if !expected.contains(&coefficients.len()) {
+            return Err(match kind {
+                CurveKind::Polynomial => "A polynomial needs between 1 and 4 coefficients (up to cubic).".to_string(),
+                _ => format!("A {} curve takes {} coefficients.", kind.name(), expected.start())
+            });
+        }
+
+        Ok(XpCurve { kind, coefficients })
+    }
+
+    // Walks every level, so this is only for when a curve gets saved, not every time one is read.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.kind == CurveKind::Exponential && (self.coefficients[0] <= 0.0 || self.coefficients[1] < 1.0) {
+            return Err("An exponential curve needs a positive base and a growth of at least 1.".to_string());
+        }
+
+        // Levelling up counts through one level at a time, so a curve that drops to almost nothing would take forever.
+        let mut previous = 0.0;
+        for level in 1..=MAX_CURVE_LEVEL {
+            let experience = self.raw_experience(level);
+            if experience < MIN_LEVEL_EXPERIENCE {
+                return Err(format!("Every level up to {} has to take at least {} experience.", MAX_CURVE_LEVEL, MIN_LEVEL_EXPERIENCE));
+            }
+            if experience < previous {
+                return Err(format!("Level {} would take less experience than level {}, and levels can't get easier.", level, level - 1));
+            }
+            previous = experience;
+        }
+
+        Ok(())
+    }
+
+    fn raw_experience(&self, level: i32) -> f64 {
+        let level = level as f64;
+        match self.kind {
+            CurveKind::Exponential => self.coefficients[0] * self.coefficients[1].powf(level),
+            _ => self.coefficients.iter().fold(0.0, |total, c| total * level + c)
+        }
+    }
+
+    // Experience needed to go from this level to the next. Never less than 1, so levelling up always ends.
+    pub fn experience_for_level(&self, level: i32) -> i32 {
+        self.raw_experience(level).round().clamp(1.0, i32::MAX as f64) as i32
+    }
+
+    // What [Ranking].[CurveExperience] takes: whether it's exponential, then the coefficients lowest power first.
+    pub fn sql_terms(&self) -> (bool, [f64; 4]) {
+        let mut terms = [0.0; 4];
+        match self.kind {
+            CurveKind::Exponential => {
+                terms[0] = self.coefficients[0];
+                terms[1] = self.coefficients[1];
+            },
+            _ => {
+                for (term, c) in terms.iter_mut().zip(self.coefficients.iter().rev()) {
+                    *term = *c;
+                }
+            }
+        }
+
+        (self.kind == CurveKind::Exponential, terms)
+    }
+
+    // Human readable, like 5L² + 50L + 100.
+    pub fn formula(&self) -> String {
+        if self.kind == CurveKind::Exponential {
+            return format!("{} × {}^L", self.coefficients[0], self.coefficients[1]);
+        }
+
+        let degree = self.coefficients.len() - 1;
+        let terms = self.coefficients.iter()
+            .enumerate()
+            .filter(|(_, c)| **c != 0.0)
+            .map(|(i, c)| match degree - i {
+                0 => format!("{}", c),
+                1 => format!("{}L", c),
+                2 => format!("{}L²", c),
+                _ => format!("{}L³", c)
+            })
+            .collect::<Vec<_>>();
+
+        if terms.is_empty() { "0".to_string() } else { terms.join(" + ").replace("+ -", "- ") }
+    }
+}
+
+impl fmt::Display for XpCurve {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.kind.name())?;
+        for c in &self.coefficients {
+            write!(f, " {}", c)?;
+        }
+        Ok(())
+    }
+}
+
+pub struct XpSettings {
+    pub min_xp: i32,
+    pub max_xp: i32,
+    pub curve: XpCurve,
+    pub level_cap: Option<i32>
+}
+
+impl Default for XpSettings {
+    fn default() -> Self {
+        XpSettings {
+            min_xp: 15,
+            max_xp: 25,
+            curve: XpCurve::default(),
+            level_cap: None
+        }
+    }
+}
+
+impl XpSettings {
+    // The minimum and maximum are set separately, so they could be the wrong way around.
+    pub fn roll_gain(&self) -> i32 {
+        random_between(self.min_xp.min(self.max_xp), self.min_xp.max(self.max_xp))
+    }
+
+    pub fn at_cap(&self, level: i32) -> bool {
+        self.level_cap.map(|cap| level >= cap).unwrap_or(false)
+    }
+
+    // Adds experience and carries it over into levels, stopping at the cap.
+    pub fn apply(&self, mut xp: i32, mut level: i32, gain: i32) -> (i32, i32) {
+        xp = xp.saturating_add(gain);
+        while !self.at_cap(level) && xp >= self.curve.experience_for_level(level) {
+            xp -= self.curve.experience_for_level(level);
+            level += 1;
+        }
+
+        (xp, level)
+    }
+
+    // Everything it took to get here from level 1, plus what they have towards the next level.
+    pub fn total_experience(&self, level: i32, xp: i32) -> i64 {
+        (1..level).map(|l| self.curve.experience_for_level(l) as i64).sum::<i64>() + xp as i64
+    }
+
+    // The other way around: where someone with this much experience in total would be, going no higher than max_level.
+    // That bound is checked as it goes, since a huge total on a slow curve would otherwise take forever to count up.
+    pub fn split_total(&self, total: i64, max_level: i32) -> (i32, i32) {
+        let (mut xp, mut level) = (total.max(0), 1);
+        while level < max_level && !self.at_cap(level) && xp >= self.curve.experience_for_level(level) as i64 {
+            xp -= self.curve.experience_for_level(level) as i64;
+            level += 1;
+        }
+
+        (xp.min(i32::MAX as i64) as i32, level)
+    }
+}
+
+// Thresholds for the message quality checks. Zero turns the length and similarity checks off, and 100 turns off the emoji one.
+pub struct QualitySettings {
+    // Characters, not counting the spaces around the message.
+    pub min_length: usize,
+    // Percent; a message at least this similar to one of the author's last few doesn't count.
+    pub max_similarity: u32,
+    // Percent of the message that can be emoji or mentions.
+    pub max_emoji_ratio: u32
+}
+
+impl Default for QualitySettings {
+    fn default() -> Self {
+        QualitySettings {
+            min_length: 3,
+            max_similarity: 90,
+            max_emoji_ratio: 60
+        }
+    }
+}
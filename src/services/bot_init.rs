@@ -1,25 +1,74 @@
 use serenity::{
+    builder::CreateApplicationCommand,
     client::Context,
     model::{
         gateway::Ready,
+        id::GuildId,
         interactions::application_command::ApplicationCommand
     }
 };
+use log::{error, info};
+use crate::services::cow_framework::CowFramework;
 
+fn command_name(command: &CreateApplicationCommand) -> &str {
+    command.0.get("name").and_then(|name| name.as_str()).unwrap_or_default()
+}
 
-use log::{error, info};
+// Anything registered that isn't defined anymore gets dropped by the bulk overwrite, but it's nice to know what went.
+fn log_stale_commands(registered: &[ApplicationCommand], commands: &[CreateApplicationCommand], location: &str) {
+    for stale in registered.iter().filter(|r| !commands.iter().any(|c| command_name(c) == r.name)) {
+        info!("Pruning stale slash command {} from {}.", stale.name, location);
+    }
+}
 
-async fn register_slash_commands(ctx: &Context, _: &Ready) {
-    if let Err(ex) = ApplicationCommand::create_global_application_command(&ctx.http, |cmd| {
-        cmd.name("info").description("Get information about this bot.")
-    }).await {
-        error!("Cannot create slash command: {}", ex)
+async fn register_guild_commands(ctx: &Context, guild_id: GuildId, commands: &[CreateApplicationCommand]) {
+    match guild_id.get_application_commands(&ctx.http).await {
+        Ok(registered) => log_stale_commands(&registered, commands, &format!("guild {}", guild_id)),
+        Err(ex) => error!("Failed to get slash commands for guild {}: {}", guild_id, ex)
+    }
+
+    if let Err(ex) = guild_id.set_application_commands(&ctx.http, |c| c.set_application_commands(commands.to_vec())).await {
+        error!("Cannot create slash commands for guild {}: {}", guild_id, ex);
     } else {
-        info!("Finished creating slash commands.")
+        info!("Finished creating slash commands for guild {}.", guild_id);
     }
 }
 
-pub async fn ready(ctx: &Context, ready: &Ready) {
+async fn register_global_commands(ctx: &Context, commands: &[CreateApplicationCommand]) {
+    match ApplicationCommand::get_global_application_commands(&ctx.http).await {
+        Ok(registered) => {
+            // Nothing to do if we're clearing them out and there's nothing there.
+            if commands.is_empty() && registered.is_empty() {
+                return;
+            }
+            log_stale_commands(&registered, commands, "global commands");
+        },
+        Err(ex) => error!("Failed to get global slash commands: {}", ex)
+    }
+
+    if let Err(ex) = ApplicationCommand::set_global_application_commands(&ctx.http, |c| c.set_application_commands(commands.to_vec())).await {
+        error!("Cannot create global slash commands: {}", ex);
+    } else {
+        info!("Finished creating global slash commands.");
+    }
+}
+
+// Guild commands update instantly, while global ones can take up to an hour to show up everywhere.
+async fn register_slash_commands(ctx: &Context, _: &Ready, framework: &CowFramework, dev_guilds: &[GuildId]) {
+    let commands = framework.application_commands();
+
+    if dev_guilds.is_empty() {
+        register_global_commands(ctx, &commands).await;
+    } else {
+        for guild_id in dev_guilds {
+            register_guild_commands(ctx, *guild_id, &commands).await;
+        }
+        // Otherwise every command would show up twice in the dev guilds.
+        register_global_commands(ctx, &[]).await;
+    }
+}
+
+pub async fn ready(ctx: &Context, ready: &Ready, framework: &CowFramework, dev_guilds: &[GuildId]) {
     info!("Logged in as {}", ready.user.name);
-    register_slash_commands(ctx, ready).await;
-}
\ No newline at end of file
+    register_slash_commands(ctx, ready, framework, dev_guilds).await;
+}
@@ -0,0 +1,281 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::{Arc, RwLock};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use chrono::Utc;
+use serenity::{
+    client::Context,
+    model::id::{ChannelId, GuildId, UserId},
+    prelude::TypeMapKey
+};
+use crate::commands::cowboard::cowboard_db_models::Cowboard;
+use crate::models::db_models::{MultiplierTarget, XpMultiplier};
+use crate::models::error::CowError;
+use crate::models::rewards::{LevelReward, RewardKind, XpBoost};
+use crate::services::database::Storage;
+
+// Expired entries are only swept out once a cache gets this big, since they're ignored on read anyway.
+const PRUNE_THRESHOLD: usize = 10_000;
+
+pub struct CacheStats {
+    pub name: &'static str,
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: usize
+}
+
+impl CacheStats {
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 { 0.0 } else { self.hits as f64 / total as f64 * 100.0 }
+    }
+}
+
+pub struct TtlCache<K, V> {
+    name: &'static str,
+    ttl: Duration,
+    entries: RwLock<HashMap<K, (Instant, V)>>,
+    hits: AtomicU64,
+    misses: AtomicU64
+}
+
+impl<K: Eq + Hash, V: Clone> TtlCache<K, V> {
+    pub fn new(name: &'static str, ttl: Duration) -> Self {
+        TtlCache {
+            name,
+            ttl,
+            entries: RwLock::new(HashMap::new()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0)
+        }
+    }
+
+    pub fn get(&self, key: &K) -> Option<V> {
+        let entries = self.entries.read().unwrap_or_else(|poisoned| poisoned.into_inner());
+        match entries.get(key) {
+            Some((inserted, value)) if inserted.elapsed() < self.ttl => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                Some(value.clone())
+            },
+            _ => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    pub fn insert(&self, key: K, value: V) {
+        let mut entries = self.entries.write().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if entries.len() >= PRUNE_THRESHOLD {
+            let ttl = self.ttl;
+            entries.retain(|_, (inserted, _)| inserted.elapsed() < ttl);
+        }
+        entries.insert(key, (Instant::now(), value));
+    }
+
+    // Changes an entry in place if it's still fresh, without resetting how long it has left.
+    pub fn update(&self, key: &K, f: impl FnOnce(&mut V)) {
+        let mut entries = self.entries.write().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some((inserted, value)) = entries.get_mut(key) {
+            if inserted.elapsed() < self.ttl {
+                f(value);
+            }
+        }
+    }
+
+    pub fn invalidate(&self, key: &K) {
+        self.entries.write().unwrap_or_else(|poisoned| poisoned.into_inner()).remove(key);
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            name: self.name,
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: self.entries.read().unwrap_or_else(|poisoned| poisoned.into_inner()).len()
+        }
+    }
+}
+
+// Sits in front of the reads that happen on every message or reaction. Writes to these go through here too, so the cache never serves something we know is stale.
+pub struct DbCache {
+    db: Arc<dyn Storage>,
+    cowboards: TtlCache<GuildId, Cowboard>,
+    disabled_channels: TtlCache<(GuildId, ChannelId), bool>,
+    timeouts: TtlCache<GuildId, i32>,
+    multipliers: TtlCache<GuildId, Vec<XpMultiplier>>,
+    rewards: TtlCache<GuildId, Vec<LevelReward>>,
+    // None is cached too, since most people don't have a boost.
+    boosts: TtlCache<(GuildId, UserId), Option<XpBoost>>,
+    // When we last handed out experience, so we can skip the database while someone is on cooldown.
+    last_exp: TtlCache<(GuildId, UserId), Instant>
+}
+
+impl TypeMapKey for DbCache {
+    type Value = Arc<DbCache>;
+}
+
+impl DbCache {
+    pub fn new(db: Arc<dyn Storage>) -> Self {
+        DbCache {
+            db,
+            cowboards: TtlCache::new("Cowboard settings", Duration::from_secs(5 * 60)),
+            disabled_channels: TtlCache::new("Disabled channels", Duration::from_secs(5 * 60)),
+            timeouts: TtlCache::new("XP cooldowns", Duration::from_secs(5 * 60)),
+            multipliers: TtlCache::new("XP multipliers", Duration::from_secs(5 * 60)),
+            rewards: TtlCache::new("Level rewards", Duration::from_secs(5 * 60)),
+            boosts: TtlCache::new("XP boosts", Duration::from_secs(5 * 60)),
+            last_exp: TtlCache::new("Recent XP", Duration::from_secs(24 * 60 * 60))
+        }
+    }
+
+    pub async fn from_context(ctx: &Context) -> Arc<DbCache> {
+        let data = ctx.data.read().await;
+        data.get::<DbCache>().expect("Expected DbCache in TypeMap.").clone()
+    }
+
+    pub fn stats(&self) -> Vec<CacheStats> {
+        vec![self.cowboards.stats(), self.disabled_channels.stats(), self.timeouts.stats(), self.multipliers.stats(), self.rewards.stats(), self.boosts.stats(), self.last_exp.stats()]
+    }
+
+    pub async fn get_cowboard_config(&self, server_id: GuildId) -> Result<Cowboard, CowError> {
+        if let Some(config) = self.cowboards.get(&server_id) {
+            return Ok(config);
+        }
+
+        let config = self.db.get_cowboard_config(server_id).await?;
+        self.cowboards.insert(server_id, config.clone());
+        Ok(config)
+    }
+
+    pub async fn update_cowboard(&self, config: &Cowboard) -> Result<(), CowError> {
+        let result = self.db.update_cowboard(config).await;
+        // Even a failed write might have gone through, so just drop it either way.
+        self.cowboards.invalidate(&GuildId(config.id));
+        result
+    }
+
+    pub async fn channel_disabled(&self, server_id: GuildId, channel_id: ChannelId) -> Result<bool, CowError> {
+        if let Some(disabled) = self.disabled_channels.get(&(server_id, channel_id)) {
+            return Ok(disabled);
+        }
+
+        let disabled = self.db.channel_disabled(server_id, channel_id).await?;
+        self.disabled_channels.insert((server_id, channel_id), disabled);
+        Ok(disabled)
+    }
+
+    // True: disabled False: enabled
+    pub async fn toggle_channel_xp(&self, server_id: GuildId, channel_id: ChannelId) -> Result<bool, CowError> {
+        match self.db.toggle_channel_xp(server_id, channel_id).await {
+            Ok(disabled) => {
+                self.disabled_channels.insert((server_id, channel_id), disabled);
+                Ok(disabled)
+            },
+            Err(ex) => {
+                self.disabled_channels.invalidate(&(server_id, channel_id));
+                Err(ex)
+            }
+        }
+    }
+
+    pub async fn get_timeout(&self, server_id: GuildId) -> Result<i32, CowError> {
+        if let Some(timeout) = self.timeouts.get(&server_id) {
+            return Ok(timeout);
+        }
+
+        let timeout = self.db.get_timeout(server_id).await?;
+        self.timeouts.insert(server_id, timeout);
+        Ok(timeout)
+    }
+
+    pub async fn set_timeout(&self, server_id: GuildId, timeout: i32) -> Result<bool, CowError> {
+        let result = self.db.set_timeout(server_id, timeout).await;
+        self.timeouts.invalidate(&server_id);
+        result
+    }
+
+    pub async fn get_multipliers(&self, server_id: GuildId) -> Result<Vec<XpMultiplier>, CowError> {
+        if let Some(multipliers) = self.multipliers.get(&server_id) {
+            return Ok(multipliers);
+        }
+
+        let multipliers = self.db.get_multipliers(server_id).await?;
+        self.multipliers.insert(server_id, multipliers.clone());
+        Ok(multipliers)
+    }
+
+    pub async fn set_multiplier(&self, server_id: GuildId, multiplier: &XpMultiplier) -> Result<(), CowError> {
+        let result = self.db.set_multiplier(server_id, multiplier).await;
+        self.multipliers.invalidate(&server_id);
+        result
+    }
+
+    pub async fn remove_multiplier(&self, server_id: GuildId, target: MultiplierTarget) -> Result<bool, CowError> {
+        let result = self.db.remove_multiplier(server_id, target).await;
+        self.multipliers.invalidate(&server_id);
+        result
+    }
+
+    pub async fn get_rewards(&self, server_id: GuildId) -> Result<Vec<LevelReward>, CowError> {
+        if let Some(rewards) = self.rewards.get(&server_id) {
+            return Ok(rewards);
+        }
+
+        let rewards = self.db.get_rewards(server_id).await?;
+        self.rewards.insert(server_id, rewards.clone());
+        Ok(rewards)
+    }
+
+    pub async fn add_reward(&self, server_id: GuildId, min_level: i32, kind: &RewardKind) -> Result<i32, CowError> {
+        let result = self.db.add_reward(server_id, min_level, kind).await;
+        self.rewards.invalidate(&server_id);
+        result
+    }
+
+    pub async fn remove_reward(&self, server_id: GuildId, id: i32) -> Result<bool, CowError> {
+        let result = self.db.remove_reward(server_id, id).await;
+        self.rewards.invalidate(&server_id);
+        result
+    }
+
+    // 1.0 for anyone without a boost. A cached boost can run out before the entry does, so check the time here too.
+    pub async fn get_boost_multiplier(&self, server_id: GuildId, user_id: UserId) -> Result<f64, CowError> {
+        let boost = match self.boosts.get(&(server_id, user_id)) {
+            Some(boost) => boost,
+            None => {
+                let boost = self.db.get_boost(server_id, user_id).await?;
+                self.boosts.insert((server_id, user_id), boost);
+                boost
+            }
+        };
+
+        Ok(boost
+            .filter(|b| b.expires_at > Utc::now().naive_utc())
+            .map(|b| b.multiplier)
+            .unwrap_or(1.0))
+    }
+
+    pub async fn set_boost(&self, server_id: GuildId, user_id: UserId, boost: &XpBoost) -> Result<(), CowError> {
+        let result = self.db.set_boost(server_id, user_id, boost).await;
+        self.boosts.invalidate(&(server_id, user_id));
+        result
+    }
+
+    // Only says yes if we gave them experience ourselves within the cooldown. Anything we don't know about
+    // (like right after a restart) goes to the database, which has the final say anyway.
+    pub async fn on_cooldown(&self, server_id: GuildId, user_id: UserId) -> Result<bool, CowError> {
+        match self.last_exp.get(&(server_id, user_id)) {
+            Some(last) => {
+                let timeout = self.get_timeout(server_id).await?;
+                Ok(last.elapsed() < Duration::from_millis(timeout.max(0) as u64))
+            },
+            None => Ok(false)
+        }
+    }
+
+    pub fn exp_provided(&self, server_id: GuildId, user_id: UserId) {
+        self.last_exp.insert((server_id, user_id), Instant::now());
+    }
+}
@@ -0,0 +1,266 @@
+use serenity::{
+    builder::{CreateApplicationCommand, CreateApplicationCommandOption},
+    client::Context,
+    framework::standard::CommandResult,
+    futures::future::BoxFuture,
+    model::{
+        interactions::application_command::ApplicationCommandOptionType,
+        permissions::Permissions
+    }
+};
+use crate::services::cow_framework::Invocation;
+
+pub type CommandFn = for<'fut> fn(&'fut Context, &'fut Invocation) -> BoxFuture<'fut, CommandResult>;
+// Err is the reason shown to the user.
+pub type CheckFn = for<'fut> fn(&'fut Context, &'fut Invocation) -> BoxFuture<'fut, Result<(), String>>;
+
+// Runs after the built-in checks, for anything a command needs that isn't a permission.
+pub struct Check {
+    pub name: &'static str,
+    pub function: CheckFn
+}
+
+// Discord caps slash descriptions at 100 characters, so long ones get cut down to their first sentence.
+fn slash_description(description: &str) -> String {
+    if description.chars().count() <= 100 {
+        return description.to_string();
+    }
+
+    match description.find(". ") {
+        Some(end) if end < 100 => description[..=end].to_string(),
+        _ => description.chars().take(97).collect::<String>() + "..."
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OptionKind {
+    String,
+    Integer,
+    Number,
+    Boolean,
+    User,
+    Channel,
+    Role
+}
+
+impl OptionKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            OptionKind::String => "text",
+            OptionKind::Integer => "whole number",
+            OptionKind::Number => "number",
+            OptionKind::Boolean => "true/false",
+            OptionKind::User => "user",
+            OptionKind::Channel => "channel",
+            OptionKind::Role => "role"
+        }
+    }
+
+    fn slash_kind(&self) -> ApplicationCommandOptionType {
+        match self {
+            OptionKind::String => ApplicationCommandOptionType::String,
+            OptionKind::Integer => ApplicationCommandOptionType::Integer,
+            OptionKind::Number => ApplicationCommandOptionType::Number,
+            OptionKind::Boolean => ApplicationCommandOptionType::Boolean,
+            OptionKind::User => ApplicationCommandOptionType::User,
+            OptionKind::Channel => ApplicationCommandOptionType::Channel,
+            OptionKind::Role => ApplicationCommandOptionType::Role
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum ChoiceValue {
+    String(&'static str),
+    Integer(i32)
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct OptionChoice {
+    pub name: &'static str,
+    pub value: ChoiceValue
+}
+
+impl OptionChoice {
+    pub const fn string(name: &'static str, value: &'static str) -> Self {
+        OptionChoice { name, value: ChoiceValue::String(value) }
+    }
+
+    pub const fn integer(name: &'static str, value: i32) -> Self {
+        OptionChoice { name, value: ChoiceValue::Integer(value) }
+    }
+}
+
+#[derive(Debug)]
+pub struct CommandOption {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub kind: OptionKind,
+    pub required: bool,
+    pub choices: &'static [OptionChoice]
+}
+
+impl CommandOption {
+    pub const fn new(name: &'static str, description: &'static str, kind: OptionKind) -> Self {
+        CommandOption { name, description, kind, required: false, choices: &[] }
+    }
+
+    pub const fn required(mut self) -> Self {
+        self.required = true;
+        self
+    }
+
+    pub const fn choices(mut self, choices: &'static [OptionChoice]) -> Self {
+        self.choices = choices;
+        self
+    }
+
+    fn create(&self, option: &mut CreateApplicationCommandOption) {
+        option
+            .name(self.name)
+            .description(slash_description(self.description))
+            .kind(self.kind.slash_kind())
+            .required(self.required);
+
+        for choice in self.choices {
+            match choice.value {
+                ChoiceValue::String(value) => option.add_string_choice(choice.name, value),
+                ChoiceValue::Integer(value) => option.add_int_choice(choice.name, value)
+            };
+        }
+    }
+}
+
+// One definition for both the text command and the slash command.
+// In text form, a string option at the very end takes the rest of the message.
+pub struct CowCommand {
+    pub name: &'static str,
+    pub aliases: &'static [&'static str],
+    pub description: &'static str,
+    pub usage: Option<&'static str>,
+    pub options: &'static [CommandOption],
+    pub permissions: Permissions,
+    pub only_in_guilds: bool,
+    pub owners_only: bool,
+    pub bucket: Option<&'static str>,
+    pub checks: &'static [&'static Check],
+    pub fun: CommandFn
+}
+
+impl CowCommand {
+    pub const fn new(name: &'static str, description: &'static str, fun: CommandFn) -> Self {
+        CowCommand {
+            name,
+            aliases: &[],
+            description,
+            usage: None,
+            options: &[],
+            permissions: Permissions::empty(),
+            only_in_guilds: false,
+            owners_only: false,
+            bucket: None,
+            checks: &[],
+            fun
+        }
+    }
+
+    pub fn matches(&self, name: &str) -> bool {
+        self.name == name || self.aliases.contains(&name)
+    }
+
+    // Generated from the options if there isn't a hand-written one.
+    pub fn usage(&self) -> String {
+        if let Some(usage) = self.usage {
+            return usage.to_string();
+        }
+
+        self.options.iter()
+            .map(|o| if o.required { format!("<{}>", o.name) } else { format!("[{}]", o.name) })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    pub fn create_application_command(&self, command: &mut CreateApplicationCommand) {
+        command.name(self.name).description(slash_description(self.description));
+
+        for option in self.options {
+            command.create_option(|o| {
+                option.create(o);
+                o
+            });
+        }
+    }
+
+    pub fn create_subcommand(&self, subcommand: &mut CreateApplicationCommandOption) {
+        subcommand
+            .name(self.name)
+            .description(slash_description(self.description))
+            .kind(ApplicationCommandOptionType::SubCommand);
+
+        for option in self.options {
+            subcommand.create_sub_option(|o| {
+                option.create(o);
+                o
+            });
+        }
+    }
+}
+
+pub struct CowGroup {
+    pub name: &'static str,
+    // Groups without prefixes have their commands invoked directly.
+    pub prefixes: &'static [&'static str],
+    pub description: &'static str,
+    pub summary: &'static str,
+    pub default_command: Option<&'static CowCommand>,
+    pub commands: &'static [&'static CowCommand],
+    pub sub_groups: &'static [&'static CowGroup]
+}
+
+impl CowGroup {
+    pub fn matches(&self, prefix: &str) -> bool {
+        self.prefixes.contains(&prefix)
+    }
+
+    pub fn command(&self, name: &str) -> Option<&'static CowCommand> {
+        self.commands.iter().find(|c| c.matches(name)).copied()
+    }
+
+    pub fn sub_group(&self, prefix: &str) -> Option<&'static CowGroup> {
+        self.sub_groups.iter().find(|g| g.matches(prefix)).copied()
+    }
+
+    // A prefixed group becomes one slash command named after its first prefix, with its commands as subcommands.
+    pub fn create_application_command(&self, command: &mut CreateApplicationCommand) {
+        command.name(self.prefixes[0]).description(slash_description(self.description));
+
+        for sub_group in self.sub_groups {
+            command.create_option(|o| {
+                sub_group.create_subcommand_group(o);
+                o
+            });
+        }
+
+        for subcommand in self.commands {
+            command.create_option(|o| {
+                subcommand.create_subcommand(o);
+                o
+            });
+        }
+    }
+
+    // Discord only allows one level of nesting, so sub-groups of sub-groups can't be slash commands.
+    fn create_subcommand_group(&self, option: &mut CreateApplicationCommandOption) {
+        option
+            .name(self.prefixes[0])
+            .description(slash_description(self.description))
+            .kind(ApplicationCommandOptionType::SubCommandGroup);
+
+        for subcommand in self.commands {
+            option.create_sub_option(|o| {
+                subcommand.create_subcommand(o);
+                o
+            });
+        }
+    }
+}
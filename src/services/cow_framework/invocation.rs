@@ -0,0 +1,375 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt::Display;
+use std::time::Duration;
+use serenity::{
+    builder::{CreateComponents, CreateEmbed},
+    client::Context,
+    http::{AttachmentType, Http},
+    model::{
+        channel::Message,
+        guild::Guild,
+        id::{ChannelId, GuildId, RoleId, UserId},
+        interactions::{
+            application_command::ApplicationCommandInteraction,
+            message_component::ButtonStyle,
+            InteractionResponseType
+        },
+        permissions::Permissions,
+        user::User
+    }
+};
+use serde_json::Map as JsonMap;
+use tokio::sync::Mutex;
+use crate::services::cow_framework::CowCommand;
+
+#[derive(Debug, Clone)]
+pub enum ArgValue {
+    String(String),
+    Integer(i64),
+    Number(f64),
+    Boolean(bool),
+    User(UserId),
+    Channel(ChannelId),
+    Role(RoleId)
+}
+
+pub trait FromArg: Sized {
+    fn from_arg(value: &ArgValue) -> Option<Self>;
+}
+
+impl FromArg for String {
+    fn from_arg(value: &ArgValue) -> Option<Self> {
+        if let ArgValue::String(s) = value { Some(s.clone()) } else { None }
+    }
+}
+
+impl FromArg for i64 {
+    fn from_arg(value: &ArgValue) -> Option<Self> {
+        if let ArgValue::Integer(i) = value { Some(*i) } else { None }
+    }
+}
+
+impl FromArg for i32 {
+    fn from_arg(value: &ArgValue) -> Option<Self> {
+        if let ArgValue::Integer(i) = value { i32::try_from(*i).ok() } else { None }
+    }
+}
+
+impl FromArg for f64 {
+    fn from_arg(value: &ArgValue) -> Option<Self> {
+        match value {
+            ArgValue::Number(n) => Some(*n),
+            ArgValue::Integer(i) => Some(*i as f64),
+            _ => None
+        }
+    }
+}
+
+impl FromArg for bool {
+    fn from_arg(value: &ArgValue) -> Option<Self> {
+        if let ArgValue::Boolean(b) = value { Some(*b) } else { None }
+    }
+}
+
+impl FromArg for UserId {
+    fn from_arg(value: &ArgValue) -> Option<Self> {
+        if let ArgValue::User(u) = value { Some(*u) } else { None }
+    }
+}
+
+impl FromArg for ChannelId {
+    fn from_arg(value: &ArgValue) -> Option<Self> {
+        if let ArgValue::Channel(c) = value { Some(*c) } else { None }
+    }
+}
+
+impl FromArg for RoleId {
+    fn from_arg(value: &ArgValue) -> Option<Self> {
+        if let ArgValue::Role(r) = value { Some(*r) } else { None }
+    }
+}
+
+pub enum InvocationSource {
+    Message(Box<Message>),
+    Interaction(Box<ApplicationCommandInteraction>)
+}
+
+// Interactions get one response, which can be deferred; everything after that is a follow-up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ResponseState {
+    Pending,
+    Deferred,
+    Responded
+}
+
+// Works like CreateMessage, but can be sent as a message or as an interaction response.
+#[derive(Default)]
+pub struct CreateReply {
+    content: Option<String>,
+    embeds: Vec<CreateEmbed>,
+    components: Option<CreateComponents>
+}
+
+impl CreateReply {
+    pub fn content<D: ToString>(&mut self, content: D) -> &mut Self {
+        self.content = Some(content.to_string());
+        self
+    }
+
+    pub fn embed<F>(&mut self, f: F) -> &mut Self
+        where F: FnOnce(&mut CreateEmbed) -> &mut CreateEmbed {
+        let mut embed = CreateEmbed::default();
+        f(&mut embed);
+        self.embeds.push(embed);
+        self
+    }
+
+    pub fn add_embed(&mut self, embed: CreateEmbed) -> &mut Self {
+        self.embeds.push(embed);
+        self
+    }
+
+    pub fn components<F>(&mut self, f: F) -> &mut Self
+        where F: FnOnce(&mut CreateComponents) -> &mut CreateComponents {
+        let mut components = CreateComponents::default();
+        f(&mut components);
+        self.components = Some(components);
+        self
+    }
+}
+
+// A command being run, either from a text message or a slash command.
+pub struct Invocation {
+    pub source: InvocationSource,
+    pub command: &'static CowCommand,
+    pub(super) args: HashMap<&'static str, ArgValue>,
+    state: Mutex<ResponseState>
+}
+
+impl Invocation {
+    pub(super) fn new(source: InvocationSource, command: &'static CowCommand) -> Self {
+        Invocation {
+            source,
+            command,
+            args: HashMap::new(),
+            state: Mutex::new(ResponseState::Pending)
+        }
+    }
+
+    pub fn author(&self) -> &User {
+        match &self.source {
+            InvocationSource::Message(msg) => &msg.author,
+            InvocationSource::Interaction(command) => &command.user
+        }
+    }
+
+    pub fn guild_id(&self) -> Option<GuildId> {
+        match &self.source {
+            InvocationSource::Message(msg) => msg.guild_id,
+            InvocationSource::Interaction(command) => command.guild_id
+        }
+    }
+
+    pub fn channel_id(&self) -> ChannelId {
+        match &self.source {
+            InvocationSource::Message(msg) => msg.channel_id,
+            InvocationSource::Interaction(command) => command.channel_id
+        }
+    }
+
+    // Only text commands have a message to look at.
+    pub fn message(&self) -> Option<&Message> {
+        if let InvocationSource::Message(msg) = &self.source { Some(msg.as_ref()) } else { None }
+    }
+
+    pub async fn guild(&self, ctx: &Context) -> Option<Guild> {
+        ctx.cache.guild(self.guild_id()?).await
+    }
+
+    pub fn arg<T: FromArg>(&self, name: &str) -> Option<T> {
+        self.args.get(name).and_then(T::from_arg)
+    }
+
+    // None outside of servers, or if we couldn't figure it out.
+    pub async fn permissions(&self, ctx: &Context) -> Option<Permissions> {
+        match &self.source {
+            InvocationSource::Message(msg) => {
+                let guild = msg.guild(&ctx.cache).await?;
+                let channel = guild.channels.get(&msg.channel_id)?;
+                let member = guild.members.get(&msg.author.id)?;
+                guild.user_permissions_in(channel, member).ok()
+            },
+            InvocationSource::Interaction(command) => command.member.as_ref().and_then(|m| m.permissions)
+        }
+    }
+
+    pub async fn say(&self, http: impl AsRef<Http>, content: impl Display) -> serenity::Result<Message> {
+        self.send_message(http, |m| m.content(content)).await
+    }
+
+    // Mentions the author for text commands; slash command responses already show who used them.
+    pub async fn reply(&self, http: impl AsRef<Http>, content: impl Display) -> serenity::Result<Message> {
+        match &self.source {
+            InvocationSource::Message(msg) => msg.channel_id.send_message(http, |m| m.reference_message(msg.as_ref()).content(content)).await,
+            InvocationSource::Interaction(_) => self.say(http, content).await
+        }
+    }
+
+    pub async fn send_message<F>(&self, http: impl AsRef<Http>, f: F) -> serenity::Result<Message>
+        where F: FnOnce(&mut CreateReply) -> &mut CreateReply {
+        let mut reply = CreateReply::default();
+        f(&mut reply);
+        let CreateReply { content, embeds, components } = reply;
+        let http = http.as_ref();
+
+        match &self.source {
+            InvocationSource::Message(msg) => {
+                msg.channel_id.send_message(http, |m| {
+                    if let Some(content) = content {
+                        m.content(content);
+                    }
+                    if let Some(components) = components {
+                        m.set_components(components);
+                    }
+                    m.set_embeds(embeds)
+                }).await
+            },
+            InvocationSource::Interaction(command) => {
+                let mut state = self.state.lock().await;
+                let message = match *state {
+                    ResponseState::Pending => {
+                        command.create_interaction_response(http, |r| r
+                            .kind(InteractionResponseType::ChannelMessageWithSource)
+                            .interaction_response_data(|d| {
+                                if let Some(content) = content {
+                                    d.content(content);
+                                }
+                                if let Some(components) = components {
+                                    d.set_components(components);
+                                }
+                                d.embeds(embeds)
+                            })
+                        ).await?;
+                        command.get_interaction_response(http).await?
+                    },
+                    ResponseState::Deferred => {
+                        command.edit_original_interaction_response(http, |r| {
+                            if let Some(content) = content {
+                                r.content(content);
+                            }
+                            if let Some(components) = components {
+                                r.components(|c| {
+                                    *c = components;
+                                    c
+                                });
+                            }
+                            r.set_embeds(embeds)
+                        }).await?
+                    },
+                    ResponseState::Responded => {
+                        command.create_followup_message(http, |f| {
+                            if let Some(content) = content {
+                                f.content(content);
+                            }
+                            if let Some(components) = components {
+                                f.set_components(components);
+                            }
+                            f.embeds(embeds)
+                        }).await?
+                    }
+                };
+
+                *state = ResponseState::Responded;
+                Ok(message)
+            }
+        }
+    }
+
+    // Serenity drops files from interaction responses, so slash commands get the text as usual and the
+    // file as a follow-up sent through the interaction's webhook, which is all a follow-up really is.
+    // Without any text the response is just deferred, and cleaned up once the command is done.
+    pub async fn send_file(&self, http: impl AsRef<Http>, content: impl Display, file: AttachmentType<'_>) -> serenity::Result<Message> {
+        let http = http.as_ref();
+        let content = content.to_string();
+        match &self.source {
+            InvocationSource::Message(msg) => {
+                msg.channel_id.send_files(http, vec![file], |m| m.reference_message(msg.as_ref()).content(content)).await
+            },
+            InvocationSource::Interaction(command) => {
+                if content.is_empty() {
+                    self.defer(http).await?;
+                } else {
+                    self.say(http, &content).await?;
+                }
+
+                http.execute_webhook_with_files(command.application_id.0, &command.token, true, vec![file], JsonMap::new()).await?
+                    .ok_or(serenity::Error::Other("Discord didn't send back the follow-up message"))
+            }
+        }
+    }
+
+    // Asks the author to press a button before going ahead with something that can't be undone.
+    // False if they cancelled or didn't answer in time; either way the buttons are taken away.
+    pub async fn confirm(&self, ctx: &Context, prompt: impl Display) -> serenity::Result<bool> {
+        let prompt = prompt.to_string();
+        let mut message = self.send_message(&ctx.http, |m| m
+            .content(&prompt)
+            .components(|c| c.create_action_row(|r| r
+                .create_button(|b| b.custom_id("confirm").label("Confirm").style(ButtonStyle::Danger))
+                .create_button(|b| b.custom_id("cancel").label("Cancel").style(ButtonStyle::Secondary))
+            ))
+        ).await?;
+
+        let answer = message.await_component_interaction(&ctx)
+            .author_id(self.author().id)
+            .timeout(Duration::from_secs(30))
+            .await;
+
+        match answer {
+            Some(interaction) => {
+                let confirmed = interaction.data.custom_id == "confirm";
+                let outcome = if confirmed { "Confirmed." } else { "Cancelled." };
+                interaction.create_interaction_response(&ctx.http, |r| r
+                    .kind(InteractionResponseType::UpdateMessage)
+                    .interaction_response_data(|d| d
+                        .content(format!("{}\n*{}*", prompt, outcome))
+                        .set_components(CreateComponents::default())
+                    )
+                ).await?;
+                Ok(confirmed)
+            },
+            None => {
+                message.edit(&ctx.http, |m| m
+                    .content(format!("{}\n*Timed out.*", prompt))
+                    .components(|c| c)
+                ).await?;
+                Ok(false)
+            }
+        }
+    }
+
+    // Shows "thinking..." for slash commands so slow commands don't time out; a no-op for text commands.
+    pub async fn defer(&self, http: impl AsRef<Http>) -> serenity::Result<()> {
+        if let InvocationSource::Interaction(command) = &self.source {
+            let mut state = self.state.lock().await;
+            if *state == ResponseState::Pending {
+                command.defer(http).await?;
+                *state = ResponseState::Deferred;
+            }
+        }
+
+        Ok(())
+    }
+
+    // Clean up the "thinking..." message if the command never replied.
+    pub(super) async fn finish(&self, http: impl AsRef<Http>) -> serenity::Result<()> {
+        if let InvocationSource::Interaction(command) = &self.source {
+            if *self.state.lock().await == ResponseState::Deferred {
+                command.delete_original_interaction_response(http).await?;
+            }
+        }
+
+        Ok(())
+    }
+}
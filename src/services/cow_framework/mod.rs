@@ -0,0 +1,461 @@
+mod definitions;
+mod invocation;
+mod paginator;
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use async_trait::async_trait;
+use log::{error, warn};
+use serenity::{
+    builder::CreateApplicationCommand,
+    client::Context,
+    framework::{
+        Framework,
+        standard::{Args, CommandResult, Delimiter}
+    },
+    futures::future::BoxFuture,
+    model::{
+        channel::Message,
+        id::UserId,
+        interactions::application_command::{
+            ApplicationCommandInteraction,
+            ApplicationCommandInteractionData,
+            ApplicationCommandInteractionDataOption,
+            ApplicationCommandInteractionDataOptionValue,
+            ApplicationCommandOptionType
+        },
+        permissions::Permissions
+    }
+};
+
+pub use definitions::*;
+pub use invocation::*;
+pub use paginator::*;
+// Command functions have the same shape as hooks, so we can borrow the macro.
+pub use serenity::framework::standard::macros::hook as command;
+
+#[derive(Debug)]
+pub enum DispatchError {
+    OnlyForGuilds,
+    OnlyForOwners,
+    LackingPermissions(Permissions),
+    NotEnoughArguments { min: usize, given: usize },
+    InvalidArgument(&'static CommandOption),
+    Ratelimited { remaining: Duration, is_first_try: bool },
+    CheckFailed { name: &'static str, reason: String }
+}
+
+pub type NormalMessageHook = for<'fut> fn(&'fut Context, &'fut Message) -> BoxFuture<'fut, ()>;
+// Returns the prefix to use for this message, or None to use the default one.
+pub type DynamicPrefixHook = for<'fut> fn(&'fut Context, &'fut Message) -> BoxFuture<'fut, Option<String>>;
+pub type DispatchHook = for<'fut> fn(&'fut Context, &'fut Invocation, DispatchError) -> BoxFuture<'fut, ()>;
+// Gets whatever the command returned, errors included.
+pub type AfterHook = for<'fut> fn(&'fut Context, &'fut Invocation, CommandResult) -> BoxFuture<'fut, ()>;
+
+// Limits how many times a command can be used in a server (or by a user in DMs) over a time span.
+pub struct Bucket {
+    pub limit: usize,
+    pub time_span: Duration
+}
+
+#[derive(Default)]
+struct BucketUses {
+    uses: VecDeque<Instant>,
+    notified: bool
+}
+
+pub struct CowFramework {
+    prefix: String,
+    dynamic_prefix: Option<DynamicPrefixHook>,
+    mention: Option<UserId>,
+    owners: HashSet<UserId>,
+    groups: Vec<&'static CowGroup>,
+    buckets: HashMap<&'static str, Bucket>,
+    bucket_uses: Mutex<HashMap<(&'static str, u64), BucketUses>>,
+    normal_message: Option<NormalMessageHook>,
+    dispatch_error: Option<DispatchHook>,
+    after: Option<AfterHook>
+}
+
+impl Default for CowFramework {
+    fn default() -> Self {
+        CowFramework::new()
+    }
+}
+
+impl CowFramework {
+    pub fn new() -> Self {
+        CowFramework {
+            prefix: String::new(),
+            dynamic_prefix: None,
+            mention: None,
+            owners: HashSet::new(),
+            groups: Vec::new(),
+            buckets: HashMap::new(),
+            bucket_uses: Mutex::new(HashMap::new()),
+            normal_message: None,
+            dispatch_error: None,
+            after: None
+        }
+    }
+
+    pub fn prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.to_string();
+        self
+    }
+
+    pub fn dynamic_prefix(mut self, hook: DynamicPrefixHook) -> Self {
+        self.dynamic_prefix = Some(hook);
+        self
+    }
+
+    pub fn on_mention(mut self, id: Option<UserId>) -> Self {
+        self.mention = id;
+        self
+    }
+
+    pub fn owners(mut self, owners: HashSet<UserId>) -> Self {
+        self.owners = owners;
+        self
+    }
+
+    pub fn normal_message(mut self, hook: NormalMessageHook) -> Self {
+        self.normal_message = Some(hook);
+        self
+    }
+
+    pub fn on_dispatch_error(mut self, hook: DispatchHook) -> Self {
+        self.dispatch_error = Some(hook);
+        self
+    }
+
+    pub fn after(mut self, hook: AfterHook) -> Self {
+        self.after = Some(hook);
+        self
+    }
+
+    pub fn bucket(mut self, name: &'static str, bucket: Bucket) -> Self {
+        self.buckets.insert(name, bucket);
+        self
+    }
+
+    pub fn group(mut self, group: &'static CowGroup) -> Self {
+        self.groups.push(group);
+        self
+    }
+
+    // Commands in groups without a prefix are top-level slash commands; everything else is nested under its group.
+    pub fn application_commands(&self) -> Vec<CreateApplicationCommand> {
+        let mut commands = Vec::new();
+
+        for group in &self.groups {
+            if group.prefixes.is_empty() {
+                for command in group.commands {
+                    let mut slash_command = CreateApplicationCommand::default();
+                    command.create_application_command(&mut slash_command);
+                    commands.push(slash_command);
+                }
+            } else {
+                let mut slash_command = CreateApplicationCommand::default();
+                group.create_application_command(&mut slash_command);
+                commands.push(slash_command);
+            }
+        }
+
+        commands
+    }
+
+    fn strip_prefix<'a>(&self, content: &'a str, prefix: &str) -> Option<&'a str> {
+        if !prefix.is_empty() {
+            if let Some(rest) = content.strip_prefix(prefix) {
+                return Some(rest);
+            }
+        }
+
+        let id = self.mention?;
+        content.strip_prefix(&format!("<@{}>", id))
+            .or_else(|| content.strip_prefix(&format!("<@!{}>", id)))
+    }
+
+    fn find_in_group(group: &'static CowGroup, args: &mut Args) -> Option<&'static CowCommand> {
+        if let Some(name) = args.current() {
+            if let Some(sub_group) = group.sub_group(name) {
+                args.advance();
+                return Self::find_in_group(sub_group, args);
+            }
+
+            if let Some(command) = group.command(name) {
+                args.advance();
+                return Some(command);
+            }
+        }
+
+        group.default_command
+    }
+
+    fn find_command(&self, args: &mut Args) -> Option<&'static CowCommand> {
+        let name = args.current()?.to_string();
+
+        for group in &self.groups {
+            if group.prefixes.is_empty() {
+                if let Some(command) = group.command(&name) {
+                    args.advance();
+                    return Some(command);
+                }
+            } else if group.matches(&name) {
+                args.advance();
+                return Self::find_in_group(group, args);
+            }
+        }
+
+        None
+    }
+
+    // Slash commands are named after the first prefix of their group.
+    fn find_slash_command<'a>(&self, data: &'a ApplicationCommandInteractionData) -> Option<(&'static CowCommand, &'a [ApplicationCommandInteractionDataOption])> {
+        fn find_in_group<'a>(group: &'static CowGroup, options: &'a [ApplicationCommandInteractionDataOption]) -> Option<(&'static CowCommand, &'a [ApplicationCommandInteractionDataOption])> {
+            let option = match options.first() {
+                Some(option) => option,
+                None => return group.default_command.map(|c| (c, options))
+            };
+
+            match option.kind {
+                ApplicationCommandOptionType::SubCommandGroup => find_in_group(group.sub_group(&option.name)?, &option.options),
+                ApplicationCommandOptionType::SubCommand => group.command(&option.name).map(|c| (c, option.options.as_slice())),
+                _ => None
+            }
+        }
+
+        for group in &self.groups {
+            if group.prefixes.is_empty() {
+                if let Some(command) = group.commands.iter().find(|c| c.name == data.name) {
+                    return Some((command, &data.options));
+                }
+            } else if group.prefixes.first() == Some(&data.name.as_str()) {
+                return find_in_group(group, &data.options);
+            }
+        }
+
+        None
+    }
+
+    fn parse_text_args(command: &'static CowCommand, args: &mut Args) -> Result<HashMap<&'static str, ArgValue>, DispatchError> {
+        let mut values = HashMap::new();
+        let required = command.options.iter().filter(|o| o.required).count();
+
+        for (index, option) in command.options.iter().enumerate() {
+            args.trimmed();
+            if args.is_empty() {
+                if option.required {
+                    return Err(DispatchError::NotEnoughArguments { min: required, given: values.len() });
+                }
+                break;
+            }
+
+            let invalid = || DispatchError::InvalidArgument(option);
+            let value = match option.kind {
+                OptionKind::String if index == command.options.len() - 1 => ArgValue::String(args.rest().to_string()),
+                OptionKind::String => ArgValue::String(args.single_quoted::<String>().map_err(|_| invalid())?),
+                OptionKind::Integer => ArgValue::Integer(args.single::<i64>().map_err(|_| invalid())?),
+                OptionKind::Number => ArgValue::Number(args.single::<f64>().map_err(|_| invalid())?),
+                OptionKind::Boolean => ArgValue::Boolean(args.single::<bool>().map_err(|_| invalid())?),
+                OptionKind::User => ArgValue::User(args.single().map_err(|_| invalid())?),
+                OptionKind::Channel => ArgValue::Channel(args.single().map_err(|_| invalid())?),
+                OptionKind::Role => ArgValue::Role(args.single().map_err(|_| invalid())?)
+            };
+
+            values.insert(option.name, Self::check_choice(option, value)?);
+        }
+
+        Ok(values)
+    }
+
+    // Discord enforces choices for slash commands, but text commands can type anything.
+    fn check_choice(option: &'static CommandOption, value: ArgValue) -> Result<ArgValue, DispatchError> {
+        if option.choices.is_empty() {
+            return Ok(value);
+        }
+
+        option.choices.iter()
+            .find_map(|choice| match (&value, choice.value) {
+                (ArgValue::String(s), ChoiceValue::String(v)) if s.eq_ignore_ascii_case(v) || s.eq_ignore_ascii_case(choice.name) => Some(ArgValue::String(v.to_string())),
+                (ArgValue::Integer(i), ChoiceValue::Integer(v)) if *i == v as i64 => Some(ArgValue::Integer(*i)),
+                _ => None
+            })
+            .ok_or(DispatchError::InvalidArgument(option))
+    }
+
+    fn parse_slash_args(command: &'static CowCommand, options: &[ApplicationCommandInteractionDataOption]) -> HashMap<&'static str, ArgValue> {
+        command.options.iter()
+            .filter_map(|option| {
+                let data = options.iter().find(|o| o.name == option.name)?;
+                let value = match data.resolved.as_ref()? {
+                    ApplicationCommandInteractionDataOptionValue::String(s) => ArgValue::String(s.clone()),
+                    ApplicationCommandInteractionDataOptionValue::Integer(i) => ArgValue::Integer(*i),
+                    ApplicationCommandInteractionDataOptionValue::Number(n) => ArgValue::Number(*n),
+                    ApplicationCommandInteractionDataOptionValue::Boolean(b) => ArgValue::Boolean(*b),
+                    ApplicationCommandInteractionDataOptionValue::User(u, _) => ArgValue::User(u.id),
+                    ApplicationCommandInteractionDataOptionValue::Channel(c) => ArgValue::Channel(c.id),
+                    ApplicationCommandInteractionDataOptionValue::Role(r) => ArgValue::Role(r.id),
+                    _ => return None
+                };
+                Some((option.name, value))
+            })
+            .collect()
+    }
+
+    fn take_bucket(&self, invocation: &Invocation) -> Result<(), DispatchError> {
+        let name = match invocation.command.bucket {
+            Some(name) => name,
+            None => return Ok(())
+        };
+
+        let bucket = match self.buckets.get(name) {
+            Some(bucket) => bucket,
+            None => {
+                warn!("Command {} uses a bucket that doesn't exist: {}", invocation.command.name, name);
+                return Ok(());
+            }
+        };
+
+        let key = invocation.guild_id().map(|g| g.0).unwrap_or(invocation.author().id.0);
+        let mut bucket_uses = self.bucket_uses.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let entry = bucket_uses.entry((name, key)).or_default();
+        let now = Instant::now();
+
+        while entry.uses.front().map(|t| now.duration_since(*t) >= bucket.time_span).unwrap_or(false) {
+            entry.uses.pop_front();
+        }
+
+        if entry.uses.len() >= bucket.limit {
+            let remaining = entry.uses.front().map(|t| bucket.time_span - now.duration_since(*t)).unwrap_or_default();
+            let is_first_try = !entry.notified;
+            entry.notified = true;
+            return Err(DispatchError::Ratelimited { remaining, is_first_try });
+        }
+
+        entry.notified = false;
+        entry.uses.push_back(now);
+        Ok(())
+    }
+
+    async fn check(&self, ctx: &Context, invocation: &Invocation) -> Result<(), DispatchError> {
+        let command = invocation.command;
+
+        if command.only_in_guilds && invocation.guild_id().is_none() {
+            return Err(DispatchError::OnlyForGuilds);
+        }
+
+        if command.owners_only && !self.owners.contains(&invocation.author().id) {
+            return Err(DispatchError::OnlyForOwners);
+        }
+
+        if !command.permissions.is_empty() {
+            let permissions = invocation.permissions(ctx).await.unwrap_or_else(Permissions::empty);
+            if !permissions.contains(command.permissions) {
+                return Err(DispatchError::LackingPermissions(command.permissions));
+            }
+        }
+
+        for check in command.checks {
+            if let Err(reason) = (check.function)(ctx, invocation).await {
+                return Err(DispatchError::CheckFailed { name: check.name, reason });
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn run(&self, ctx: &Context, mut invocation: Invocation, args: Result<HashMap<&'static str, ArgValue>, DispatchError>) {
+        let checked = match self.check(ctx, &invocation).await {
+            Ok(()) => args.map(|args| invocation.args = args).and_then(|_| self.take_bucket(&invocation)),
+            Err(ex) => Err(ex)
+        };
+
+        if let Err(ex) = checked {
+            if let Some(hook) = self.dispatch_error {
+                hook(ctx, &invocation, ex).await;
+            }
+            return;
+        }
+
+        if let Err(ex) = invocation.defer(&ctx.http).await {
+            error!("Failed to defer slash command: {}", ex);
+        }
+
+        let result = (invocation.command.fun)(ctx, &invocation).await;
+        match self.after {
+            Some(hook) => hook(ctx, &invocation, result).await,
+            None => if let Err(ex) = result {
+                error!("Command {} returned an error: {}", invocation.command.name, ex);
+            }
+        }
+
+        if let Err(ex) = invocation.finish(&ctx.http).await {
+            error!("Failed to clean up slash command response: {}", ex);
+        }
+    }
+
+    pub async fn dispatch_interaction(&self, ctx: &Context, interaction: ApplicationCommandInteraction) {
+        let (command, args) = match self.find_slash_command(&interaction.data) {
+            Some((command, options)) => (command, Self::parse_slash_args(command, options)),
+            None => {
+                warn!("Received an unknown slash command: {}", interaction.data.name);
+                return;
+            }
+        };
+
+        let invocation = Invocation::new(InvocationSource::Interaction(Box::new(interaction)), command);
+        self.run(ctx, invocation, Ok(args)).await;
+    }
+}
+
+#[async_trait]
+impl Framework for CowFramework {
+    async fn dispatch(&self, ctx: Context, msg: Message) {
+        if msg.author.bot || msg.webhook_id.is_some() {
+            return;
+        }
+
+        let prefix = match self.dynamic_prefix {
+            Some(hook) => hook(&ctx, &msg).await,
+            None => None
+        };
+
+        let content = match self.strip_prefix(msg.content.trim_start(), prefix.as_deref().unwrap_or(&self.prefix)) {
+            Some(content) => content.trim_start(),
+            None => {
+                if let Some(hook) = self.normal_message {
+                    hook(&ctx, &msg).await;
+                }
+                return;
+            }
+        };
+
+        let mut args = Args::new(content, &[Delimiter::Single(' ')]);
+        let command = match self.find_command(&mut args) {
+            Some(command) => command,
+            None => {
+                if let Some(hook) = self.normal_message {
+                    hook(&ctx, &msg).await;
+                }
+                return;
+            }
+        };
+
+        let parsed = Self::parse_text_args(command, &mut args);
+        let invocation = Invocation::new(InvocationSource::Message(Box::new(msg)), command);
+        self.run(&ctx, invocation, parsed).await;
+    }
+}
+
+// Serenity wants to own the framework, but the interaction handler needs it too.
+pub struct SharedFramework(pub Arc<CowFramework>);
+
+#[async_trait]
+impl Framework for SharedFramework {
+    async fn dispatch(&self, ctx: Context, msg: Message) {
+        self.0.dispatch(ctx, msg).await;
+    }
+}
@@ -0,0 +1,131 @@
+use std::time::Duration;
+use async_trait::async_trait;
+use serenity::{
+    builder::{CreateComponents, CreateEmbed},
+    client::Context,
+    model::{
+        id::UserId,
+        interactions::{
+            message_component::ButtonStyle,
+            InteractionApplicationCommandCallbackDataFlags,
+            InteractionResponseType
+        }
+    }
+};
+use crate::models::error::CowError;
+use super::Invocation;
+
+// The buttons stop working once nobody has pressed one for this long.
+const PAGE_TIMEOUT: Duration = Duration::from_secs(120);
+
+// Anything that can be shown one embed at a time.
+#[async_trait]
+pub trait PageSource: Send + Sync {
+    // Only asked once, when the paginator starts.
+    fn page_count(&self) -> usize;
+    // Starts at 0. The page number is put in the footer afterwards.
+    async fn page(&self, ctx: &Context, index: usize) -> Result<CreateEmbed, CowError>;
+
+    // Sources with people in them can say where someone is, which adds a "jump to me" button.
+    fn can_jump(&self) -> bool {
+        false
+    }
+
+    async fn page_of(&self, _ctx: &Context, _user_id: UserId) -> Result<Option<usize>, CowError> {
+        Ok(None)
+    }
+}
+
+// Pages that are already built, like search results.
+pub struct StaticPages(pub Vec<CreateEmbed>);
+
+#[async_trait]
+impl PageSource for StaticPages {
+    fn page_count(&self) -> usize {
+        self.0.len()
+    }
+
+    async fn page(&self, _: &Context, index: usize) -> Result<CreateEmbed, CowError> {
+        Ok(self.0.get(index).cloned().unwrap_or_default())
+    }
+}
+
+fn buttons(components: &mut CreateComponents, index: usize, pages: usize, jump: bool) -> &mut CreateComponents {
+    let last = index + 1 >= pages;
+    components.create_action_row(|r| {
+        r
+            .create_button(|b| b.custom_id("page_first").label("«").style(ButtonStyle::Secondary).disabled(index == 0))
+            .create_button(|b| b.custom_id("page_prev").label("‹").style(ButtonStyle::Secondary).disabled(index == 0))
+            .create_button(|b| b.custom_id("page_next").label("›").style(ButtonStyle::Secondary).disabled(last))
+            .create_button(|b| b.custom_id("page_last").label("»").style(ButtonStyle::Secondary).disabled(last));
+        if jump {
+            r.create_button(|b| b.custom_id("page_me").label("Jump to me").style(ButtonStyle::Primary));
+        }
+        r
+    })
+}
+
+async fn page_embed(ctx: &Context, source: &dyn PageSource, index: usize, pages: usize) -> Result<CreateEmbed, CowError> {
+    let mut embed = source.page(ctx, index).await?;
+    embed.footer(|f| f.text(format!("Page {}/{}", index + 1, pages)));
+    Ok(embed)
+}
+
+// Shows the source starting at the given page, with buttons for the author to flip through it until they stop.
+pub async fn paginate(ctx: &Context, cmd: &Invocation, source: &dyn PageSource, start: usize) -> Result<(), CowError> {
+    let pages = source.page_count().max(1);
+    let jump = source.can_jump();
+    let mut index = start.min(pages - 1);
+
+    let embed = page_embed(ctx, source, index, pages).await?;
+    if pages == 1 && !jump {
+        cmd.send_message(&ctx.http, |m| m.add_embed(embed)).await?;
+        return Ok(());
+    }
+
+    let mut message = cmd.send_message(&ctx.http, |m| m
+        .add_embed(embed)
+        .components(|c| buttons(c, index, pages, jump))
+    ).await?;
+
+    while let Some(interaction) = message.await_component_interaction(&ctx)
+        .author_id(cmd.author().id)
+        .timeout(PAGE_TIMEOUT)
+        .await {
+        let target = match interaction.data.custom_id.as_str() {
+            "page_first" => Some(0),
+            "page_prev" => Some(index.saturating_sub(1)),
+            "page_next" => Some((index + 1).min(pages - 1)),
+            "page_last" => Some(pages - 1),
+            "page_me" => source.page_of(ctx, interaction.user.id).await?.map(|page| page.min(pages - 1)),
+            _ => continue
+        };
+
+        match target {
+            Some(target) => {
+                index = target;
+                let embed = page_embed(ctx, source, index, pages).await?;
+                interaction.create_interaction_response(&ctx.http, |r| r
+                    .kind(InteractionResponseType::UpdateMessage)
+                    .interaction_response_data(|d| d
+                        .add_embed(embed)
+                        .components(|c| buttons(c, index, pages, jump))
+                    )
+                ).await?;
+            },
+            None => {
+                interaction.create_interaction_response(&ctx.http, |r| r
+                    .kind(InteractionResponseType::ChannelMessageWithSource)
+                    .interaction_response_data(|d| d
+                        .content("You aren't on here yet.")
+                        .flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL)
+                    )
+                ).await?;
+            }
+        }
+    }
+
+    message.edit(&ctx.http, |m| m.components(|c| c)).await?;
+
+    Ok(())
+}
@@ -0,0 +1,138 @@
+use std::collections::HashSet;
+use bb8::Pool;
+use bb8_tiberius::ConnectionManager;
+use log::info;
+use rusqlite::{Connection, params};
+use crate::models::error::CowError;
+
+pub struct Migration {
+    pub version: i32,
+    pub name: &'static str,
+    pub script: &'static str
+}
+
+// Both lists are applied in order, and a version is never reused once it has shipped.
+pub const SQL_SERVER_MIGRATIONS: [Migration; 13] = [
+    Migration { version: 1, name: "ranking", script: include_str!("../../../migrations/sql_server/0001_ranking.sql") },
+    Migration { version: 2, name: "cowboard", script: include_str!("../../../migrations/sql_server/0002_cowboard.sql") },
+    Migration { version: 3, name: "ucm", script: include_str!("../../../migrations/sql_server/0003_ucm.sql") },
+    Migration { version: 4, name: "settings", script: include_str!("../../../migrations/sql_server/0004_settings.sql") },
+    Migration { version: 5, name: "xp_curve", script: include_str!("../../../migrations/sql_server/0005_xp_curve.sql") },
+    Migration { version: 6, name: "multipliers", script: include_str!("../../../migrations/sql_server/0006_multipliers.sql") },
+    Migration { version: 7, name: "add_exp", script: include_str!("../../../migrations/sql_server/0007_add_exp.sql") },
+    Migration { version: 8, name: "old_level", script: include_str!("../../../migrations/sql_server/0008_old_level.sql") },
+    Migration { version: 9, name: "audit", script: include_str!("../../../migrations/sql_server/0009_audit.sql") },
+    Migration { version: 10, name: "xp_events", script: include_str!("../../../migrations/sql_server/0010_xp_events.sql") },
+    Migration { version: 11, name: "rewards", script: include_str!("../../../migrations/sql_server/0011_rewards.sql") },
+    Migration { version: 12, name: "role_fixes", script: include_str!("../../../migrations/sql_server/0012_role_fixes.sql") },
+    Migration { version: 13, name: "add_exp_old_level", script: include_str!("../../../migrations/sql_server/0013_add_exp_old_level.sql") }
+];
+
+pub const SQLITE_MIGRATIONS: [Migration; 9] = [
+    Migration { version: 1, name: "ranking", script: include_str!("../../../migrations/sqlite/0001_ranking.sql") },
+    Migration { version: 2, name: "cowboard", script: include_str!("../../../migrations/sqlite/0002_cowboard.sql") },
+    Migration { version: 3, name: "ucm", script: include_str!("../../../migrations/sqlite/0003_ucm.sql") },
+    Migration { version: 4, name: "settings", script: include_str!("../../../migrations/sqlite/0004_settings.sql") },
+    Migration { version: 5, name: "multipliers", script: include_str!("../../../migrations/sqlite/0005_multipliers.sql") },
+    Migration { version: 6, name: "audit", script: include_str!("../../../migrations/sqlite/0006_audit.sql") },
+    Migration { version: 7, name: "xp_events", script: include_str!("../../../migrations/sqlite/0007_xp_events.sql") },
+    Migration { version: 8, name: "rewards", script: include_str!("../../../migrations/sqlite/0008_rewards.sql") },
+    Migration { version: 9, name: "role_fixes", script: include_str!("../../../migrations/sqlite/0009_role_fixes.sql") }
+];
+
+// GO isn't T-SQL, it's how sqlcmd/SSMS split a script into batches, so we have to do the same.
+fn batches(script: &str) -> Vec<String> {
+    let mut batches = Vec::new();
+    let mut current = String::new();
+
+    for line in script.lines() {
+        if line.trim().eq_ignore_ascii_case("GO") {
+            if !current.trim().is_empty() {
+                batches.push(current.clone());
+            }
+            current.clear();
+        } else {
+            current.push_str(line);
+            current.push('\n');
+        }
+    }
+
+    if !current.trim().is_empty() {
+        batches.push(current);
+    }
+
+    batches
+}
+
+// Returns the versions that were applied.
+pub async fn migrate_sql_server(pool: &Pool<ConnectionManager>) -> Result<Vec<i32>, CowError> {
+    let mut conn = pool.get().await?;
+
+    conn.simple_query(
+        "IF OBJECT_ID(N'[dbo].[SchemaMigrations]', N'U') IS NULL
+        CREATE TABLE [dbo].[SchemaMigrations] (
+            version INT NOT NULL PRIMARY KEY,
+            name NVARCHAR(256) NOT NULL,
+            applied_at DATETIME2 NOT NULL DEFAULT SYSUTCDATETIME()
+        )"
+    ).await?.into_results().await?;
+
+    // Scripts may USE another database, so remember where the migrations table lives.
+    let database = conn.simple_query("SELECT DB_NAME()").await?.into_row().await?
+        .and_then(|row| row.get::<&str, _>(0).map(|name| name.to_string()))
+        .ok_or_else(|| CowError::decode("could not get the current database name"))?;
+
+    let applied: HashSet<i32> = conn.simple_query("SELECT version FROM [dbo].[SchemaMigrations]").await?
+        .into_first_result().await?
+        .into_iter()
+        .filter_map(|row| row.get::<i32, _>(0))
+        .collect();
+
+    let mut ran = Vec::new();
+
+    // SQL Server can't roll back CREATE DATABASE or full-text indexes, so the scripts are written
+    // to be re-runnable instead of being wrapped in a transaction.
+    for migration in SQL_SERVER_MIGRATIONS.iter().filter(|m| !applied.contains(&m.version)) {
+        for batch in batches(migration.script) {
+            conn.simple_query(batch).await?.into_results().await?;
+        }
+
+        conn.simple_query(format!("USE [{}]", database.replace(']', "]]"))).await?.into_results().await?;
+        conn.execute("INSERT INTO [dbo].[SchemaMigrations] (version, name) VALUES (@P1, @P2)", &[&migration.version, &migration.name]).await?;
+
+        info!("Applied migration {} ({})", migration.version, migration.name);
+        ran.push(migration.version);
+    }
+
+    Ok(ran)
+}
+
+pub fn migrate_sqlite(conn: &mut Connection) -> Result<Vec<i32>, rusqlite::Error> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS schema_migrations (
+            version INTEGER NOT NULL PRIMARY KEY,
+            name TEXT NOT NULL,
+            applied_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )"
+    )?;
+
+    let applied = {
+        let mut stmt = conn.prepare("SELECT version FROM schema_migrations")?;
+        let versions = stmt.query_map([], |row| row.get::<_, i32>(0))?.collect::<Result<HashSet<i32>, _>>()?;
+        versions
+    };
+
+    let mut ran = Vec::new();
+
+    for migration in SQLITE_MIGRATIONS.iter().filter(|m| !applied.contains(&m.version)) {
+        let tx = conn.transaction()?;
+        tx.execute_batch(migration.script)?;
+        tx.execute("INSERT INTO schema_migrations (version, name) VALUES (?1, ?2)", params![migration.version, migration.name])?;
+        tx.commit()?;
+
+        info!("Applied migration {} ({})", migration.version, migration.name);
+        ran.push(migration.version);
+    }
+
+    Ok(ran)
+}
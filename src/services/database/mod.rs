@@ -0,0 +1,118 @@
+pub mod migrations;
+pub mod sql_server;
+pub mod sqlite;
+
+use std::sync::Arc;
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use serenity::{
+    model::id::{
+        UserId,
+        GuildId,
+        ChannelId,
+        RoleId
+    },
+    prelude::TypeMapKey
+};
+use crate::models::config::{Config, DatabaseBackend};
+use crate::models::db_models::*;
+use crate::models::error::CowError;
+use crate::models::rewards::{LevelReward, RewardKind, XpBoost};
+use crate::models::xp_settings::XpSettings;
+use crate::commands::cowboard::cowboard_db::CowboardStore;
+use crate::commands::ucm::courses_db::CourseStore;
+use crate::commands::guild_config::settings_db::SettingsStore;
+
+pub use sql_server::SqlServerDatabase;
+pub use sqlite::SqliteDatabase;
+
+// Everything the bot needs from a storage backend. The cowboard, course and settings queries live in their own modules.
+pub trait Storage: RankingStore + CowboardStore + CourseStore + SettingsStore + Send + Sync {}
+
+impl<T: RankingStore + CowboardStore + CourseStore + SettingsStore + Send + Sync> Storage for T {}
+
+// Only used as the key to fetch the storage backend from the context data.
+pub struct Database;
+
+impl TypeMapKey for Database {
+    type Value = Arc<dyn Storage>;
+}
+
+impl Database {
+    // Pending migrations are applied before anyone gets to use the connection.
+    pub async fn connect(config: &Config, migrate: bool) -> Result<Arc<dyn Storage>, CowError> {
+        let storage: Arc<dyn Storage> = match config.database {
+            DatabaseBackend::SqlServer => {
+                let db = SqlServerDatabase::new(&config.sql_server_ip, config.sql_server_port, &config.sql_server_username, &config.sql_server_password).await?;
+                if migrate {
+                    db.migrate().await?;
+                }
+                Arc::new(db)
+            },
+            DatabaseBackend::Sqlite => {
+                let db = SqliteDatabase::new(&config.sqlite_path)?;
+                if migrate {
+                    db.migrate()?;
+                }
+                Arc::new(db)
+            }
+        };
+
+        Ok(storage)
+    }
+}
+
+#[async_trait]
+pub trait RankingStore {
+    // Returns a level of -1 if the user did not level up.
+    async fn provide_exp(&self, server_id: GuildId, user_id: UserId, gain: i32, settings: &XpSettings) -> Result<LevelUp, CowError>;
+    // Like provide_exp, but doesn't check or reset the cooldown.
+    async fn add_exp(&self, server_id: GuildId, user_id: UserId, gain: i32, settings: &XpSettings) -> Result<LevelUp, CowError>;
+    async fn get_xp(&self, server_id: GuildId, user_id: UserId) -> Result<Experience, CowError>;
+    // Like get_xp, but None if they've never earned any.
+    async fn find_xp(&self, server_id: GuildId, user_id: UserId) -> Result<Option<Experience>, CowError>;
+    async fn get_highest_role(&self, server_id: GuildId, level: i32) -> Result<Option<RoleId>, CowError>;
+    // True: disabled False: enabled
+    async fn toggle_channel_xp(&self, server_id: GuildId, channel_id: ChannelId) -> Result<bool, CowError>;
+    async fn channel_disabled(&self, server_id: GuildId, channel_id: ChannelId) -> Result<bool, CowError>;
+    // Page number is zero-indexed.
+    async fn top_members(&self, server_id: GuildId, page: i32) -> Result<MemberPagination, CowError>;
+    async fn rank_within_members(&self, server_id: GuildId, user_id: UserId) -> Result<Option<i64>, CowError>;
+    async fn get_roles(&self, server_id: GuildId) -> Result<Vec<Rank>, CowError>;
+    // False if there is already a rank at this level.
+    async fn add_role(&self, server_id: GuildId, role_name: &str, role_id: RoleId, min_level: i32) -> Result<bool, CowError>;
+    async fn remove_role(&self, server_id: GuildId, role_id: RoleId) -> Result<bool, CowError>;
+    async fn set_timeout(&self, server_id: GuildId, timeout: i32) -> Result<bool, CowError>;
+    async fn get_timeout(&self, server_id: GuildId) -> Result<i32, CowError>;
+    async fn get_users(&self, server_id: GuildId) -> Result<Vec<FullMember>, CowError>;
+    async fn get_multipliers(&self, server_id: GuildId) -> Result<Vec<XpMultiplier>, CowError>;
+    async fn set_multiplier(&self, server_id: GuildId, multiplier: &XpMultiplier) -> Result<(), CowError>;
+    // False if there wasn't one.
+    async fn remove_multiplier(&self, server_id: GuildId, target: MultiplierTarget) -> Result<bool, CowError>;
+    // Overwrites someone's experience without touching the cooldown.
+    async fn set_exp(&self, server_id: GuildId, user_id: UserId, xp: i32, level: i32) -> Result<(), CowError>;
+    // Everyone if there's no user. Returns how many were wiped.
+    async fn reset_exp(&self, server_id: GuildId, user_id: Option<UserId>) -> Result<u64, CowError>;
+    // set_exp for a lot of people at once, like when moving over from another bot. Returns how many were written.
+    async fn import_exp(&self, server_id: GuildId, members: &[(UserId, Experience)]) -> Result<u64, CowError>;
+    async fn add_audit(&self, server_id: GuildId, entry: &AuditEntry) -> Result<(), CowError>;
+    // Newest first.
+    async fn get_audit(&self, server_id: GuildId, limit: i32) -> Result<Vec<AuditEntry>, CowError>;
+    // Everyone who earned experience since then (UTC), most first.
+    async fn top_gains(&self, server_id: GuildId, since: NaiveDateTime) -> Result<Vec<XpGain>, CowError>;
+    // Lowest level first.
+    async fn get_rewards(&self, server_id: GuildId) -> Result<Vec<LevelReward>, CowError>;
+    // Returns the new reward's ID.
+    async fn add_reward(&self, server_id: GuildId, min_level: i32, kind: &RewardKind) -> Result<i32, CowError>;
+    // False if there wasn't one.
+    async fn remove_reward(&self, server_id: GuildId, id: i32) -> Result<bool, CowError>;
+    // None if they don't have one, or it ran out.
+    async fn get_boost(&self, server_id: GuildId, user_id: UserId) -> Result<Option<XpBoost>, CowError>;
+    // Replaces whatever boost they had.
+    async fn set_boost(&self, server_id: GuildId, user_id: UserId, boost: &XpBoost) -> Result<(), CowError>;
+    // Replaces the server's last fix, which can't be undone after this.
+    async fn record_fix(&self, server_id: GuildId, created_at: NaiveDateTime, changes: &[RoleChange]) -> Result<i32, CowError>;
+    async fn last_fix(&self, server_id: GuildId) -> Result<Option<FixChangeset>, CowError>;
+    // False if it was already gone, like when two undos race.
+    async fn remove_fix(&self, server_id: GuildId, id: i32) -> Result<bool, CowError>;
+}
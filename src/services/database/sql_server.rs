@@ -0,0 +1,729 @@
+use bb8::Pool;
+use bb8_tiberius::ConnectionManager;
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use serenity::{
+    model::id::{
+        UserId,
+        GuildId,
+        ChannelId, RoleId
+    }
+};
+use tiberius::{AuthMethod, Config, FromSql, Row, ToSql};
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+use crate::models::db_models::*;
+use crate::models::error::CowError;
+use crate::models::rewards::{LevelReward, RewardKind, XpBoost};
+use crate::models::xp_settings::XpSettings;
+use crate::services::database::{RankingStore, migrations};
+
+// Rows per MERGE when importing; SQL Server allows 2100 parameters a query.
+const IMPORT_BATCH_SIZE: usize = 500;
+
+pub struct SqlServerDatabase {
+    pub(crate) pool: Pool<ConnectionManager>
+}
+
+impl SqlServerDatabase {
+    pub async fn new(ip: &str, port: u16, usr: &str, pwd: &str) -> Result<Self, CowError> {
+        // The password is stored in a file; using secure strings is probably not going to make much of a difference.
+        let mut config = Config::new();
+
+        config.host(ip);
+        config.port(port);
+        config.authentication(AuthMethod::sql_server(usr, pwd));
+        // Default schema needs to be Cow
+        config.database("Cow");
+        config.trust_cert();
+
+        let manager = ConnectionManager::build(config)?;
+        let pool = Pool::builder().max_size(8).build(manager).await?;
+
+        Ok(SqlServerDatabase { pool })
+    }
+
+    pub async fn migrate(&self) -> Result<Vec<i32>, CowError> {
+        migrations::migrate_sql_server(&self.pool).await
+    }
+
+    // ProvideExp and AddExp take the same parameters and return the same row.
+    async fn give_exp(&self, procedure: &str, server_id: GuildId, user_id: UserId, gain: i32, settings: &XpSettings) -> Result<LevelUp, CowError> {
+        let mut conn = self.pool.get().await?;
+        let server = to_decimal(server_id.0);
+        let user = to_decimal(user_id.0);
+        let (exponential, terms) = settings.curve.sql_terms();
+        let res = conn.query(
+            format!("{} @serverid = @P1, @userid = @P2, @gain = @P3, @level_cap = @P4, @exponential = @P5, @c0 = @P6, @c1 = @P7, @c2 = @P8, @c3 = @P9", procedure),
+            &[&server, &user, &gain, &settings.level_cap, &exponential, &terms[0], &terms[1], &terms[2], &terms[3]])
+            .await?
+            .into_row()
+            .await?;
+
+        let mut out = LevelUp::new();
+
+        if let Some(row) = res {
+            out = LevelUp {
+                level: column(&row, 0)?,
+                old_level: column(&row, 3)?,
+                old_rank: nullable_id(&row, 1)?,
+                new_rank: nullable_id(&row, 2)?
+            };
+        }
+
+        Ok(out)
+    }
+}
+
+// Snowflakes always fit in a DECIMAL(20, 0).
+pub(crate) fn to_decimal(id: u64) -> Decimal {
+    Decimal::from(id)
+}
+
+pub(crate) fn from_decimal(id: Decimal) -> Result<u64, CowError> {
+    id.to_u64().ok_or_else(|| CowError::decode(format!("{} is not a valid ID", id)))
+}
+
+// Like row.get, but a NULL or the wrong type is an error instead of a panic.
+pub(crate) fn column<'a, T: FromSql<'a>>(row: &'a Row, index: usize) -> Result<T, CowError> {
+    nullable(row, index)?.ok_or_else(|| CowError::decode(format!("column {} was NULL", index)))
+}
+
+pub(crate) fn nullable<'a, T: FromSql<'a>>(row: &'a Row, index: usize) -> Result<Option<T>, CowError> {
+    Ok(row.try_get(index)?)
+}
+
+pub(crate) fn nullable_id(row: &Row, index: usize) -> Result<Option<u64>, CowError> {
+    nullable::<Decimal>(row, index)?.map(from_decimal).transpose()
+}
+
+#[async_trait]
+impl RankingStore for SqlServerDatabase {
+    async fn provide_exp(&self, server_id: GuildId, user_id: UserId, gain: i32, settings: &XpSettings) -> Result<LevelUp, CowError> {
+        self.give_exp("EXEC Ranking.ProvideExp", server_id, user_id, gain, settings).await
+    }
+
+    async fn add_exp(&self, server_id: GuildId, user_id: UserId, gain: i32, settings: &XpSettings) -> Result<LevelUp, CowError> {
+        self.give_exp("EXEC Ranking.AddExp", server_id, user_id, gain, settings).await
+    }
+
+    async fn get_xp(&self, server_id: GuildId, user_id: UserId) -> Result<Experience, CowError> {
+        Ok(self.find_xp(server_id, user_id).await?.unwrap_or_else(Experience::new))
+    }
+
+    async fn find_xp(&self, server_id: GuildId, user_id: UserId) -> Result<Option<Experience>, CowError> {
+        let mut conn = self.pool.get().await?;
+        let server = to_decimal(server_id.0);
+        let user = to_decimal(user_id.0);
+        let res = conn.query(
+            "SELECT xp, level FROM [Ranking].[Level] WHERE server_id = @P1 AND [user_id] = @P2",
+            &[&server, &user])
+            .await?
+            .into_row()
+            .await?;
+
+        match res {
+            Some(item) => Ok(Some(Experience {
+                xp: column(&item, 0)?,
+                level: column(&item, 1)?
+            })),
+            None => Ok(None)
+        }
+    }
+
+    async fn get_highest_role(&self, server_id: GuildId, level: i32) -> Result<Option<RoleId>, CowError> {
+        let mut conn = self.pool.get().await?;
+        let server = to_decimal(server_id.0);
+        let res = conn.query(
+            "SELECT TOP 1 role_id FROM [Ranking].[Role] WHERE server_id = @P1 AND min_level <= @P2 ORDER BY min_level DESC",
+            &[&server, &level])
+            .await?
+            .into_row()
+            .await?;
+
+        let mut out: Option<RoleId> = None;
+
+        if let Some(item) = res {
+            out = nullable_id(&item, 0)?.map(RoleId::from);
+        }
+
+        Ok(out)
+    }
+
+    // Because by default a channel should be enabled, right?
+    async fn toggle_channel_xp(&self, server_id: GuildId, channel_id: ChannelId) -> Result<bool, CowError> {
+        let mut conn = self.pool.get().await?;
+        let server = to_decimal(server_id.0);
+        let channel = to_decimal(channel_id.0);
+        let res = conn.query(
+            "EXEC [Ranking].[ToggleChannel] @serverid = @P1, @channelid = @P2",
+            &[&server, &channel])
+            .await?
+            .into_row()
+            .await?;
+
+        let mut out: bool = false;
+
+        if let Some(item) = res {
+            out = column(&item, 0)?;
+        }
+
+        Ok(out)
+    }
+
+    async fn channel_disabled(&self, server_id: GuildId, channel_id: ChannelId) -> Result<bool, CowError> {
+        let mut conn = self.pool.get().await?;
+        let server = to_decimal(server_id.0);
+        let channel = to_decimal(channel_id.0);
+        let res = conn.query(
+            "SELECT CAST(1 AS BIT) FROM [Ranking].[DisabledChannel] WHERE server_id = @P1 AND channel_id = @P2",
+            &[&server, &channel])
+            .await?
+            .into_row()
+            .await?;
+
+        let mut out: bool = false;
+
+        if let Some(item) = res {
+            out = column(&item, 0)?;
+        }
+
+        Ok(out)
+    }
+
+    async fn top_members(&self, server_id: GuildId, page: i32) -> Result<MemberPagination, CowError> {
+        let mut conn = self.pool.get().await?;
+        let server = to_decimal(server_id.0);
+        const ROWS_FETCHED: i32 = 10;
+        let mut offset = page * ROWS_FETCHED;
+        offset = offset.max(0);
+        let res = conn.query(
+            "SELECT user_id, level, xp FROM [Ranking].[Level] WHERE server_id = @P1 ORDER BY level DESC, xp DESC OFFSET @P2 ROWS FETCH NEXT @P3 ROWS ONLY; SELECT COUNT(1) FROM [Ranking].[Level] WHERE server_id = @P1",
+            &[&server, &offset, &ROWS_FETCHED])
+            .await?
+            .into_results()
+            .await?;
+
+        let count: i32 = match res.get(1).and_then(|rows| rows.first()) {
+            Some(row) => column(row, 0)?,
+            None => return Err(CowError::decode("missing the member count"))
+        };
+
+        let members = res.first().map(|rows| rows.as_slice()).unwrap_or_default().iter()
+            .map(|row| Ok(Member {
+                id: UserId::from(from_decimal(column(row, 0)?)?),
+                exp: Experience {
+                    level: column(row, 1)?,
+                    xp: column(row, 2)?
+                }
+            }))
+            .collect::<Result<Vec<_>, CowError>>()?;
+
+        let pages = (count / ROWS_FETCHED) + ((count % ROWS_FETCHED != 0) as i32); // Divide, then round if not perfect division
+
+        Ok(MemberPagination {
+            members,
+            current_page: page,
+            last_page: pages
+        })
+    }
+
+    async fn rank_within_members(&self, server_id: GuildId, user_id: UserId) -> Result<Option<i64>, CowError> {
+        let mut conn = self.pool.get().await?;
+        let server = to_decimal(server_id.0);
+        let user = to_decimal(user_id.0);
+        let res = conn.query(
+            "SELECT row_number FROM (SELECT user_id, ROW_NUMBER() OVER (ORDER BY level DESC, xp DESC) AS row_number FROM [Ranking].[Level] WHERE server_id = @P1) mukyu WHERE user_id = @P2",
+            &[&server, &user])
+            .await?
+            .into_row()
+            .await?;
+
+        let mut out: Option<i64> = None;
+
+        if let Some(item) = res {
+            // Apparently it's an i64. Cool.
+            out = nullable(&item, 0)?;
+        }
+
+        Ok(out)
+    }
+
+    async fn get_roles(&self, server_id: GuildId) -> Result<Vec<Rank>, CowError> {
+        let mut conn = self.pool.get().await?;
+        let server = to_decimal(server_id.0);
+        let res = conn.query(
+            "SELECT role_name, role_id, min_level FROM [Ranking].[Role] WHERE server_id = @P1 ORDER BY min_level ASC",
+            &[&server])
+            .await?
+            .into_first_result()
+            .await?
+            .into_iter()
+            .map(|row| Ok(Rank {
+                name: column::<&str>(&row, 0)?.to_string(),
+                role_id: nullable_id(&row, 1)?.map(RoleId::from),
+                min_level: column(&row, 2)?
+            }))
+            .collect::<Result<Vec<_>, CowError>>()?;
+
+        Ok(res)
+    }
+
+    // will also set role 
+    async fn add_role(&self, server_id: GuildId, role_name: &str, role_id: RoleId, min_level: i32) -> Result<bool, CowError> {
+        let mut conn = self.pool.get().await?;
+        let server = to_decimal(server_id.0);
+        let role = to_decimal(role_id.0);
+        let res = conn.query(
+            "EXEC [Ranking].[AddRole] @server_id = @P1, @role_name = @P2, @role_id = @P3, @min_level = @P4",
+            &[&server, &role_name, &role, &Decimal::from(min_level)])
+            .await?
+            .into_row()
+            .await?;
+
+        let mut out: bool = false;
+
+        if let Some(item) = res {
+            out = column(&item, 0)?;
+        }
+
+        Ok(out)
+    }
+
+    async fn remove_role(&self, server_id: GuildId, role_id: RoleId) -> Result<bool, CowError> {
+        let mut conn = self.pool.get().await?;
+        let server = to_decimal(server_id.0);
+        let role = to_decimal(role_id.0);
+        let res = conn.query(
+            "EXEC [Ranking].[RemoveRole] @serverid = @P1, @roleid = @P2",
+            &[&server, &role])
+            .await?
+            .into_row()
+            .await?;
+
+        let mut out: bool = false;
+
+        if let Some(item) = res {
+            out = column(&item, 0)?;
+        }
+
+        Ok(out)
+    }
+
+    async fn set_timeout(&self, server_id: GuildId, timeout: i32) -> Result<bool, CowError> {
+        let mut conn = self.pool.get().await?;
+        let server = to_decimal(server_id.0);
+        let timeout = Decimal::from(timeout);
+        let res = conn.query(
+            "EXEC [Ranking].[SetServerTimeout] @serverid = @P1, @timeout = @P2",
+            &[&server, &timeout])
+            .await?
+            .into_row()
+            .await?;
+
+        let mut out: bool = false;
+
+        if let Some(item) = res {
+            out = column(&item, 0)?;
+        }
+
+        Ok(out)
+    }
+
+    async fn get_timeout(&self, server_id: GuildId) -> Result<i32, CowError> {
+        let mut conn = self.pool.get().await?;
+        let server = to_decimal(server_id.0);
+        let res = conn.query(
+            "SELECT TOP 1 timeout FROM [Ranking].[Server] WHERE id=@P1",
+            &[&server])
+            .await?
+            .into_row()
+            .await?;
+
+        let mut out: i32 = -1;
+
+        if let Some(item) = res {
+            out = column(&item, 0)?;
+        }
+
+        Ok(out)
+    }
+
+    async fn get_users(&self, server_id: GuildId) -> Result<Vec<FullMember>, CowError> {
+        let mut conn = self.pool.get().await?;
+        let server = to_decimal(server_id.0);
+        let res = conn.query(
+            "EXEC [Ranking].[GetAllUsers] @serverid = @P1",
+            &[&server])
+            .await?
+            .into_first_result()
+            .await?
+            .into_iter()
+            .map(|row| Ok(FullMember {
+                user: UserId::from(from_decimal(column(&row, 0)?)?),
+                exp: Experience {
+                    level: column(&row, 1)?,
+                    xp: column(&row, 2)?
+                },
+                role_id: nullable_id(&row, 3)?.map(RoleId::from)
+            }))
+            .collect::<Result<Vec<_>, CowError>>()?;
+
+        Ok(res)
+    }
+
+    async fn get_multipliers(&self, server_id: GuildId) -> Result<Vec<XpMultiplier>, CowError> {
+        let mut conn = self.pool.get().await?;
+        let server = to_decimal(server_id.0);
+        let res = conn.query(
+            "SELECT target_id, is_role, multiplier FROM [Ranking].[Multiplier] WHERE server_id = @P1",
+            &[&server])
+            .await?
+            .into_first_result()
+            .await?
+            .into_iter()
+            .map(|row| {
+                let target = from_decimal(column(&row, 0)?)?;
+                Ok(XpMultiplier {
+                    target: if column(&row, 1)? { MultiplierTarget::Role(RoleId(target)) } else { MultiplierTarget::Channel(ChannelId(target)) },
+                    multiplier: column(&row, 2)?
+                })
+            })
+            .collect::<Result<Vec<_>, CowError>>()?;
+
+        Ok(res)
+    }
+
+    async fn set_multiplier(&self, server_id: GuildId, multiplier: &XpMultiplier) -> Result<(), CowError> {
+        let mut conn = self.pool.get().await?;
+        let server = to_decimal(server_id.0);
+        let (target, is_role) = match multiplier.target {
+            MultiplierTarget::Channel(channel) => (to_decimal(channel.0), false),
+            MultiplierTarget::Role(role) => (to_decimal(role.0), true)
+        };
+        conn.execute(
+            "EXEC [Ranking].[SetMultiplier] @serverid = @P1, @targetid = @P2, @isrole = @P3, @multiplier = @P4",
+            &[&server, &target, &is_role, &multiplier.multiplier])
+            .await?;
+
+        Ok(())
+    }
+
+    async fn remove_multiplier(&self, server_id: GuildId, target: MultiplierTarget) -> Result<bool, CowError> {
+        let mut conn = self.pool.get().await?;
+        let server = to_decimal(server_id.0);
+        let target = match target {
+            MultiplierTarget::Channel(channel) => to_decimal(channel.0),
+            MultiplierTarget::Role(role) => to_decimal(role.0)
+        };
+        let res = conn.execute(
+            "DELETE FROM [Ranking].[Multiplier] WHERE server_id = @P1 AND target_id = @P2",
+            &[&server, &target])
+            .await?;
+
+        Ok(res.total() > 0)
+    }
+
+    async fn set_exp(&self, server_id: GuildId, user_id: UserId, xp: i32, level: i32) -> Result<(), CowError> {
+        let mut conn = self.pool.get().await?;
+        let server = to_decimal(server_id.0);
+        let user = to_decimal(user_id.0);
+        conn.execute(
+            "UPDATE [Ranking].[Level] SET xp = @P3, level = @P4 WHERE server_id = @P1 AND [user_id] = @P2; \
+            IF @@ROWCOUNT = 0 INSERT INTO [Ranking].[Level] (server_id, [user_id], xp, level) VALUES (@P1, @P2, @P3, @P4)",
+            &[&server, &user, &xp, &level])
+            .await?;
+
+        Ok(())
+    }
+
+    async fn reset_exp(&self, server_id: GuildId, user_id: Option<UserId>) -> Result<u64, CowError> {
+        let mut conn = self.pool.get().await?;
+        let server = to_decimal(server_id.0);
+        let user = user_id.map(|u| to_decimal(u.0));
+        // The level rows go last so their count is the one returned.
+        let res = conn.execute(
+            "DELETE FROM [Ranking].[XpEvent] WHERE server_id = @P1 AND (@P2 IS NULL OR [user_id] = @P2); DELETE FROM [Ranking].[Level] WHERE server_id = @P1 AND (@P2 IS NULL OR [user_id] = @P2)",
+            &[&server, &user])
+            .await?;
+
+        Ok(res.rows_affected().last().copied().unwrap_or(0))
+    }
+
+    async fn import_exp(&self, server_id: GuildId, members: &[(UserId, Experience)]) -> Result<u64, CowError> {
+        let mut conn = self.pool.get().await?;
+        let server = to_decimal(server_id.0);
+        let mut written = 0;
+
+        for chunk in members.chunks(IMPORT_BATCH_SIZE) {
+            let users = chunk.iter().map(|(user, _)| to_decimal(user.0)).collect::<Vec<_>>();
+            let mut params: Vec<&dyn ToSql> = vec![&server];
+            let mut rows = Vec::with_capacity(chunk.len());
+            for (i, (user, (_, exp))) in users.iter().zip(chunk).enumerate() {
+                params.push(user);
+                params.push(&exp.xp);
+                params.push(&exp.level);
+                rows.push(format!("(@P{}, @P{}, @P{})", i * 3 + 2, i * 3 + 3, i * 3 + 4));
+            }
+
+            let res = conn.execute(
+                format!("MERGE [Ranking].[Level] AS target \
+                USING (VALUES {}) AS source ([user_id], xp, level) \
+                ON target.server_id = @P1 AND target.[user_id] = source.[user_id] \
+                WHEN MATCHED THEN UPDATE SET xp = source.xp, level = source.level \
+                WHEN NOT MATCHED THEN INSERT (server_id, [user_id], xp, level) VALUES (@P1, source.[user_id], source.xp, source.level);",
+                    rows.join(", ")),
+                &params)
+                .await?;
+            written += res.total();
+        }
+
+        Ok(written)
+    }
+
+    async fn add_audit(&self, server_id: GuildId, entry: &AuditEntry) -> Result<(), CowError> {
+        let mut conn = self.pool.get().await?;
+        let server = to_decimal(server_id.0);
+        let moderator = to_decimal(entry.moderator.0);
+        let user = entry.user.map(|u| to_decimal(u.0));
+        conn.execute(
+            "INSERT INTO [Ranking].[Audit] (server_id, moderator_id, [user_id], action, detail) VALUES (@P1, @P2, @P3, @P4, @P5)",
+            &[&server, &moderator, &user, &entry.action.as_str(), &entry.detail.as_str()])
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_audit(&self, server_id: GuildId, limit: i32) -> Result<Vec<AuditEntry>, CowError> {
+        let mut conn = self.pool.get().await?;
+        let server = to_decimal(server_id.0);
+        let res = conn.query(
+            "SELECT TOP (@P2) moderator_id, [user_id], action, detail, created_at FROM [Ranking].[Audit] WHERE server_id = @P1 ORDER BY created_at DESC, id DESC",
+            &[&server, &limit])
+            .await?
+            .into_first_result()
+            .await?
+            .into_iter()
+            .map(|row| Ok(AuditEntry {
+                moderator: UserId::from(from_decimal(column(&row, 0)?)?),
+                user: nullable_id(&row, 1)?.map(UserId::from),
+                action: column::<&str>(&row, 2)?.to_string(),
+                detail: column::<&str>(&row, 3)?.to_string(),
+                created_at: nullable(&row, 4)?
+            }))
+            .collect::<Result<Vec<_>, CowError>>()?;
+
+        Ok(res)
+    }
+
+    async fn top_gains(&self, server_id: GuildId, since: NaiveDateTime) -> Result<Vec<XpGain>, CowError> {
+        let mut conn = self.pool.get().await?;
+        let server = to_decimal(server_id.0);
+        let res = conn.query(
+            "SELECT [user_id], SUM(CAST(gain AS BIGINT)) AS total FROM [Ranking].[XpEvent] WHERE server_id = @P1 AND created_at >= @P2 GROUP BY [user_id] ORDER BY total DESC, [user_id]",
+            &[&server, &since])
+            .await?
+            .into_first_result()
+            .await?
+            .into_iter()
+            .map(|row| Ok(XpGain {
+                user: UserId::from(from_decimal(column(&row, 0)?)?),
+                gain: column(&row, 1)?
+            }))
+            .collect::<Result<Vec<_>, CowError>>()?;
+
+        Ok(res)
+    }
+
+    async fn get_rewards(&self, server_id: GuildId) -> Result<Vec<LevelReward>, CowError> {
+        let mut conn = self.pool.get().await?;
+        let server = to_decimal(server_id.0);
+        let res = conn.query(
+            "SELECT id, min_level, kind, channel_id, message, multiplier, minutes FROM [Ranking].[Reward] WHERE server_id = @P1 ORDER BY min_level, id",
+            &[&server])
+            .await?
+            .into_first_result()
+            .await?
+            .into_iter()
+            .map(|row| {
+                let kind = column::<&str>(&row, 2)?;
+                let message = nullable::<&str>(&row, 4)?.map(|m| m.to_string());
+                Ok(LevelReward {
+                    id: column(&row, 0)?,
+                    min_level: column(&row, 1)?,
+                    kind: RewardKind::from_columns(kind, nullable_id(&row, 3)?, message, nullable(&row, 5)?, nullable(&row, 6)?)
+                        .ok_or_else(|| CowError::decode(format!("{} is not a valid reward", kind)))?
+                })
+            })
+            .collect::<Result<Vec<_>, CowError>>()?;
+
+        Ok(res)
+    }
+
+    async fn add_reward(&self, server_id: GuildId, min_level: i32, kind: &RewardKind) -> Result<i32, CowError> {
+        let mut conn = self.pool.get().await?;
+        let server = to_decimal(server_id.0);
+        let (channel, message, multiplier, minutes) = match kind {
+            RewardKind::DirectMessage(message) => (None, Some(message.as_str()), None, None),
+            RewardKind::ChannelPost { channel, message } => (Some(to_decimal(channel.0)), Some(message.as_str()), None, None),
+            RewardKind::XpBoost { multiplier, minutes } => (None, None, Some(*multiplier), Some(*minutes))
+        };
+        let res = conn.query(
+            "INSERT INTO [Ranking].[Reward] (server_id, min_level, kind, channel_id, message, multiplier, minutes) OUTPUT INSERTED.id VALUES (@P1, @P2, @P3, @P4, @P5, @P6, @P7)",
+            &[&server, &min_level, &kind.name(), &channel, &message, &multiplier, &minutes])
+            .await?
+            .into_row()
+            .await?;
+
+        match res {
+            Some(row) => column(&row, 0),
+            None => Err(CowError::decode("missing the new reward's ID"))
+        }
+    }
+
+    async fn remove_reward(&self, server_id: GuildId, id: i32) -> Result<bool, CowError> {
+        let mut conn = self.pool.get().await?;
+        let server = to_decimal(server_id.0);
+        let res = conn.execute(
+            "DELETE FROM [Ranking].[Reward] WHERE server_id = @P1 AND id = @P2",
+            &[&server, &id])
+            .await?;
+
+        Ok(res.total() > 0)
+    }
+
+    async fn get_boost(&self, server_id: GuildId, user_id: UserId) -> Result<Option<XpBoost>, CowError> {
+        let mut conn = self.pool.get().await?;
+        let server = to_decimal(server_id.0);
+        let user = to_decimal(user_id.0);
+        let res = conn.query(
+            "SELECT multiplier, expires_at FROM [Ranking].[Boost] WHERE server_id = @P1 AND [user_id] = @P2 AND expires_at > SYSUTCDATETIME()",
+            &[&server, &user])
+            .await?
+            .into_row()
+            .await?;
+
+        let mut out = None;
+
+        if let Some(row) = res {
+            out = Some(XpBoost {
+                multiplier: column(&row, 0)?,
+                expires_at: column(&row, 1)?
+            });
+        }
+
+        Ok(out)
+    }
+
+    async fn set_boost(&self, server_id: GuildId, user_id: UserId, boost: &XpBoost) -> Result<(), CowError> {
+        let mut conn = self.pool.get().await?;
+        let server = to_decimal(server_id.0);
+        let user = to_decimal(user_id.0);
+        conn.execute(
+            "UPDATE [Ranking].[Boost] SET multiplier = @P3, expires_at = @P4 WHERE server_id = @P1 AND [user_id] = @P2; \
+            IF @@ROWCOUNT = 0 INSERT INTO [Ranking].[Boost] (server_id, [user_id], multiplier, expires_at) VALUES (@P1, @P2, @P3, @P4)",
+            &[&server, &user, &boost.multiplier, &boost.expires_at])
+            .await?;
+
+        Ok(())
+    }
+
+    async fn record_fix(&self, server_id: GuildId, created_at: NaiveDateTime, changes: &[RoleChange]) -> Result<i32, CowError> {
+        let mut conn = self.pool.get().await?;
+        let server = to_decimal(server_id.0);
+
+        // All or nothing, since undo puts back whatever is stored here.
+        conn.simple_query("SET XACT_ABORT ON; BEGIN TRAN").await?.into_results().await?;
+
+        let res = async {
+            let res = conn.query(
+                "DELETE FROM [Ranking].[RoleFix] WHERE server_id = @P1; \
+                INSERT INTO [Ranking].[RoleFix] (server_id, created_at) OUTPUT INSERTED.id VALUES (@P1, @P2)",
+                &[&server, &created_at])
+                .await?
+                .into_row()
+                .await?;
+
+            let id: i32 = match res {
+                Some(row) => column(&row, 0)?,
+                None => return Err(CowError::decode("missing the new fix's ID"))
+            };
+
+            for chunk in changes.chunks(IMPORT_BATCH_SIZE) {
+                let ids = chunk.iter().map(|c| (to_decimal(c.user.0), to_decimal(c.role.0))).collect::<Vec<_>>();
+                let mut params: Vec<&dyn ToSql> = vec![&id];
+                let mut rows = Vec::with_capacity(chunk.len());
+                for (i, ((user, role), change)) in ids.iter().zip(chunk).enumerate() {
+                    params.push(user);
+                    params.push(role);
+                    params.push(&change.added);
+                    rows.push(format!("(@P1, @P{}, @P{}, @P{})", i * 3 + 2, i * 3 + 3, i * 3 + 4));
+                }
+
+                conn.execute(
+                    format!("INSERT INTO [Ranking].[RoleFixChange] (fix_id, [user_id], role_id, added) VALUES {}", rows.join(", ")),
+                    &params)
+                    .await?;
+            }
+
+            Ok(id)
+        }.await;
+
+        match res {
+            Ok(id) => {
+                conn.simple_query("COMMIT").await?.into_results().await?;
+                Ok(id)
+            },
+            Err(ex) => {
+                // XACT_ABORT has already rolled back after a server error; this covers everything else.
+                if let Ok(stream) = conn.simple_query("IF @@TRANCOUNT > 0 ROLLBACK").await {
+                    let _ = stream.into_results().await;
+                }
+                Err(ex)
+            }
+        }
+    }
+
+    async fn last_fix(&self, server_id: GuildId) -> Result<Option<FixChangeset>, CowError> {
+        let mut conn = self.pool.get().await?;
+        let server = to_decimal(server_id.0);
+        let res = conn.query(
+            "SELECT TOP 1 id, created_at FROM [Ranking].[RoleFix] WHERE server_id = @P1 ORDER BY id DESC",
+            &[&server])
+            .await?
+            .into_row()
+            .await?;
+
+        let (id, created_at): (i32, NaiveDateTime) = match res {
+            Some(row) => (column(&row, 0)?, column(&row, 1)?),
+            None => return Ok(None)
+        };
+
+        let changes = conn.query(
+            "SELECT [user_id], role_id, added FROM [Ranking].[RoleFixChange] WHERE fix_id = @P1",
+            &[&id])
+            .await?
+            .into_first_result()
+            .await?
+            .into_iter()
+            .map(|row| Ok(RoleChange {
+                user: UserId::from(from_decimal(column(&row, 0)?)?),
+                role: RoleId::from(from_decimal(column(&row, 1)?)?),
+                added: column(&row, 2)?
+            }))
+            .collect::<Result<Vec<_>, CowError>>()?;
+
+        Ok(Some(FixChangeset { id, created_at, changes }))
+    }
+
+    async fn remove_fix(&self, server_id: GuildId, id: i32) -> Result<bool, CowError> {
+        let mut conn = self.pool.get().await?;
+        let server = to_decimal(server_id.0);
+        let res = conn.execute(
+            "DELETE FROM [Ranking].[RoleFix] WHERE server_id = @P1 AND id = @P2",
+            &[&server, &id])
+            .await?;
+
+        Ok(res.total() > 0)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,569 @@
+use std::sync::{Mutex, MutexGuard};
+use async_trait::async_trait;
+use chrono::{NaiveDateTime, Utc};
+use rusqlite::{Connection, OptionalExtension, params};
+use serenity::{
+    model::id::{
+        UserId,
+        GuildId,
+        ChannelId, RoleId
+    }
+};
+use crate::models::db_models::*;
+use crate::models::error::CowError;
+use crate::models::rewards::{LevelReward, RewardKind, XpBoost};
+use crate::models::xp_settings::XpSettings;
+use crate::services::database::{RankingStore, migrations};
+
+pub struct SqliteDatabase {
+    conn: Mutex<Connection>
+}
+
+impl SqliteDatabase {
+    pub fn new(path: &str) -> Result<Self, CowError> {
+        let conn = Connection::open(path)?;
+        conn.pragma_update(None, "foreign_keys", true)?;
+
+        Ok(SqliteDatabase { conn: Mutex::new(conn) })
+    }
+
+    pub fn migrate(&self) -> Result<Vec<i32>, CowError> {
+        Ok(migrations::migrate_sqlite(&mut self.conn())?)
+    }
+
+    // A panic while holding the lock doesn't leave SQLite in a bad state, so just take it back.
+    pub(crate) fn conn(&self) -> MutexGuard<'_, Connection> {
+        self.conn.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+// Snowflakes fit in a signed 64-bit integer, which is all SQLite has.
+pub(crate) fn to_sql_id(id: u64) -> i64 {
+    id as i64
+}
+
+pub(crate) fn from_sql_id(id: i64) -> u64 {
+    id as u64
+}
+
+fn highest_role(conn: &Connection, server: i64, level: i32) -> Result<Option<u64>, rusqlite::Error> {
+    let role: Option<Option<i64>> = conn.query_row(
+        "SELECT role_id FROM ranking_role WHERE server_id = ?1 AND min_level <= ?2 ORDER BY min_level DESC LIMIT 1",
+        params![server, level],
+        |row| row.get(0))
+        .optional()?;
+
+    Ok(role.flatten().map(from_sql_id))
+}
+
+// Adds experience and works out the new level and rank, creating the rows if they aren't there yet.
+fn give_exp(conn: &Connection, server: i64, user: i64, gain: i32, settings: &XpSettings) -> Result<LevelUp, rusqlite::Error> {
+    conn.execute("INSERT OR IGNORE INTO ranking_server (id) VALUES (?1)", params![server])?;
+    conn.execute("INSERT OR IGNORE INTO ranking_level (server_id, user_id) VALUES (?1, ?2)", params![server, user])?;
+
+    let (xp, level): (i32, i32) = conn.query_row(
+        "SELECT xp, level FROM ranking_level WHERE server_id = ?1 AND user_id = ?2",
+        params![server, user],
+        |row| Ok((row.get(0)?, row.get(1)?)))?;
+
+    let old_level = level;
+    let mut out = LevelUp {
+        level: -1,
+        old_level,
+        old_rank: None,
+        new_rank: None
+    };
+
+    let (xp, level) = settings.apply(xp, level, gain);
+
+    conn.execute(
+        "UPDATE ranking_level SET xp = ?3, level = ?4 WHERE server_id = ?1 AND user_id = ?2",
+        params![server, user, xp, level])?;
+    if gain > 0 {
+        conn.execute("INSERT INTO ranking_xp_event (server_id, user_id, gain) VALUES (?1, ?2, ?3)", params![server, user, gain])?;
+    }
+
+    if level != old_level {
+        out.level = level;
+
+        let old_rank = highest_role(conn, server, old_level)?;
+        let new_rank = highest_role(conn, server, level)?;
+        if old_rank != new_rank {
+            out.old_rank = old_rank;
+            out.new_rank = new_rank;
+        }
+    }
+
+    Ok(out)
+}
+
+#[async_trait]
+impl RankingStore for SqliteDatabase {
+    // Replaces [Ranking].[ProvideExp].
+    async fn provide_exp(&self, server_id: GuildId, user_id: UserId, gain: i32, settings: &XpSettings) -> Result<LevelUp, CowError> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let server = to_sql_id(server_id.0);
+        let user = to_sql_id(user_id.0);
+
+        let timeout: Option<i64> = tx.query_row("SELECT timeout FROM ranking_server WHERE id = ?1", params![server], |row| row.get(0)).optional()?;
+        let last_xp: Option<i64> = tx.query_row(
+            "SELECT last_xp FROM ranking_level WHERE server_id = ?1 AND user_id = ?2",
+            params![server, user],
+            |row| row.get(0))
+            .optional()?;
+
+        let now = Utc::now().timestamp_millis();
+        if let (Some(timeout), Some(last_xp)) = (timeout, last_xp) {
+            if now - last_xp < timeout {
+                return Ok(LevelUp { level: -1, old_level: -1, old_rank: None, new_rank: None });
+            }
+        }
+
+        let out = give_exp(&tx, server, user, gain, settings)?;
+        tx.execute("UPDATE ranking_level SET last_xp = ?3 WHERE server_id = ?1 AND user_id = ?2", params![server, user, now])?;
+        tx.commit()?;
+
+        Ok(out)
+    }
+
+    // Replaces [Ranking].[AddExp].
+    async fn add_exp(&self, server_id: GuildId, user_id: UserId, gain: i32, settings: &XpSettings) -> Result<LevelUp, CowError> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let out = give_exp(&tx, to_sql_id(server_id.0), to_sql_id(user_id.0), gain, settings)?;
+        tx.commit()?;
+
+        Ok(out)
+    }
+
+    async fn get_xp(&self, server_id: GuildId, user_id: UserId) -> Result<Experience, CowError> {
+        Ok(self.find_xp(server_id, user_id).await?.unwrap_or_else(Experience::new))
+    }
+
+    async fn find_xp(&self, server_id: GuildId, user_id: UserId) -> Result<Option<Experience>, CowError> {
+        let conn = self.conn();
+        let res = conn.query_row(
+            "SELECT xp, level FROM ranking_level WHERE server_id = ?1 AND user_id = ?2",
+            params![to_sql_id(server_id.0), to_sql_id(user_id.0)],
+            |row| Ok(Experience {
+                xp: row.get(0)?,
+                level: row.get(1)?
+            }))
+            .optional()?;
+
+        Ok(res)
+    }
+
+    async fn get_highest_role(&self, server_id: GuildId, level: i32) -> Result<Option<RoleId>, CowError> {
+        let conn = self.conn();
+        Ok(highest_role(&conn, to_sql_id(server_id.0), level)?.map(RoleId::from))
+    }
+
+    // Replaces [Ranking].[ToggleChannel].
+    async fn toggle_channel_xp(&self, server_id: GuildId, channel_id: ChannelId) -> Result<bool, CowError> {
+        let conn = self.conn();
+        let server = to_sql_id(server_id.0);
+        let channel = to_sql_id(channel_id.0);
+
+        let removed = conn.execute(
+            "DELETE FROM ranking_disabled_channel WHERE server_id = ?1 AND channel_id = ?2",
+            params![server, channel])?;
+
+        if removed > 0 {
+            return Ok(false);
+        }
+
+        conn.execute(
+            "INSERT INTO ranking_disabled_channel (server_id, channel_id) VALUES (?1, ?2)",
+            params![server, channel])?;
+
+        Ok(true)
+    }
+
+    async fn channel_disabled(&self, server_id: GuildId, channel_id: ChannelId) -> Result<bool, CowError> {
+        let conn = self.conn();
+        let res = conn.query_row(
+            "SELECT 1 FROM ranking_disabled_channel WHERE server_id = ?1 AND channel_id = ?2",
+            params![to_sql_id(server_id.0), to_sql_id(channel_id.0)],
+            |row| row.get::<_, i32>(0))
+            .optional()?;
+
+        Ok(res.is_some())
+    }
+
+    async fn top_members(&self, server_id: GuildId, page: i32) -> Result<MemberPagination, CowError> {
+        let conn = self.conn();
+        let server = to_sql_id(server_id.0);
+        const ROWS_FETCHED: i32 = 10;
+        let offset = (page * ROWS_FETCHED).max(0);
+
+        let count: i32 = conn.query_row("SELECT COUNT(1) FROM ranking_level WHERE server_id = ?1", params![server], |row| row.get(0))?;
+
+        let mut statement = conn.prepare(
+            "SELECT user_id, level, xp FROM ranking_level WHERE server_id = ?1 ORDER BY level DESC, xp DESC LIMIT ?2 OFFSET ?3")?;
+        let members = statement.query_map(params![server, ROWS_FETCHED, offset], |row| Ok(Member {
+                id: UserId::from(from_sql_id(row.get(0)?)),
+                exp: Experience {
+                    level: row.get(1)?,
+                    xp: row.get(2)?
+                }
+            }))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let pages = (count / ROWS_FETCHED) + ((count % ROWS_FETCHED != 0) as i32); // Divide, then round if not perfect division
+
+        Ok(MemberPagination {
+            members,
+            current_page: page,
+            last_page: pages
+        })
+    }
+
+    async fn rank_within_members(&self, server_id: GuildId, user_id: UserId) -> Result<Option<i64>, CowError> {
+        let conn = self.conn();
+        let res = conn.query_row(
+            "SELECT row_number FROM (SELECT user_id, ROW_NUMBER() OVER (ORDER BY level DESC, xp DESC) AS row_number FROM ranking_level WHERE server_id = ?1) mukyu WHERE user_id = ?2",
+            params![to_sql_id(server_id.0), to_sql_id(user_id.0)],
+            |row| row.get(0))
+            .optional()?;
+
+        Ok(res)
+    }
+
+    async fn get_roles(&self, server_id: GuildId) -> Result<Vec<Rank>, CowError> {
+        let conn = self.conn();
+        let mut statement = conn.prepare(
+            "SELECT role_name, role_id, min_level FROM ranking_role WHERE server_id = ?1 ORDER BY min_level ASC")?;
+        let res = statement.query_map(params![to_sql_id(server_id.0)], |row| Ok(Rank {
+                name: row.get(0)?,
+                role_id: row.get::<_, Option<i64>>(1)?.map(|o| RoleId::from(from_sql_id(o))),
+                min_level: row.get(2)?
+            }))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(res)
+    }
+
+    // Replaces [Ranking].[AddRole]; re-adding a role moves it to the new level.
+    async fn add_role(&self, server_id: GuildId, role_name: &str, role_id: RoleId, min_level: i32) -> Result<bool, CowError> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let server = to_sql_id(server_id.0);
+        let role = to_sql_id(role_id.0);
+
+        let duplicate = tx.query_row(
+            "SELECT 1 FROM ranking_role WHERE server_id = ?1 AND min_level = ?2",
+            params![server, min_level],
+            |row| row.get::<_, i32>(0))
+            .optional()?;
+
+        if duplicate.is_some() {
+            return Ok(false);
+        }
+
+        tx.execute("DELETE FROM ranking_role WHERE server_id = ?1 AND role_id = ?2", params![server, role])?;
+        tx.execute(
+            "INSERT INTO ranking_role (server_id, role_name, role_id, min_level) VALUES (?1, ?2, ?3, ?4)",
+            params![server, role_name, role, min_level])?;
+        tx.commit()?;
+
+        Ok(true)
+    }
+
+    // Replaces [Ranking].[RemoveRole].
+    async fn remove_role(&self, server_id: GuildId, role_id: RoleId) -> Result<bool, CowError> {
+        let conn = self.conn();
+        let removed = conn.execute(
+            "DELETE FROM ranking_role WHERE server_id = ?1 AND role_id = ?2",
+            params![to_sql_id(server_id.0), to_sql_id(role_id.0)])?;
+
+        Ok(removed > 0)
+    }
+
+    // Replaces [Ranking].[SetServerTimeout].
+    async fn set_timeout(&self, server_id: GuildId, timeout: i32) -> Result<bool, CowError> {
+        let conn = self.conn();
+        conn.execute(
+            "INSERT INTO ranking_server (id, timeout) VALUES (?1, ?2) ON CONFLICT (id) DO UPDATE SET timeout = excluded.timeout",
+            params![to_sql_id(server_id.0), timeout])?;
+
+        Ok(true)
+    }
+
+    async fn get_timeout(&self, server_id: GuildId) -> Result<i32, CowError> {
+        let conn = self.conn();
+        let res = conn.query_row(
+            "SELECT timeout FROM ranking_server WHERE id = ?1",
+            params![to_sql_id(server_id.0)],
+            |row| row.get(0))
+            .optional()?;
+
+        Ok(res.unwrap_or(-1))
+    }
+
+    // Replaces [Ranking].[GetAllUsers].
+    async fn get_users(&self, server_id: GuildId) -> Result<Vec<FullMember>, CowError> {
+        let conn = self.conn();
+        let mut statement = conn.prepare(
+            "SELECT user_id, level, xp, \
+                (SELECT role_id FROM ranking_role r WHERE r.server_id = l.server_id AND r.min_level <= l.level ORDER BY r.min_level DESC LIMIT 1) \
+            FROM ranking_level l WHERE server_id = ?1")?;
+        let res = statement.query_map(params![to_sql_id(server_id.0)], |row| Ok(FullMember {
+                user: UserId::from(from_sql_id(row.get(0)?)),
+                exp: Experience {
+                    level: row.get(1)?,
+                    xp: row.get(2)?
+                },
+                role_id: row.get::<_, Option<i64>>(3)?.map(|o| RoleId::from(from_sql_id(o)))
+            }))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(res)
+    }
+
+    async fn get_multipliers(&self, server_id: GuildId) -> Result<Vec<XpMultiplier>, CowError> {
+        let conn = self.conn();
+        let mut statement = conn.prepare(
+            "SELECT target_id, is_role, multiplier FROM ranking_multiplier WHERE server_id = ?1")?;
+        let res = statement.query_map(params![to_sql_id(server_id.0)], |row| {
+                let target = from_sql_id(row.get(0)?);
+                Ok(XpMultiplier {
+                    target: if row.get(1)? { MultiplierTarget::Role(RoleId(target)) } else { MultiplierTarget::Channel(ChannelId(target)) },
+                    multiplier: row.get(2)?
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(res)
+    }
+
+    async fn set_multiplier(&self, server_id: GuildId, multiplier: &XpMultiplier) -> Result<(), CowError> {
+        let conn = self.conn();
+        let (target, is_role) = match multiplier.target {
+            MultiplierTarget::Channel(channel) => (to_sql_id(channel.0), false),
+            MultiplierTarget::Role(role) => (to_sql_id(role.0), true)
+        };
+        conn.execute(
+            "INSERT INTO ranking_multiplier (server_id, target_id, is_role, multiplier) VALUES (?1, ?2, ?3, ?4) \
+            ON CONFLICT (server_id, target_id) DO UPDATE SET is_role = excluded.is_role, multiplier = excluded.multiplier",
+            params![to_sql_id(server_id.0), target, is_role, multiplier.multiplier])?;
+
+        Ok(())
+    }
+
+    async fn remove_multiplier(&self, server_id: GuildId, target: MultiplierTarget) -> Result<bool, CowError> {
+        let conn = self.conn();
+        let target = match target {
+            MultiplierTarget::Channel(channel) => channel.0,
+            MultiplierTarget::Role(role) => role.0
+        };
+        let removed = conn.execute(
+            "DELETE FROM ranking_multiplier WHERE server_id = ?1 AND target_id = ?2",
+            params![to_sql_id(server_id.0), to_sql_id(target)])?;
+
+        Ok(removed > 0)
+    }
+
+    async fn set_exp(&self, server_id: GuildId, user_id: UserId, xp: i32, level: i32) -> Result<(), CowError> {
+        let conn = self.conn();
+        conn.execute(
+            "INSERT INTO ranking_level (server_id, user_id, xp, level) VALUES (?1, ?2, ?3, ?4) \
+            ON CONFLICT (server_id, user_id) DO UPDATE SET xp = excluded.xp, level = excluded.level",
+            params![to_sql_id(server_id.0), to_sql_id(user_id.0), xp, level])?;
+
+        Ok(())
+    }
+
+    async fn reset_exp(&self, server_id: GuildId, user_id: Option<UserId>) -> Result<u64, CowError> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let server = to_sql_id(server_id.0);
+        let user = user_id.map(|u| to_sql_id(u.0));
+        tx.execute("DELETE FROM ranking_xp_event WHERE server_id = ?1 AND (?2 IS NULL OR user_id = ?2)", params![server, user])?;
+        let removed = tx.execute("DELETE FROM ranking_level WHERE server_id = ?1 AND (?2 IS NULL OR user_id = ?2)", params![server, user])?;
+        tx.commit()?;
+
+        Ok(removed as u64)
+    }
+
+    async fn import_exp(&self, server_id: GuildId, members: &[(UserId, Experience)]) -> Result<u64, CowError> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let server = to_sql_id(server_id.0);
+        let mut written = 0;
+        {
+            let mut statement = tx.prepare(
+                "INSERT INTO ranking_level (server_id, user_id, xp, level) VALUES (?1, ?2, ?3, ?4) \
+                ON CONFLICT (server_id, user_id) DO UPDATE SET xp = excluded.xp, level = excluded.level")?;
+            for (user, exp) in members {
+                written += statement.execute(params![server, to_sql_id(user.0), exp.xp, exp.level])? as u64;
+            }
+        }
+        tx.commit()?;
+
+        Ok(written)
+    }
+
+    async fn add_audit(&self, server_id: GuildId, entry: &AuditEntry) -> Result<(), CowError> {
+        let conn = self.conn();
+        conn.execute(
+            "INSERT INTO ranking_audit (server_id, moderator_id, user_id, action, detail) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![to_sql_id(server_id.0), to_sql_id(entry.moderator.0), entry.user.map(|u| to_sql_id(u.0)), entry.action, entry.detail])?;
+
+        Ok(())
+    }
+
+    async fn get_audit(&self, server_id: GuildId, limit: i32) -> Result<Vec<AuditEntry>, CowError> {
+        let conn = self.conn();
+        let mut statement = conn.prepare(
+            "SELECT moderator_id, user_id, action, detail, created_at FROM ranking_audit WHERE server_id = ?1 ORDER BY created_at DESC, id DESC LIMIT ?2")?;
+        let res = statement.query_map(params![to_sql_id(server_id.0), limit], |row| Ok(AuditEntry {
+                moderator: UserId::from(from_sql_id(row.get(0)?)),
+                user: row.get::<_, Option<i64>>(1)?.map(|u| UserId::from(from_sql_id(u))),
+                action: row.get(2)?,
+                detail: row.get(3)?,
+                created_at: row.get(4)?
+            }))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(res)
+    }
+
+    async fn top_gains(&self, server_id: GuildId, since: NaiveDateTime) -> Result<Vec<XpGain>, CowError> {
+        let conn = self.conn();
+        let mut statement = conn.prepare(
+            "SELECT user_id, SUM(gain) AS total FROM ranking_xp_event WHERE server_id = ?1 AND created_at >= ?2 GROUP BY user_id ORDER BY total DESC, user_id")?;
+        let res = statement.query_map(params![to_sql_id(server_id.0), since], |row| Ok(XpGain {
+                user: UserId::from(from_sql_id(row.get(0)?)),
+                gain: row.get(1)?
+            }))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(res)
+    }
+
+    async fn get_rewards(&self, server_id: GuildId) -> Result<Vec<LevelReward>, CowError> {
+        let conn = self.conn();
+        let mut statement = conn.prepare(
+            "SELECT id, min_level, kind, channel_id, message, multiplier, minutes FROM ranking_reward WHERE server_id = ?1 ORDER BY min_level, id")?;
+        let rows = statement.query_map(params![to_sql_id(server_id.0)], |row| Ok((
+                row.get::<_, i32>(0)?,
+                row.get::<_, i32>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, Option<i64>>(3)?.map(from_sql_id),
+                row.get::<_, Option<String>>(4)?,
+                row.get::<_, Option<f64>>(5)?,
+                row.get::<_, Option<i32>>(6)?
+            )))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        rows.into_iter()
+            .map(|(id, min_level, kind, channel, message, multiplier, minutes)| Ok(LevelReward {
+                id,
+                min_level,
+                kind: RewardKind::from_columns(&kind, channel, message, multiplier, minutes)
+                    .ok_or_else(|| CowError::decode(format!("{} is not a valid reward", kind)))?
+            }))
+            .collect()
+    }
+
+    async fn add_reward(&self, server_id: GuildId, min_level: i32, kind: &RewardKind) -> Result<i32, CowError> {
+        let conn = self.conn();
+        let (channel, message, multiplier, minutes) = match kind {
+            RewardKind::DirectMessage(message) => (None, Some(message.as_str()), None, None),
+            RewardKind::ChannelPost { channel, message } => (Some(to_sql_id(channel.0)), Some(message.as_str()), None, None),
+            RewardKind::XpBoost { multiplier, minutes } => (None, None, Some(*multiplier), Some(*minutes))
+        };
+        conn.execute(
+            "INSERT INTO ranking_reward (server_id, min_level, kind, channel_id, message, multiplier, minutes) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![to_sql_id(server_id.0), min_level, kind.name(), channel, message, multiplier, minutes])?;
+
+        Ok(conn.last_insert_rowid() as i32)
+    }
+
+    async fn remove_reward(&self, server_id: GuildId, id: i32) -> Result<bool, CowError> {
+        let conn = self.conn();
+        let removed = conn.execute(
+            "DELETE FROM ranking_reward WHERE server_id = ?1 AND id = ?2",
+            params![to_sql_id(server_id.0), id])?;
+
+        Ok(removed > 0)
+    }
+
+    async fn get_boost(&self, server_id: GuildId, user_id: UserId) -> Result<Option<XpBoost>, CowError> {
+        let conn = self.conn();
+        let res = conn.query_row(
+            "SELECT multiplier, expires_at FROM ranking_boost WHERE server_id = ?1 AND user_id = ?2 AND expires_at > ?3",
+            params![to_sql_id(server_id.0), to_sql_id(user_id.0), Utc::now().naive_utc()],
+            |row| Ok(XpBoost {
+                multiplier: row.get(0)?,
+                expires_at: row.get(1)?
+            }))
+            .optional()?;
+
+        Ok(res)
+    }
+
+    async fn set_boost(&self, server_id: GuildId, user_id: UserId, boost: &XpBoost) -> Result<(), CowError> {
+        let conn = self.conn();
+        conn.execute(
+            "INSERT INTO ranking_boost (server_id, user_id, multiplier, expires_at) VALUES (?1, ?2, ?3, ?4) \
+            ON CONFLICT (server_id, user_id) DO UPDATE SET multiplier = excluded.multiplier, expires_at = excluded.expires_at",
+            params![to_sql_id(server_id.0), to_sql_id(user_id.0), boost.multiplier, boost.expires_at])?;
+
+        Ok(())
+    }
+
+    async fn record_fix(&self, server_id: GuildId, created_at: NaiveDateTime, changes: &[RoleChange]) -> Result<i32, CowError> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let server = to_sql_id(server_id.0);
+        tx.execute("DELETE FROM ranking_role_fix WHERE server_id = ?1", params![server])?;
+        tx.execute("INSERT INTO ranking_role_fix (server_id, created_at) VALUES (?1, ?2)", params![server, created_at])?;
+        let id = tx.last_insert_rowid() as i32;
+        {
+            let mut statement = tx.prepare(
+                "INSERT INTO ranking_role_fix_change (fix_id, user_id, role_id, added) VALUES (?1, ?2, ?3, ?4)")?;
+            for change in changes {
+                statement.execute(params![id, to_sql_id(change.user.0), to_sql_id(change.role.0), change.added])?;
+            }
+        }
+        tx.commit()?;
+
+        Ok(id)
+    }
+
+    async fn last_fix(&self, server_id: GuildId) -> Result<Option<FixChangeset>, CowError> {
+        let conn = self.conn();
+        let fix = conn.query_row(
+            "SELECT id, created_at FROM ranking_role_fix WHERE server_id = ?1 ORDER BY id DESC LIMIT 1",
+            params![to_sql_id(server_id.0)],
+            |row| Ok((row.get::<_, i32>(0)?, row.get::<_, NaiveDateTime>(1)?)))
+            .optional()?;
+
+        let (id, created_at) = match fix {
+            Some(fix) => fix,
+            None => return Ok(None)
+        };
+
+        let mut statement = conn.prepare("SELECT user_id, role_id, added FROM ranking_role_fix_change WHERE fix_id = ?1")?;
+        let changes = statement.query_map(params![id], |row| Ok(RoleChange {
+                user: UserId::from(from_sql_id(row.get(0)?)),
+                role: RoleId::from(from_sql_id(row.get(1)?)),
+                added: row.get(2)?
+            }))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Some(FixChangeset { id, created_at, changes }))
+    }
+
+    async fn remove_fix(&self, server_id: GuildId, id: i32) -> Result<bool, CowError> {
+        let conn = self.conn();
+        let removed = conn.execute(
+            "DELETE FROM ranking_role_fix WHERE server_id = ?1 AND id = ?2",
+            params![to_sql_id(server_id.0), id])?;
+
+        Ok(removed > 0)
+    }
+}
@@ -0,0 +1,398 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use serenity::{
+    client::Context,
+    model::id::{ChannelId, GuildId},
+    prelude::TypeMapKey
+};
+use crate::commands::rank_config::role_sync::FixOptions;
+use crate::models::error::CowError;
+use crate::models::level_up::{self, LevelUpRoute, LevelUpSettings};
+use crate::models::rank_mode::RankMode;
+use crate::models::xp_settings::{QualitySettings, XpCurve, XpSettings};
+use crate::services::cache::{CacheStats, TtlCache};
+use crate::services::database::Storage;
+
+pub struct SettingDefinition {
+    pub key: &'static str,
+    pub description: &'static str,
+    // Used when a server hasn't set anything.
+    pub default: &'static str,
+    // Returns the cleaned up value, or why it isn't allowed.
+    pub validate: fn(&str) -> Result<String, String>
+}
+
+fn validate_prefix(value: &str) -> Result<String, String> {
+    let value = value.trim();
+    if value.is_empty() || value.chars().count() > 10 {
+        Err("The prefix must be between 1 and 10 characters.".to_string())
+    } else if value.chars().any(char::is_whitespace) {
+        Err("The prefix can't have spaces in it.".to_string())
+    } else {
+        Ok(value.to_string())
+    }
+}
+
+fn validate_xp_gain(value: &str) -> Result<String, String> {
+    match value.trim().parse::<i32>() {
+        Ok(xp) if (0..=1000).contains(&xp) => Ok(xp.to_string()),
+        _ => Err("The experience per message must be a whole number from 0 to 1000.".to_string())
+    }
+}
+
+fn validate_xp_curve(value: &str) -> Result<String, String> {
+    let curve = XpCurve::parse(value)?;
+    curve.validate()?;
+    Ok(curve.to_string())
+}
+
+fn validate_level_cap(value: &str) -> Result<String, String> {
+    match value.trim().parse::<i32>() {
+        Ok(cap) if cap >= 1 => Ok(cap.to_string()),
+        _ => Err("The level cap must be a whole number of at least 1. Reset it to remove the cap.".to_string())
+    }
+}
+
+fn validate_toggle(value: &str) -> Result<String, String> {
+    match value.trim().to_lowercase().as_str() {
+        "on" | "true" | "yes" | "enable" | "enabled" => Ok("on".to_string()),
+        "off" | "false" | "no" | "disable" | "disabled" => Ok("off".to_string()),
+        _ => Err("This should be on or off.".to_string())
+    }
+}
+
+const DEFAULT_VOICE_XP_RATE: &str = "5";
+
+fn validate_voice_rate(value: &str) -> Result<String, String> {
+    match value.trim().parse::<i32>() {
+        Ok(xp) if (0..=1000).contains(&xp) => Ok(xp.to_string()),
+        _ => Err("The experience per minute must be a whole number from 0 to 1000.".to_string())
+    }
+}
+
+fn validate_min_length(value: &str) -> Result<String, String> {
+    match value.trim().parse::<usize>() {
+        Ok(length) if length <= 2000 => Ok(length.to_string()),
+        _ => Err("The minimum length must be a whole number from 0 to 2000.".to_string())
+    }
+}
+
+fn validate_percent(value: &str) -> Result<String, String> {
+    match value.trim().trim_end_matches('%').parse::<u32>() {
+        Ok(percent) if percent <= 100 => Ok(percent.to_string()),
+        _ => Err("This should be a percentage from 0 to 100.".to_string())
+    }
+}
+
+fn validate_levelup_channel(value: &str) -> Result<String, String> {
+    LevelUpRoute::parse(value).map(|route| route.to_string())
+}
+
+fn validate_channel(value: &str) -> Result<String, String> {
+    value.trim().parse::<ChannelId>()
+        .map(|channel_id| channel_id.to_string())
+        .map_err(|_| "This should be a channel.".to_string())
+}
+
+fn validate_rank_mode(value: &str) -> Result<String, String> {
+    RankMode::parse(value).map(|mode| mode.to_string())
+}
+
+// Empty means only the trivial fixes, same as running fix with no options.
+fn validate_fix_options(value: &str) -> Result<String, String> {
+    let words = value.to_lowercase().split_whitespace().map(String::from).collect::<Vec<_>>();
+    match words.iter().find(|word| !["multiple", "remove", "demote"].contains(&word.as_str())) {
+        Some(word) => Err(format!("{} isn't a fix option. This should be any of multiple, remove and demote.", word)),
+        None => Ok(words.join(" "))
+    }
+}
+
+fn validate_min_level(value: &str) -> Result<String, String> {
+    match value.trim().parse::<i32>() {
+        Ok(level) if level >= 0 => Ok(level.to_string()),
+        _ => Err("The level must be a whole number of at least 0.".to_string())
+    }
+}
+
+pub static SETTINGS: &[SettingDefinition] = &[
+    SettingDefinition {
+        key: "prefix",
+        description: "The prefix for text commands in this server. Mentioning the bot always works.",
+        default: "",
+        validate: validate_prefix
+    },
+    SettingDefinition {
+        key: "xp_min",
+        description: "The least experience a message can give.",
+        default: "15",
+        validate: validate_xp_gain
+    },
+    SettingDefinition {
+        key: "xp_max",
+        description: "The most experience a message can give.",
+        default: "25",
+        validate: validate_xp_gain
+    },
+    SettingDefinition {
+        key: "xp_curve",
+        description: "How much experience each level takes: linear, quadratic, exponential or polynomial, followed by its coefficients. Preview it with `rankconfig xp`.",
+        default: "quadratic 5 50 100",
+        validate: validate_xp_curve
+    },
+    SettingDefinition {
+        key: "level_cap",
+        description: "The highest level anyone can reach.",
+        default: "",
+        validate: validate_level_cap
+    },
+    SettingDefinition {
+        key: "voice_xp",
+        description: "Whether time spent talking in voice channels gives experience.",
+        default: "off",
+        validate: validate_toggle
+    },
+    SettingDefinition {
+        key: "voice_xp_rate",
+        description: "How much experience each minute in a voice channel gives.",
+        default: DEFAULT_VOICE_XP_RATE,
+        validate: validate_voice_rate
+    },
+    SettingDefinition {
+        key: "xp_min_length",
+        description: "Messages shorter than this many characters don't give experience. 0 turns this off.",
+        default: "3",
+        validate: validate_min_length
+    },
+    SettingDefinition {
+        key: "xp_max_similarity",
+        description: "Messages at least this similar (in percent) to one of the author's last few don't give experience. 0 turns this off.",
+        default: "90",
+        validate: validate_percent
+    },
+    SettingDefinition {
+        key: "xp_max_emoji_ratio",
+        description: "Messages where more than this percent of the words are emoji or mentions don't give experience. 100 turns this off.",
+        default: "60",
+        validate: validate_percent
+    },
+    SettingDefinition {
+        key: "levelup_message",
+        description: "What to say when someone levels up. You can use {user}, {level}, {old_level} and {role}; sentences with {role} are left out when there's no new rank.",
+        default: level_up::DEFAULT_TEMPLATE,
+        validate: level_up::validate_template
+    },
+    SettingDefinition {
+        key: "levelup_channel",
+        description: "Where level-up messages go: same (where the experience came from), dm, none, or a channel.",
+        default: "same",
+        validate: validate_levelup_channel
+    },
+    SettingDefinition {
+        key: "levelup_min_level",
+        description: "Level-ups below this level aren't announced.",
+        default: "0",
+        validate: validate_min_level
+    },
+    SettingDefinition {
+        key: "rank_card",
+        description: "Whether the rank command shows a picture instead of an embed.",
+        default: "off",
+        validate: validate_toggle
+    },
+    SettingDefinition {
+        key: "rank_mode",
+        description: "Whether members keep only their highest rank role (exclusive) or every rank role they've reached (stacking). Run `rankconfig fix` after changing it.",
+        default: "exclusive",
+        validate: validate_rank_mode
+    },
+    SettingDefinition {
+        key: "weekly_top_channel",
+        description: "Where to post the week's top 10 every Monday. Reset it to stop posting.",
+        default: "",
+        validate: validate_channel
+    },
+    SettingDefinition {
+        key: "auto_fix",
+        description: "Whether rank roles are fixed in the background every few hours and after ranks are added or removed, like `rankconfig fix`.",
+        default: "off",
+        validate: validate_toggle
+    },
+    SettingDefinition {
+        key: "auto_fix_options",
+        description: "Which of multiple, remove and demote the background fix also handles, like the options to `rankconfig fix`.",
+        default: "",
+        validate: validate_fix_options
+    },
+    SettingDefinition {
+        key: "modlog_channel",
+        description: "Where to log role changes the bot makes on its own. Reset it to stop logging.",
+        default: "",
+        validate: validate_channel
+    }
+];
+
+pub fn find_setting(key: &str) -> Option<&'static SettingDefinition> {
+    SETTINGS.iter().find(|s| s.key.eq_ignore_ascii_case(key))
+}
+
+// Per-server key/value settings. Reads come from memory for a while once a server has been loaded, and writes go to the database first.
+pub struct GuildSettings {
+    db: Arc<dyn Storage>,
+    cache: TtlCache<GuildId, HashMap<String, String>>
+}
+
+impl TypeMapKey for GuildSettings {
+    type Value = Arc<GuildSettings>;
+}
+
+impl GuildSettings {
+    pub fn new(db: Arc<dyn Storage>) -> Self {
+        GuildSettings {
+            db,
+            cache: TtlCache::new("Server settings", Duration::from_secs(10 * 60))
+        }
+    }
+
+    pub async fn from_context(ctx: &Context) -> Arc<GuildSettings> {
+        let data = ctx.data.read().await;
+        data.get::<GuildSettings>().expect("Expected GuildSettings in TypeMap.").clone()
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.cache.stats()
+    }
+
+    async fn load(&self, server_id: GuildId) -> Result<HashMap<String, String>, CowError> {
+        if let Some(settings) = self.cache.get(&server_id) {
+            return Ok(settings);
+        }
+
+        let settings = self.db.get_guild_settings(server_id).await?.into_iter().collect::<HashMap<_, _>>();
+        self.cache.insert(server_id, settings.clone());
+
+        Ok(settings)
+    }
+
+    // None if the server never set it.
+    pub async fn get(&self, server_id: GuildId, key: &str) -> Result<Option<String>, CowError> {
+        Ok(self.load(server_id).await?.remove(key))
+    }
+
+    // Everything is validated when it's set, so anything that doesn't parse means the database was edited by hand.
+    pub async fn xp_settings(&self, server_id: GuildId) -> Result<XpSettings, CowError> {
+        let settings = self.load(server_id).await?;
+        let defaults = XpSettings::default();
+        let number = |key: &str| settings.get(key)
+            .map(|value| value.parse::<i32>().map_err(|_| CowError::decode(format!("{} is not a valid {}", value, key))))
+            .transpose();
+
+        Ok(XpSettings {
+            min_xp: number("xp_min")?.unwrap_or(defaults.min_xp),
+            max_xp: number("xp_max")?.unwrap_or(defaults.max_xp),
+            curve: match settings.get("xp_curve") {
+                Some(curve) => XpCurve::parse(curve).map_err(CowError::decode)?,
+                None => defaults.curve
+            },
+            level_cap: number("level_cap")?
+        })
+    }
+
+    pub async fn quality_settings(&self, server_id: GuildId) -> Result<QualitySettings, CowError> {
+        let settings = self.load(server_id).await?;
+        let defaults = QualitySettings::default();
+        let number = |key: &str| settings.get(key)
+            .map(|value| value.parse::<u32>().map_err(|_| CowError::decode(format!("{} is not a valid {}", value, key))))
+            .transpose();
+
+        Ok(QualitySettings {
+            min_length: number("xp_min_length")?.map(|length| length as usize).unwrap_or(defaults.min_length),
+            max_similarity: number("xp_max_similarity")?.unwrap_or(defaults.max_similarity),
+            max_emoji_ratio: number("xp_max_emoji_ratio")?.unwrap_or(defaults.max_emoji_ratio)
+        })
+    }
+
+    pub async fn level_up_settings(&self, server_id: GuildId) -> Result<LevelUpSettings, CowError> {
+        let settings = self.load(server_id).await?;
+        let defaults = LevelUpSettings::default();
+
+        Ok(LevelUpSettings {
+            template: settings.get("levelup_message").cloned().unwrap_or(defaults.template),
+            route: match settings.get("levelup_channel") {
+                Some(route) => LevelUpRoute::parse(route).map_err(CowError::decode)?,
+                None => defaults.route
+            },
+            min_level: match settings.get("levelup_min_level") {
+                Some(level) => level.parse::<i32>().map_err(|_| CowError::decode(format!("{} is not a valid levelup_min_level", level)))?,
+                None => defaults.min_level
+            }
+        })
+    }
+
+    // None if voice experience is turned off.
+    pub async fn voice_xp_rate(&self, server_id: GuildId) -> Result<Option<i32>, CowError> {
+        let settings = self.load(server_id).await?;
+        if settings.get("voice_xp").map(String::as_str) != Some("on") {
+            return Ok(None);
+        }
+
+        let rate = settings.get("voice_xp_rate").map(String::as_str).unwrap_or(DEFAULT_VOICE_XP_RATE);
+        rate.parse::<i32>().map(Some).map_err(|_| CowError::decode(format!("{} is not a valid voice_xp_rate", rate)))
+    }
+
+    pub async fn rank_card(&self, server_id: GuildId) -> Result<bool, CowError> {
+        Ok(self.load(server_id).await?.get("rank_card").map(String::as_str) == Some("on"))
+    }
+
+    pub async fn rank_mode(&self, server_id: GuildId) -> Result<RankMode, CowError> {
+        match self.load(server_id).await?.get("rank_mode") {
+            Some(mode) => RankMode::parse(mode).map_err(CowError::decode),
+            None => Ok(RankMode::default())
+        }
+    }
+
+    // None if the weekly top 10 isn't posted anywhere.
+    pub async fn weekly_top_channel(&self, server_id: GuildId) -> Result<Option<ChannelId>, CowError> {
+        self.load(server_id).await?.get("weekly_top_channel")
+            .map(|channel| channel.parse::<ChannelId>().map_err(|_| CowError::decode(format!("{} is not a valid weekly_top_channel", channel))))
+            .transpose()
+    }
+
+    // None if roles aren't fixed in the background.
+    pub async fn auto_fix(&self, server_id: GuildId) -> Result<Option<FixOptions>, CowError> {
+        let settings = self.load(server_id).await?;
+        if settings.get("auto_fix").map(String::as_str) != Some("on") {
+            return Ok(None);
+        }
+
+        Ok(Some(FixOptions::parse(settings.get("auto_fix_options").map(String::as_str).unwrap_or_default())))
+    }
+
+    pub async fn modlog_channel(&self, server_id: GuildId) -> Result<Option<ChannelId>, CowError> {
+        self.load(server_id).await?.get("modlog_channel")
+            .map(|channel| channel.parse::<ChannelId>().map_err(|_| CowError::decode(format!("{} is not a valid modlog_channel", channel))))
+            .transpose()
+    }
+
+    pub async fn set(&self, server_id: GuildId, key: &str, value: &str) -> Result<(), CowError> {
+        self.db.set_guild_setting(server_id, key, value).await?;
+
+        // Only touch servers that are already cached, otherwise the next read would think this is all they have.
+        self.cache.update(&server_id, |settings| {
+            settings.insert(key.to_string(), value.to_string());
+        });
+
+        Ok(())
+    }
+
+    // False if it was never set.
+    pub async fn reset(&self, server_id: GuildId, key: &str) -> Result<bool, CowError> {
+        let removed = self.db.remove_guild_setting(server_id, key).await?;
+
+        self.cache.update(&server_id, |settings| {
+            settings.remove(key);
+        });
+
+        Ok(removed)
+    }
+}
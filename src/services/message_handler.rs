@@ -1,9 +1,20 @@
 use serenity::{
     client::Context,
-    model::{channel::Message, id::{RoleId, GuildId}, guild::Member}
+    http::Http,
+    model::{channel::Message, id::{ChannelId, RoleId, GuildId, UserId}, guild::Member}
 };
 use log::error;
 use crate::{Database, db};
+use crate::commands::rank_config::role_sync::{expected_roles, role_levels};
+use crate::models::db_models::{LevelUp, XpMultiplier};
+use crate::models::error::CowError;
+use crate::models::level_up::{LevelUpRoute, LevelUpSettings};
+use crate::models::rank_mode::RankMode;
+use crate::services::cache::DbCache;
+use crate::services::database::Storage;
+use crate::services::guild_settings::GuildSettings;
+use crate::services::quality_filter::QualityFilter;
+use crate::services::rewards;
 
 pub async fn message(_: &Context, _msg: &Message) {
     // This is basically useless for most cases.
@@ -15,9 +26,10 @@ pub async fn non_command(ctx: &Context, msg: &Message) {
     }
 
     let db = db!(ctx);
+    let cache = DbCache::from_context(ctx).await;
 
     if let Some(server_id) = msg.guild_id {
-        match db.channel_disabled(server_id, msg.channel_id).await {
+        match cache.channel_disabled(server_id, msg.channel_id).await {
             Err(ex) => {
                 error!("Failed checking if the current channel was disabled: {}", ex);
             },
@@ -28,53 +40,193 @@ pub async fn non_command(ctx: &Context, msg: &Message) {
             }
         }
 
-        match db.provide_exp(server_id, msg.author.id).await {
+        let filter = QualityFilter::from_context(ctx).await;
+
+        // Most messages come in while the author is still on cooldown, so don't bother the database with those.
+        match cache.on_cooldown(server_id, msg.author.id).await {
+            Err(ex) => {
+                error!("Failed checking the xp cooldown: {}", ex);
+            },
+            Ok(result) => {
+                if result {
+                    // Still worth remembering, so the same thing can't be posted again once the cooldown is up.
+                    filter.remember(server_id, msg.author.id, &msg.content);
+                    return;
+                }
+            }
+        }
+
+        let guild_settings = GuildSettings::from_context(ctx).await;
+
+        match guild_settings.quality_settings(server_id).await {
+            Ok(quality) => {
+                // Attachments can carry a message on their own, so only look at the text if there is any.
+                if (msg.attachments.is_empty() || !msg.content.trim().is_empty())
+                    && filter.check(server_id, msg.author.id, &msg.content, &quality).is_some() {
+                    return;
+                }
+            },
+            Err(ex) => {
+                error!("Failed getting the message quality settings for {}: {}", server_id, ex);
+            }
+        }
+
+        let settings = match guild_settings.xp_settings(server_id).await {
+            Ok(settings) => settings,
+            Err(ex) => {
+                error!("Failed getting the xp settings for {}: {}", server_id, ex);
+                return;
+            }
+        };
+
+        let multiplier = match cache.get_multipliers(server_id).await {
+            Ok(multipliers) => {
+                let roles = msg.member.as_ref().map(|m| m.roles.as_slice()).unwrap_or(&[]);
+                XpMultiplier::combine(&multipliers, msg.channel_id, roles)
+            },
+            Err(ex) => {
+                error!("Failed getting the xp multipliers for {}: {}", server_id, ex);
+                1.0
+            }
+        };
+        let boost = cache.get_boost_multiplier(server_id, msg.author.id).await.unwrap_or_else(|ex| {
+            error!("Failed getting the xp boost for {} in {}: {}", msg.author.id, server_id, ex);
+            1.0
+        });
+        let gain = (settings.roll_gain() as f64 * multiplier * boost).round() as i32;
+
+        match db.provide_exp(server_id, msg.author.id, gain, &settings).await {
             Err(ex) => {
                 error!("Failed providing exp to user: {}", ex)
             },
             Ok(data) => {
-                if data.level < 0 {
-                    return;
+                // An old level of -1 means the database's own cooldown turned it down, so nothing was given.
+                if data.old_level != -1 {
+                    cache.exp_provided(server_id, msg.author.id);
                 }
+                if data.level >= 0 {
+                    let announcement = guild_settings.level_up_settings(server_id).await.unwrap_or_else(|ex| {
+                        error!("Failed getting the level-up settings for {}: {}", server_id, ex);
+                        LevelUpSettings::default()
+                    });
+                    let mode = guild_settings.rank_mode(server_id).await.unwrap_or_else(|ex| {
+                        error!("Failed getting the rank mode for {}: {}", server_id, ex);
+                        RankMode::default()
+                    });
+                    let ranks = rank_update(db.as_ref(), server_id, &data, mode).await;
+                    level_up(&ctx.http, server_id, msg.author.id, &data, Some(msg.channel_id), &announcement, &ranks).await;
+                    rewards::dispatch(&ctx.http, &cache, server_id, msg.author.id, &data).await;
+                }
+            }
+        }
+    }
+}
 
-                let mut content = format!("<@{}> leveled up from {} to {}.", msg.author.id.as_u64(), data.level - 1, data.level);
-                if let Some(new_rank_id) = data.new_rank {
-                    content += &*format!("\nYou are now a <@&{}>.", new_rank_id);
-
-                    let mut error = false;
-                    let guild = msg.guild(&ctx).await.unwrap();
-                    let mut member = guild.member(&ctx.http, msg.author.id).await.unwrap();
-
-                    if let Some(old_rank_id) = data.old_rank {
-                        let old_rank = RoleId::from(old_rank_id);
-                        if member.roles.contains(&old_rank) {
-                            // We know we're in a guild, so an error is probably an API issue.
-                            if let Err(ex) = member.remove_role(&ctx.http, old_rank).await {
-                                error = true;
-                                content += "\n(We failed to update your roles; maybe we don't have permission?)";
-                                error!("Failed to remove role from user: {}", ex);
-                            }
+// The rank roles a level-up changes. Nothing changes unless they crossed into a new rank.
+#[derive(Default)]
+pub struct RankUpdate {
+    pub add: Vec<RoleId>,
+    pub remove: Option<RoleId>
+}
+
+// When ranks stack, jumping past several at once adds every one of them, not just the newest.
+pub async fn rank_update(db: &dyn Storage, server_id: GuildId, data: &LevelUp, mode: RankMode) -> RankUpdate {
+    let new_rank = match data.new_rank {
+        Some(new_rank) if data.level >= 0 => RoleId::from(new_rank),
+        _ => return RankUpdate::default()
+    };
+
+    match mode {
+        RankMode::Exclusive => RankUpdate { add: vec![new_rank], remove: data.old_rank.map(RoleId::from) },
+        RankMode::Stacking => match role_levels(db, server_id).await {
+            Ok(role_levels) => RankUpdate { add: expected_roles(data.level, Some(new_rank), &role_levels, mode), remove: None },
+            Err(ex) => {
+                error!("Failed getting the ranks for {}, so only the newest one is added: {}", server_id, ex);
+                RankUpdate { add: vec![new_rank], remove: None }
+            }
+        }
+    }
+}
+
+// Changes the member's rank roles and announces it. Does nothing if they didn't level up.
+// The origin is where the experience came from, for servers that announce in the same channel.
+pub async fn level_up(http: &Http, server_id: GuildId, user_id: UserId, data: &LevelUp, origin: Option<ChannelId>, announcement: &LevelUpSettings, ranks: &RankUpdate) {
+    if data.level < 0 {
+        return;
+    }
+
+    let mut content = announcement.render(user_id, data);
+    if !ranks.add.is_empty() {
+        let mut error = false;
+        match server_id.member(http, user_id).await {
+            Ok(mut member) => {
+                if let Some(old_rank) = ranks.remove {
+                    if member.roles.contains(&old_rank) {
+                        // We know we're in a guild, so an error is probably an API issue.
+                        if let Err(ex) = member.remove_role(http, old_rank).await {
+                            error = true;
+                            content += "\n(We failed to update your roles; maybe we don't have permission?)";
+                            error!("Failed to remove role from user: {}", ex);
                         }
                     }
+                }
 
-                    if let Err(ex) = member.add_role(&ctx.http, RoleId::from(new_rank_id)).await {
+                let missing = ranks.add.iter().filter(|r| !member.roles.contains(r)).cloned().collect::<Vec<_>>();
+                if !missing.is_empty() {
+                    if let Err(ex) = member.add_roles(http, &missing).await {
                         if !error {
                             content += "\n(We failed to update your roles; maybe we don't have permission?)";
                         }
                         error!("Failed to add role to user: {}", ex);
                     }
                 }
-
-                if let Err(ex2) =
-                    msg.channel_id.send_message(&ctx.http, |m| m.embed(|e| e
-                        .title("Level Up!")
-                        .description(content)
-                    )).await {
-                        error!("Error sending level-up message: {}", ex2)
-                };
+            },
+            Err(ex) => {
+                content += "\n(We failed to update your roles; maybe we don't have permission?)";
+                error!("Failed to get the member to update roles for: {}", ex);
             }
         }
     }
+
+    if data.level < announcement.min_level {
+        return;
+    }
+
+    let channel = match announcement.route {
+        LevelUpRoute::SameChannel => origin,
+        LevelUpRoute::Channel(channel) => Some(channel),
+        LevelUpRoute::DirectMessage => match user_id.create_dm_channel(http).await {
+            Ok(dm) => Some(dm.id),
+            Err(ex) => {
+                error!("Failed to open a DM with {} for a level-up message: {}", user_id, ex);
+                None
+            }
+        },
+        LevelUpRoute::Nowhere => None
+    };
+
+    if let Some(channel) = channel {
+        if let Err(ex2) =
+            channel.send_message(http, |m| m.embed(|e| e
+                .title("Level Up!")
+                .description(content)
+            )).await {
+                error!("Error sending level-up message: {}", ex2)
+        };
+    }
+}
+
+// Whatever rank roles someone should have if they left and came back.
+async fn returning_roles(db: &dyn Storage, guild_id: GuildId, user_id: UserId, mode: RankMode) -> Result<Vec<RoleId>, CowError> {
+    let experience = db.get_xp(guild_id, user_id).await?;
+    match mode {
+        RankMode::Exclusive => Ok(db.get_highest_role(guild_id, experience.level).await?.into_iter().collect()),
+        RankMode::Stacking if experience.level > 0 => Ok(db.get_roles(guild_id).await?.into_iter()
+            .filter(|r| r.min_level <= experience.level)
+            .filter_map(|r| r.role_id)
+            .collect()),
+        RankMode::Stacking => Ok(Vec::new())
+    }
 }
 
 pub async fn on_join(ctx: &Context, guild_id: &GuildId, new_member: &Member) {
@@ -85,10 +237,21 @@ pub async fn on_join(ctx: &Context, guild_id: &GuildId, new_member: &Member) {
     let db = db!(ctx);
     let mut member = new_member.clone();
 
-    let experience = db.get_xp(*guild_id, member.user.id).await.unwrap();
-    let current_role = db.get_highest_role(*guild_id, experience.level).await.unwrap();
-    if let Some(current_role_id) = current_role {
-        if let Err(ex) = member.add_role(&ctx.http, current_role_id).await {
+    let mode = GuildSettings::from_context(ctx).await.rank_mode(*guild_id).await.unwrap_or_else(|ex| {
+        error!("Failed getting the rank mode for {}: {}", guild_id, ex);
+        RankMode::default()
+    });
+
+    let roles = match returning_roles(db.as_ref(), *guild_id, member.user.id, mode).await {
+        Ok(roles) => roles,
+        Err(ex) => {
+            error!("Failed to get the rank for a returning member in {}: {}", guild_id, ex);
+            return;
+        }
+    };
+
+    if !roles.is_empty() {
+        if let Err(ex) = member.add_roles(&ctx.http, &roles).await {
             error!("Failed to add role for server {}: {}", guild_id, ex);
             if let Err(ex2) = member.user.direct_message(&ctx.http, |m|
                 m.content("I tried to re-add your roles, but the server didn't let me. Sorry~")).await {
@@ -2,4 +2,11 @@ pub mod interaction_handler;
 pub mod message_handler;
 pub mod bot_init;
 pub mod database;
-pub mod cow_framework;
\ No newline at end of file
+pub mod cow_framework;
+pub mod guild_settings;
+pub mod cache;
+pub mod supervisor;
+pub mod voice_xp;
+pub mod quality_filter;
+pub mod rank_card;
+pub mod weekly_top;pub mod rewards;
@@ -0,0 +1,180 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use serenity::{
+    client::Context,
+    model::id::{GuildId, UserId},
+    prelude::TypeMapKey
+};
+use crate::models::xp_settings::QualitySettings;
+use crate::services::cache::TtlCache;
+
+// How many of someone's messages we keep around to compare new ones against.
+const HISTORY_SIZE: usize = 5;
+
+// One step of the filter in front of provide_exp. New checks just need adding to CHECKS.
+pub trait QualityCheck: Send + Sync {
+    fn name(&self) -> &'static str;
+    // Recent is oldest first, and doesn't include this message.
+    fn passes(&self, content: &str, recent: &VecDeque<String>, settings: &QualitySettings) -> bool;
+}
+
+struct MinLength;
+
+impl QualityCheck for MinLength {
+    fn name(&self) -> &'static str {
+        "Too short"
+    }
+
+    fn passes(&self, content: &str, _: &VecDeque<String>, settings: &QualitySettings) -> bool {
+        content.trim().chars().count() >= settings.min_length
+    }
+}
+
+struct Repetition;
+
+impl QualityCheck for Repetition {
+    fn name(&self) -> &'static str {
+        "Repeated"
+    }
+
+    fn passes(&self, content: &str, recent: &VecDeque<String>, settings: &QualitySettings) -> bool {
+        if settings.max_similarity == 0 {
+            return true;
+        }
+
+        let content = normalize(content);
+        recent.iter().all(|previous| similarity(&content, &normalize(previous)) * 100.0 < settings.max_similarity as f64)
+    }
+}
+
+struct EmojiRatio;
+
+impl QualityCheck for EmojiRatio {
+    fn name(&self) -> &'static str {
+        "Mostly emoji or mentions"
+    }
+
+    fn passes(&self, content: &str, _: &VecDeque<String>, settings: &QualitySettings) -> bool {
+        let (symbols, words) = count_symbols(content);
+        if symbols == 0 {
+            return true;
+        }
+
+        (symbols * 100) as f64 / ((symbols + words) as f64) <= settings.max_emoji_ratio as f64
+    }
+}
+
+pub static CHECKS: &[&dyn QualityCheck] = &[&MinLength, &Repetition, &EmojiRatio];
+
+fn normalize(content: &str) -> String {
+    content.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+// Sørensen–Dice over pairs of characters, which doesn't care much about small edits or where they are.
+fn similarity(a: &str, b: &str) -> f64 {
+    let a = a.chars().collect::<Vec<_>>();
+    let b = b.chars().collect::<Vec<_>>();
+    if a.len() < 2 || b.len() < 2 {
+        return if a == b { 1.0 } else { 0.0 };
+    }
+
+    let mut pairs: HashMap<(char, char), i32> = HashMap::new();
+    for pair in a.windows(2) {
+        *pairs.entry((pair[0], pair[1])).or_insert(0) += 1;
+    }
+
+    let mut shared = 0;
+    for pair in b.windows(2) {
+        if let Some(count) = pairs.get_mut(&(pair[0], pair[1])) {
+            if *count > 0 {
+                *count -= 1;
+                shared += 1;
+            }
+        }
+    }
+
+    (2 * shared) as f64 / (a.len() + b.len() - 2) as f64
+}
+
+fn is_emoji(c: char) -> bool {
+    matches!(c as u32, 0x1F000..=0x1FAFF | 0x2600..=0x27BF | 0x2B00..=0x2BFF)
+}
+
+// Mentions and custom emoji are markup like <@123> or <:cow:123>; everything else is counted by character.
+fn is_markup(token: &str) -> bool {
+    token.ends_with('>') && ["<@", "<#", "<:", "<a:"].iter().any(|start| token.starts_with(start))
+}
+
+// (emoji and mentions, words)
+fn count_symbols(content: &str) -> (usize, usize) {
+    content.split_whitespace().fold((0, 0), |(symbols, words), token| {
+        if is_markup(token) {
+            (symbols + 1, words)
+        } else if token.chars().any(char::is_alphanumeric) {
+            (symbols, words + 1)
+        } else {
+            (symbols + token.chars().filter(|c| is_emoji(*c)).count(), words)
+        }
+    })
+}
+
+// Remembers what people said recently and counts what got turned away, per server since the bot started.
+pub struct QualityFilter {
+    history: TtlCache<(GuildId, UserId), VecDeque<String>>,
+    rejected: Mutex<HashMap<GuildId, HashMap<&'static str, u64>>>
+}
+
+impl TypeMapKey for QualityFilter {
+    type Value = Arc<QualityFilter>;
+}
+
+impl Default for QualityFilter {
+    fn default() -> Self {
+        QualityFilter::new()
+    }
+}
+
+impl QualityFilter {
+    pub fn new() -> Self {
+        QualityFilter {
+            history: TtlCache::new("Recent messages", Duration::from_secs(60 * 60)),
+            rejected: Mutex::new(HashMap::new())
+        }
+    }
+
+    pub async fn from_context(ctx: &Context) -> Arc<QualityFilter> {
+        let data = ctx.data.read().await;
+        data.get::<QualityFilter>().expect("Expected QualityFilter in TypeMap.").clone()
+    }
+
+    pub fn remember(&self, server_id: GuildId, user_id: UserId, content: &str) {
+        let mut recent = self.history.get(&(server_id, user_id)).unwrap_or_default();
+        if recent.len() >= HISTORY_SIZE {
+            recent.pop_front();
+        }
+        recent.push_back(content.to_string());
+        self.history.insert((server_id, user_id), recent);
+    }
+
+    // The name of the first check the message failed, if any. The message is remembered either way.
+    pub fn check(&self, server_id: GuildId, user_id: UserId, content: &str, settings: &QualitySettings) -> Option<&'static str> {
+        let recent = self.history.get(&(server_id, user_id)).unwrap_or_default();
+        let failed = CHECKS.iter().find(|check| !check.passes(content, &recent, settings)).map(|check| check.name());
+        self.remember(server_id, user_id, content);
+
+        if let Some(name) = failed {
+            let mut rejected = self.rejected.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            *rejected.entry(server_id).or_default().entry(name).or_insert(0) += 1;
+        }
+
+        failed
+    }
+
+    pub fn rejections(&self, server_id: GuildId) -> HashMap<&'static str, u64> {
+        self.rejected.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+            .get(&server_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+}
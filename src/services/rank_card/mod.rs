@@ -0,0 +1,195 @@
+mod font;
+mod png;
+
+use std::io;
+use log::error;
+use serenity::model::user::User;
+use font::{glyph, text_width, GLYPH_HEIGHT, GLYPH_WIDTH, SPACING};
+pub use png::Image;
+
+const WIDTH: u32 = 640;
+const HEIGHT: u32 = 200;
+const AVATAR_SIZE: u32 = 128;
+const PADDING: u32 = 36;
+const TEXT_LEFT: u32 = PADDING + AVATAR_SIZE + 30;
+const TEXT_RIGHT: u32 = WIDTH - PADDING;
+const BAR_TOP: u32 = 130;
+const BAR_HEIGHT: u32 = 30;
+
+type Colour = (u8, u8, u8);
+
+const BACKGROUND: Colour = (35, 39, 42);
+const BAR_TRACK: Colour = (72, 75, 78);
+const TEXT: Colour = (255, 255, 255);
+const TEXT_MUTED: Colour = (185, 187, 190);
+// For people whose rank has no colour, or who have no rank.
+pub const DEFAULT_ACCENT: Colour = (88, 101, 242);
+
+struct Canvas {
+    image: Image
+}
+
+impl Canvas {
+    fn new(width: u32, height: u32, background: Colour) -> Self {
+        let mut image = Image::new(width, height);
+        for px in image.pixels.chunks_mut(4) {
+            px.copy_from_slice(&[background.0, background.1, background.2, 255]);
+        }
+        Canvas { image }
+    }
+
+    // Draws over what's there, with coverage from 0 (nothing) to 1 (solid).
+    fn blend(&mut self, x: i64, y: i64, colour: Colour, coverage: f64) {
+        if x < 0 || y < 0 || x >= self.image.width as i64 || y >= self.image.height as i64 || coverage <= 0.0 {
+            return;
+        }
+
+        let i = (y as usize * self.image.width as usize + x as usize) * 4;
+        let alpha = coverage.min(1.0);
+        for (channel, value) in [colour.0, colour.1, colour.2].iter().enumerate() {
+            let old = self.image.pixels[i + channel] as f64;
+            self.image.pixels[i + channel] = (old + (*value as f64 - old) * alpha).round() as u8;
+        }
+    }
+
+    // The corners are quarter circles, smoothed at the edges.
+    fn rounded_rect(&mut self, left: u32, top: u32, width: u32, height: u32, radius: f64, colour: Colour) {
+        let (left, top, right, bottom) = (left as f64, top as f64, (left + width) as f64, (top + height) as f64);
+        for y in top as i64..bottom as i64 {
+            for x in left as i64..right as i64 {
+                let (cx, cy) = (x as f64 + 0.5, y as f64 + 0.5);
+                let nearest_x = cx.clamp(left + radius, right - radius);
+                let nearest_y = cy.clamp(top + radius, bottom - radius);
+                let distance = ((cx - nearest_x).powi(2) + (cy - nearest_y).powi(2)).sqrt();
+                self.blend(x, y, colour, radius + 0.5 - distance);
+            }
+        }
+    }
+
+    fn text(&mut self, left: u32, top: u32, text: &str, scale: u32, colour: Colour) {
+        let mut x = left;
+        for c in text.chars() {
+            for (column, bits) in glyph(c).iter().enumerate() {
+                for row in 0..GLYPH_HEIGHT {
+                    if bits & (1 << row) == 0 {
+                        continue;
+                    }
+                    for dy in 0..scale {
+                        for dx in 0..scale {
+                            self.blend((x + column as u32 * scale + dx) as i64, (top + row * scale + dy) as i64, colour, 1.0);
+                        }
+                    }
+                }
+            }
+            x += (GLYPH_WIDTH + SPACING) * scale;
+        }
+    }
+
+    // Scaled to fit and cut into a circle.
+    fn avatar(&mut self, left: u32, top: u32, size: u32, avatar: &Image) {
+        let radius = size as f64 / 2.0;
+        for y in 0..size {
+            for x in 0..size {
+                let distance = ((x as f64 + 0.5 - radius).powi(2) + (y as f64 + 0.5 - radius).powi(2)).sqrt();
+                let edge = radius + 0.5 - distance;
+                if edge <= 0.0 {
+                    continue;
+                }
+
+                let px = avatar.pixel(x * avatar.width / size, y * avatar.height / size);
+                let coverage = edge.min(1.0) * px[3] as f64 / 255.0;
+                self.blend((left + x) as i64, (top + y) as i64, (px[0], px[1], px[2]), coverage);
+            }
+        }
+    }
+}
+
+// Cuts the text down until it fits, adding dots if anything was taken off.
+fn fit(text: &str, scale: u32, width: u32) -> String {
+    if text_width(text, scale) <= width {
+        return text.to_string();
+    }
+
+    let mut chars = text.chars().collect::<Vec<_>>();
+    while !chars.is_empty() && text_width(&format!("{}...", chars.iter().collect::<String>()), scale) > width {
+        chars.pop();
+    }
+    format!("{}...", chars.iter().collect::<String>())
+}
+
+pub struct RankCard {
+    pub username: String,
+    pub avatar: Option<Image>,
+    pub level: i32,
+    pub xp: i32,
+    // None at the level cap.
+    pub needed: Option<i32>,
+    pub rank: Option<i64>,
+    pub accent: Colour
+}
+
+impl RankCard {
+    // Everything is drawn here, so there's nothing to install and nothing to call out to.
+    pub fn render(&self) -> io::Result<Vec<u8>> {
+        let mut canvas = Canvas::new(WIDTH, HEIGHT, BACKGROUND);
+
+        match &self.avatar {
+            Some(avatar) => canvas.avatar(PADDING, PADDING, AVATAR_SIZE, avatar),
+            None => {
+                let radius = AVATAR_SIZE as f64 / 2.0;
+                canvas.rounded_rect(PADDING, PADDING, AVATAR_SIZE, AVATAR_SIZE, radius, self.accent);
+            }
+        }
+
+        // Rank and level go on top on the right, like the usual rank cards.
+        let level = format!("LEVEL {}", self.level);
+        let rank = self.rank.map(|r| format!("RANK #{}", r)).unwrap_or_else(|| "UNRANKED".to_string());
+        let level_left = TEXT_RIGHT - text_width(&level, 3);
+        canvas.text(level_left, 40, &level, 3, self.accent);
+        canvas.text(level_left.saturating_sub(24 + text_width(&rank, 3)), 40, &rank, 3, TEXT);
+
+        let progress = match self.needed {
+            Some(needed) => format!("{} / {} XP", self.xp, needed),
+            None => format!("{} XP (MAX)", self.xp)
+        };
+        let progress_width = text_width(&progress, 2);
+        canvas.text(TEXT_RIGHT - progress_width, 100, &progress, 2, TEXT_MUTED);
+
+        let name = fit(&self.username, 3, (TEXT_RIGHT - TEXT_LEFT).saturating_sub(progress_width + 16));
+        canvas.text(TEXT_LEFT, 93, &name, 3, TEXT);
+
+        let bar_width = TEXT_RIGHT - TEXT_LEFT;
+        let radius = BAR_HEIGHT as f64 / 2.0;
+        canvas.rounded_rect(TEXT_LEFT, BAR_TOP, bar_width, BAR_HEIGHT, radius, BAR_TRACK);
+        let fraction = match self.needed {
+            Some(needed) if needed > 0 => (self.xp as f64 / needed as f64).clamp(0.0, 1.0),
+            Some(_) => 0.0,
+            None => 1.0
+        };
+        // Too short a bar can't have round ends, so leave it out until it's at least a circle.
+        let filled = (bar_width as f64 * fraction).round() as u32;
+        if filled >= BAR_HEIGHT {
+            canvas.rounded_rect(TEXT_LEFT, BAR_TOP, filled, BAR_HEIGHT, radius, self.accent);
+        }
+
+        png::encode(&canvas.image)
+    }
+}
+
+// Always PNG, since that's all we can read. None if it couldn't be downloaded or read, and the card goes without.
+pub async fn fetch_avatar(user: &User) -> Option<Image> {
+    let url = match &user.avatar {
+        Some(hash) => format!("https://cdn.discordapp.com/avatars/{}/{}.png?size={}", user.id, hash, AVATAR_SIZE),
+        None => user.default_avatar_url()
+    };
+
+    let bytes = match reqwest::get(&url).await.and_then(|r| r.error_for_status()) {
+        Ok(response) => response.bytes().await.ok()?,
+        Err(ex) => {
+            error!("Failed to download the avatar of {}: {}", user.id, ex);
+            return None;
+        }
+    };
+
+    png::decode(&bytes)
+}
@@ -0,0 +1,162 @@
+use std::io::{self, Read, Write};
+use flate2::{read::ZlibDecoder, write::ZlibEncoder, Compression};
+
+const SIGNATURE: [u8; 8] = [137, 80, 78, 71, 13, 10, 26, 10];
+
+// Always 8-bit RGBA, row by row.
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>
+}
+
+impl Image {
+    pub fn new(width: u32, height: u32) -> Self {
+        Image { width, height, pixels: vec![0; width as usize * height as usize * 4] }
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let i = (y as usize * self.width as usize + x as usize) * 4;
+        [self.pixels[i], self.pixels[i + 1], self.pixels[i + 2], self.pixels[i + 3]]
+    }
+}
+
+fn write_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    let mut crc = crc32fast::Hasher::new();
+    crc.update(kind);
+    crc.update(data);
+
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    out.extend_from_slice(kind);
+    out.extend_from_slice(data);
+    out.extend_from_slice(&crc.finalize().to_be_bytes());
+}
+
+pub fn encode(image: &Image) -> io::Result<Vec<u8>> {
+    let stride = image.width as usize * 4;
+    let mut raw = Vec::with_capacity((stride + 1) * image.height as usize);
+    for row in image.pixels.chunks(stride) {
+        // No filtering; the cards are mostly flat colour, so it compresses fine without.
+        raw.push(0);
+        raw.extend_from_slice(row);
+    }
+
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&raw)?;
+    let data = encoder.finish()?;
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&image.width.to_be_bytes());
+    header.extend_from_slice(&image.height.to_be_bytes());
+    // 8 bits per channel, RGBA, default compression and filtering, not interlaced.
+    header.extend_from_slice(&[8, 6, 0, 0, 0]);
+
+    let mut out = SIGNATURE.to_vec();
+    write_chunk(&mut out, b"IHDR", &header);
+    write_chunk(&mut out, b"IDAT", &data);
+    write_chunk(&mut out, b"IEND", &[]);
+    Ok(out)
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let (pa, pb, pc) = ((p - a as i16).abs(), (p - b as i16).abs(), (p - c as i16).abs());
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+// Undoes the per-row filters in place, leaving just the pixel bytes.
+fn unfilter(data: &[u8], stride: usize, bpp: usize, height: usize) -> Option<Vec<u8>> {
+    let mut out = vec![0u8; stride * height];
+    for y in 0..height {
+        let filter = *data.get(y * (stride + 1))?;
+        let row = data.get(y * (stride + 1) + 1..(y + 1) * (stride + 1))?;
+        for x in 0..stride {
+            let a = if x >= bpp { out[y * stride + x - bpp] } else { 0 };
+            let b = if y > 0 { out[(y - 1) * stride + x] } else { 0 };
+            let c = if x >= bpp && y > 0 { out[(y - 1) * stride + x - bpp] } else { 0 };
+            out[y * stride + x] = row[x].wrapping_add(match filter {
+                0 => 0,
+                1 => a,
+                2 => b,
+                3 => ((a as u16 + b as u16) / 2) as u8,
+                4 => paeth(a, b, c),
+                _ => return None
+            });
+        }
+    }
+
+    Some(out)
+}
+
+// Enough to read Discord avatars: 8-bit, not interlaced, any colour type. None for anything else.
+pub fn decode(bytes: &[u8]) -> Option<Image> {
+    if bytes.get(..8)? != SIGNATURE {
+        return None;
+    }
+
+    let (mut width, mut height, mut color_type) = (0, 0, 0);
+    let (mut palette, mut transparency, mut data) = (Vec::new(), Vec::new(), Vec::new());
+    let mut rest = &bytes[8..];
+    while rest.len() >= 12 {
+        let length = u32::from_be_bytes(rest[..4].try_into().ok()?) as usize;
+        let kind = &rest[4..8];
+        let body = rest.get(8..8 + length)?;
+        match kind {
+            b"IHDR" => {
+                width = u32::from_be_bytes(body.get(..4)?.try_into().ok()?);
+                height = u32::from_be_bytes(body.get(4..8)?.try_into().ok()?);
+                let (depth, interlace) = (*body.get(8)?, *body.get(12)?);
+                color_type = *body.get(9)?;
+                if depth != 8 || interlace != 0 {
+                    return None;
+                }
+            },
+            b"PLTE" => palette = body.to_vec(),
+            b"tRNS" => transparency = body.to_vec(),
+            b"IDAT" => data.extend_from_slice(body),
+            b"IEND" => break,
+            _ => {}
+        }
+        rest = rest.get(12 + length..)?;
+    }
+
+    let channels = match color_type {
+        0 | 3 => 1,
+        2 => 3,
+        4 => 2,
+        6 => 4,
+        _ => return None
+    };
+    if width == 0 || height == 0 || width > 4096 || height > 4096 {
+        return None;
+    }
+
+    let mut inflated = Vec::new();
+    ZlibDecoder::new(data.as_slice()).read_to_end(&mut inflated).ok()?;
+    let stride = width as usize * channels;
+    let raw = unfilter(&inflated, stride, channels, height as usize)?;
+
+    let mut image = Image::new(width, height);
+    for (i, px) in raw.chunks(channels).enumerate() {
+        let rgba = match color_type {
+            0 => [px[0], px[0], px[0], 255],
+            2 => [px[0], px[1], px[2], 255],
+            3 => {
+                let index = px[0] as usize;
+                let colour = palette.get(index * 3..index * 3 + 3)?;
+                [colour[0], colour[1], colour[2], transparency.get(index).copied().unwrap_or(255)]
+            },
+            4 => [px[0], px[0], px[0], px[1]],
+            _ => [px[0], px[1], px[2], px[3]]
+        };
+        image.pixels[i * 4..i * 4 + 4].copy_from_slice(&rgba);
+    }
+
+    Some(image)
+}
@@ -0,0 +1,59 @@
+use chrono::{Duration, Utc};
+use log::error;
+use serenity::{
+    http::Http,
+    model::id::{GuildId, UserId}
+};
+use crate::models::db_models::LevelUp;
+use crate::models::level_up::fill;
+use crate::models::rewards::{LevelReward, RewardKind, XpBoost};
+use crate::services::cache::DbCache;
+
+// Hands out every reward for the levels they just passed, so skipping a level doesn't skip its reward.
+// Rewards are given in level order; a later boost replaces an earlier one.
+pub async fn dispatch(http: &Http, cache: &DbCache, server_id: GuildId, user_id: UserId, data: &LevelUp) {
+    if data.level < 0 {
+        return;
+    }
+
+    let rewards = match cache.get_rewards(server_id).await {
+        Ok(rewards) => rewards,
+        Err(ex) => {
+            error!("Failed getting the level rewards for {}: {}", server_id, ex);
+            return;
+        }
+    };
+
+    for reward in rewards.iter().filter(|r| data.old_level < r.min_level && r.min_level <= data.level) {
+        give(http, cache, server_id, user_id, data, reward).await;
+    }
+}
+
+async fn give(http: &Http, cache: &DbCache, server_id: GuildId, user_id: UserId, data: &LevelUp, reward: &LevelReward) {
+    match &reward.kind {
+        RewardKind::DirectMessage(message) => {
+            let content = fill(message, user_id, data);
+            match user_id.create_dm_channel(http).await {
+                Ok(dm) => if let Err(ex) = dm.send_message(http, |m| m.content(content)).await {
+                    error!("Failed sending reward {} to {}: {}", reward.id, user_id, ex);
+                },
+                Err(ex) => error!("Failed to open a DM with {} for reward {}: {}", user_id, reward.id, ex)
+            }
+        },
+        RewardKind::ChannelPost { channel, message } => {
+            let content = fill(message, user_id, data);
+            if let Err(ex) = channel.send_message(http, |m| m.content(content)).await {
+                error!("Failed posting reward {} in {}: {}", reward.id, channel, ex);
+            }
+        },
+        RewardKind::XpBoost { multiplier, minutes } => {
+            let boost = XpBoost {
+                multiplier: *multiplier,
+                expires_at: Utc::now().naive_utc() + Duration::minutes(*minutes as i64)
+            };
+            if let Err(ex) = cache.set_boost(server_id, user_id, &boost).await {
+                error!("Failed giving reward {} to {} in {}: {}", reward.id, user_id, server_id, ex);
+            }
+        }
+    }
+}
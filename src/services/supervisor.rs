@@ -0,0 +1,204 @@
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use log::{error, info, warn};
+use serenity::{
+    client::Context,
+    prelude::TypeMapKey
+};
+use tokio::sync::{watch, Notify};
+use tokio::task::{JoinError, JoinHandle};
+
+const MIN_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(5 * 60);
+// If a job stayed up this long, its next crash starts the backoff over instead of waiting even longer.
+const STABLE_AFTER: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Clone)]
+pub enum JobStatus {
+    Running,
+    Restarting { at: Instant },
+    Finished,
+    Stopped
+}
+
+#[derive(Clone)]
+pub struct JobHealth {
+    pub name: &'static str,
+    pub status: JobStatus,
+    pub restarts: u32,
+    pub last_panic: Option<String>
+}
+
+struct InFlight {
+    count: AtomicUsize,
+    idle: Notify
+}
+
+// Held while something that shouldn't be cut off halfway (like a cowboard post) is running, so shutdown can wait for it.
+pub struct WorkGuard(Arc<InFlight>);
+
+impl Drop for WorkGuard {
+    fn drop(&mut self) {
+        if self.0.count.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.0.idle.notify_waiters();
+        }
+    }
+}
+
+struct Job {
+    health: Arc<Mutex<JobHealth>>,
+    // Taken once the job is told to stop.
+    handle: Option<JoinHandle<()>>
+}
+
+// Owns the loops that run next to the bot, and brings them back if they panic.
+pub struct Supervisor {
+    jobs: Mutex<Vec<Job>>,
+    shutdown: watch::Sender<bool>,
+    in_flight: Arc<InFlight>
+}
+
+impl TypeMapKey for Supervisor {
+    type Value = Arc<Supervisor>;
+}
+
+impl Default for Supervisor {
+    fn default() -> Self {
+        Supervisor::new()
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn panic_message(ex: JoinError) -> String {
+    if ex.is_cancelled() {
+        return "cancelled".to_string();
+    }
+
+    let payload = ex.into_panic();
+    payload.downcast_ref::<&str>().map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "<unknown panic>".to_string())
+}
+
+impl Supervisor {
+    pub fn new() -> Self {
+        Supervisor {
+            jobs: Mutex::new(Vec::new()),
+            shutdown: watch::channel(false).0,
+            in_flight: Arc::new(InFlight { count: AtomicUsize::new(0), idle: Notify::new() })
+        }
+    }
+
+    pub async fn from_context(ctx: &Context) -> Arc<Supervisor> {
+        let data = ctx.data.read().await;
+        data.get::<Supervisor>().expect("Expected Supervisor in TypeMap.").clone()
+    }
+
+    // The job is started again from scratch after a panic, so it gets a fresh future each time.
+    pub fn spawn<F, Fut>(&self, name: &'static str, job: F)
+        where F: Fn() -> Fut + Send + Sync + 'static, Fut: Future<Output = ()> + Send + 'static {
+        let health = Arc::new(Mutex::new(JobHealth { name, status: JobStatus::Running, restarts: 0, last_panic: None }));
+        let state = health.clone();
+        let mut shutdown = self.shutdown.subscribe();
+
+        let handle = tokio::spawn(async move {
+            let mut backoff = MIN_BACKOFF;
+
+            loop {
+                lock(&state).status = JobStatus::Running;
+                let started = Instant::now();
+                let mut task = tokio::spawn(job());
+
+                let result = tokio::select! {
+                    result = &mut task => result,
+                    _ = shutdown.changed() => {
+                        task.abort();
+                        lock(&state).status = JobStatus::Stopped;
+                        return;
+                    }
+                };
+
+                let ex = match result {
+                    Ok(()) => {
+                        info!("Background job {} finished.", name);
+                        lock(&state).status = JobStatus::Finished;
+                        return;
+                    },
+                    Err(ex) => panic_message(ex)
+                };
+
+                if started.elapsed() >= STABLE_AFTER {
+                    backoff = MIN_BACKOFF;
+                }
+
+                error!("Background job {} panicked, restarting in {}s: {}", name, backoff.as_secs(), ex);
+                {
+                    let mut health = lock(&state);
+                    health.restarts += 1;
+                    health.last_panic = Some(ex);
+                    health.status = JobStatus::Restarting { at: Instant::now() + backoff };
+                }
+
+                tokio::select! {
+                    _ = tokio::time::sleep(backoff) => {},
+                    _ = shutdown.changed() => {
+                        lock(&state).status = JobStatus::Stopped;
+                        return;
+                    }
+                }
+
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+            }
+        });
+
+        lock(&self.jobs).push(Job { health, handle: Some(handle) });
+    }
+
+    pub fn health(&self) -> Vec<JobHealth> {
+        lock(&self.jobs).iter()
+            .map(|job| lock(&job.health).clone())
+            .collect()
+    }
+
+    pub fn begin_work(&self) -> WorkGuard {
+        self.in_flight.count.fetch_add(1, Ordering::SeqCst);
+        WorkGuard(self.in_flight.clone())
+    }
+
+    // Safe to call more than once; only the first call has anything to wait on.
+    pub async fn stop_jobs(&self) {
+        self.shutdown.send_replace(true);
+
+        let handles = lock(&self.jobs).iter_mut()
+            .filter_map(|job| Some((lock(&job.health).name, job.handle.take()?)))
+            .collect::<Vec<_>>();
+
+        for (name, handle) in handles {
+            if let Err(ex) = handle.await {
+                error!("Background job {} didn't stop cleanly: {}", name, ex);
+            }
+        }
+    }
+
+    // Waits for anything holding a WorkGuard, up to the timeout.
+    pub async fn drain(&self, timeout: Duration) {
+        let waiting = async {
+            loop {
+                let idle = self.in_flight.idle.notified();
+                if self.in_flight.count.load(Ordering::SeqCst) == 0 {
+                    return;
+                }
+                idle.await;
+            }
+        };
+
+        if tokio::time::timeout(timeout, waiting).await.is_err() {
+            warn!("Gave up waiting on {} unfinished task(s).", self.in_flight.count.load(Ordering::SeqCst));
+        }
+    }
+}
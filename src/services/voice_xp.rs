@@ -0,0 +1,236 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use log::error;
+use serenity::{
+    CacheAndHttp,
+    client::Context,
+    model::{
+        guild::Guild,
+        id::{ChannelId, GuildId, UserId},
+        voice::VoiceState
+    },
+    prelude::{RwLock, TypeMap, TypeMapKey}
+};
+use crate::Database;
+use crate::models::db_models::XpMultiplier;
+use crate::models::level_up::LevelUpSettings;
+use crate::models::rank_mode::RankMode;
+use crate::services::cache::DbCache;
+use crate::services::guild_settings::GuildSettings;
+use crate::services::message_handler::{level_up, rank_update};
+use crate::services::rewards;
+
+const CREDIT_INTERVAL: Duration = Duration::from_secs(60);
+
+struct VoiceSession {
+    channel_id: ChannelId,
+    // Only time since this counts; it moves forward whenever we pay out or they aren't eligible.
+    since: Instant
+}
+
+// Who is in which voice channel, from voice state updates. Whether they should get anything for it is decided
+// when experience is handed out, since that depends on everyone else in the channel too.
+#[derive(Default)]
+pub struct VoiceTracker {
+    sessions: Mutex<HashMap<(GuildId, UserId), VoiceSession>>
+}
+
+impl TypeMapKey for VoiceTracker {
+    type Value = Arc<VoiceTracker>;
+}
+
+impl VoiceTracker {
+    pub fn new() -> Self {
+        VoiceTracker::default()
+    }
+
+    pub async fn from_context(ctx: &Context) -> Arc<VoiceTracker> {
+        let data = ctx.data.read().await;
+        data.get::<VoiceTracker>().expect("Expected VoiceTracker in TypeMap.").clone()
+    }
+
+    fn sessions(&self) -> std::sync::MutexGuard<'_, HashMap<(GuildId, UserId), VoiceSession>> {
+        self.sessions.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    pub fn update(&self, guild_id: GuildId, state: &VoiceState) {
+        if is_bot(state) {
+            return;
+        }
+
+        let mut sessions = self.sessions();
+        match state.channel_id {
+            Some(channel_id) => {
+                // Muting or deafening also comes through here, so only start over when they actually move.
+                let session = sessions.entry((guild_id, state.user_id)).or_insert(VoiceSession { channel_id, since: Instant::now() });
+                if session.channel_id != channel_id {
+                    *session = VoiceSession { channel_id, since: Instant::now() };
+                }
+            },
+            None => {
+                sessions.remove(&(guild_id, state.user_id));
+            }
+        }
+    }
+
+    // Picks up anyone who was already in voice when we connected.
+    pub fn add_guild(&self, guild: &Guild) {
+        for state in guild.voice_states.values() {
+            self.update(guild.id, state);
+        }
+    }
+
+    fn guilds(&self) -> Vec<GuildId> {
+        let mut guilds = self.sessions().keys().map(|(guild_id, _)| *guild_id).collect::<Vec<_>>();
+        guilds.sort();
+        guilds.dedup();
+        guilds
+    }
+
+    // Whole minutes since the session started paying out, moving its start up by that much.
+    fn take_minutes(&self, guild_id: GuildId, user_id: UserId, channel_id: ChannelId) -> u32 {
+        let mut sessions = self.sessions();
+        match sessions.get_mut(&(guild_id, user_id)) {
+            Some(session) if session.channel_id == channel_id => {
+                let minutes = (session.since.elapsed().as_secs() / CREDIT_INTERVAL.as_secs()) as u32;
+                session.since += CREDIT_INTERVAL * minutes;
+                minutes
+            },
+            _ => 0
+        }
+    }
+
+    fn reset(&self, guild_id: GuildId, user_id: UserId) {
+        if let Some(session) = self.sessions().get_mut(&(guild_id, user_id)) {
+            session.since = Instant::now();
+        }
+    }
+}
+
+fn is_bot(state: &VoiceState) -> bool {
+    state.member.as_ref().map(|m| m.user.bot).unwrap_or(false)
+}
+
+// AFK channels, people who can't hear anyone, and people talking to themselves don't count.
+fn eligible(state: &VoiceState, afk_channel: Option<ChannelId>, voice_states: &HashMap<UserId, VoiceState>) -> bool {
+    let channel_id = match state.channel_id {
+        Some(channel_id) => channel_id,
+        None => return false
+    };
+
+    if Some(channel_id) == afk_channel || state.self_deaf || state.deaf {
+        return false;
+    }
+
+    voice_states.values().any(|other| other.user_id != state.user_id && other.channel_id == Some(channel_id) && !is_bot(other))
+}
+
+async fn credit_guild(data: &Arc<RwLock<TypeMap>>, cache_and_http: &Arc<CacheAndHttp>, tracker: &VoiceTracker, guild_id: GuildId) {
+    let (db, settings, cache) = {
+        let data = data.read().await;
+        (
+            data.get::<Database>().expect("Couldn't find database").clone(),
+            data.get::<GuildSettings>().expect("Expected GuildSettings in TypeMap.").clone(),
+            data.get::<DbCache>().expect("Expected DbCache in TypeMap.").clone()
+        )
+    };
+
+    // There's no message to answer, so level-ups that would go to the same channel go to the system channel instead.
+    let guild = cache_and_http.cache.guild_field(guild_id, |g| (g.afk_channel_id, g.system_channel_id, g.voice_states.clone())).await;
+    let (afk_channel, announce_channel, voice_states) = match guild {
+        Some(guild) => guild,
+        None => return
+    };
+
+    let rate = match settings.voice_xp_rate(guild_id).await {
+        Ok(Some(rate)) => rate,
+        Ok(None) => {
+            // Turning it on later shouldn't pay out for all the time it was off.
+            voice_states.keys().for_each(|user_id| tracker.reset(guild_id, *user_id));
+            return;
+        },
+        Err(ex) => {
+            error!("Failed getting the voice xp settings for {}: {}", guild_id, ex);
+            return;
+        }
+    };
+
+    let xp_settings = match settings.xp_settings(guild_id).await {
+        Ok(xp_settings) => xp_settings,
+        Err(ex) => {
+            error!("Failed getting the xp settings for {}: {}", guild_id, ex);
+            return;
+        }
+    };
+
+    let announcement = settings.level_up_settings(guild_id).await.unwrap_or_else(|ex| {
+        error!("Failed getting the level-up settings for {}: {}", guild_id, ex);
+        LevelUpSettings::default()
+    });
+
+    let mode = settings.rank_mode(guild_id).await.unwrap_or_else(|ex| {
+        error!("Failed getting the rank mode for {}: {}", guild_id, ex);
+        RankMode::default()
+    });
+
+    let multipliers = match cache.get_multipliers(guild_id).await {
+        Ok(multipliers) => multipliers,
+        Err(ex) => {
+            error!("Failed getting the xp multipliers for {}: {}", guild_id, ex);
+            Vec::new()
+        }
+    };
+
+    for state in voice_states.values() {
+        let channel_id = match state.channel_id {
+            Some(channel_id) => channel_id,
+            None => continue
+        };
+
+        if !eligible(state, afk_channel, &voice_states) {
+            tracker.reset(guild_id, state.user_id);
+            continue;
+        }
+
+        let minutes = tracker.take_minutes(guild_id, state.user_id, channel_id);
+        if minutes == 0 {
+            continue;
+        }
+
+        let roles = state.member.as_ref().map(|m| m.roles.as_slice()).unwrap_or(&[]);
+        let boost = cache.get_boost_multiplier(guild_id, state.user_id).await.unwrap_or_else(|ex| {
+            error!("Failed getting the xp boost for {} in {}: {}", state.user_id, guild_id, ex);
+            1.0
+        });
+        let multiplier = XpMultiplier::combine(&multipliers, channel_id, roles) * boost;
+        let gain = (rate as f64 * minutes as f64 * multiplier).round() as i32;
+
+        match db.add_exp(guild_id, state.user_id, gain, &xp_settings).await {
+            Ok(data) => {
+                let ranks = rank_update(db.as_ref(), guild_id, &data, mode).await;
+                level_up(&cache_and_http.http, guild_id, state.user_id, &data, announce_channel, &announcement, &ranks).await;
+                rewards::dispatch(&cache_and_http.http, &cache, guild_id, state.user_id, &data).await;
+            },
+            Err(ex) => error!("Failed providing voice exp to user: {}", ex)
+        }
+    }
+}
+
+pub async fn credit_voice(data: Arc<RwLock<TypeMap>>, cache_and_http: Arc<CacheAndHttp>) {
+    let mut interval = tokio::time::interval(CREDIT_INTERVAL);
+    loop {
+        interval.tick().await;
+        let tracker = data.read().await.get::<VoiceTracker>().expect("Expected VoiceTracker in TypeMap.").clone();
+
+        for guild_id in tracker.guilds() {
+            credit_guild(&data, &cache_and_http, &tracker, guild_id).await;
+        }
+    }
+}
+
+pub async fn voice_state_update(ctx: &Context, guild_id: Option<GuildId>, state: &VoiceState) {
+    if let Some(guild_id) = guild_id.or(state.guild_id) {
+        VoiceTracker::from_context(ctx).await.update(guild_id, state);
+    }
+}
@@ -0,0 +1,79 @@
+use std::sync::Arc;
+use chrono::{Datelike, Duration, NaiveDateTime, Utc};
+use log::error;
+use serenity::{
+    CacheAndHttp,
+    model::id::GuildId,
+    prelude::{RwLock, TypeMap}
+};
+use crate::Database;
+use crate::services::guild_settings::GuildSettings;
+
+const WEEKLY_TOP_SIZE: usize = 10;
+
+// Midnight UTC on the coming Monday. Counting from now means a restart never posts the same week twice,
+// though one that happens right at midnight will skip a week.
+fn next_monday(now: NaiveDateTime) -> NaiveDateTime {
+    let days = 7 - now.weekday().num_days_from_monday() as i64;
+    (now.date() + Duration::days(days)).and_hms_opt(0, 0, 0).unwrap_or(now)
+}
+
+async fn post_guild(data: &Arc<RwLock<TypeMap>>, cache_and_http: &Arc<CacheAndHttp>, guild_id: GuildId, since: NaiveDateTime) {
+    let (db, settings) = {
+        let data = data.read().await;
+        (
+            data.get::<Database>().expect("Couldn't find database").clone(),
+            data.get::<GuildSettings>().expect("Expected GuildSettings in TypeMap.").clone()
+        )
+    };
+
+    let channel_id = match settings.weekly_top_channel(guild_id).await {
+        Ok(Some(channel_id)) => channel_id,
+        Ok(None) => return,
+        Err(ex) => {
+            error!("Failed getting the weekly top channel for {}: {}", guild_id, ex);
+            return;
+        }
+    };
+
+    let gains = match db.top_gains(guild_id, since).await {
+        Ok(gains) => gains,
+        Err(ex) => {
+            error!("Failed getting the weekly top for {}: {}", guild_id, ex);
+            return;
+        }
+    };
+
+    // A quiet week isn't worth a post.
+    if gains.is_empty() {
+        return;
+    }
+
+    let content = gains.iter()
+        .take(WEEKLY_TOP_SIZE)
+        .enumerate()
+        .map(|(position, gain)| format!("`#{}` <@{}> - {} xp", position + 1, gain.user, gain.gain))
+        .reduce(|a, b| format!("{}\n{}", a, b))
+        .unwrap_or_default();
+
+    if let Err(ex) = channel_id.send_message(&cache_and_http.http, |m| m.embed(|e| e
+        .title("This Week's Top Users")
+        .description(content)
+        .footer(|f| f.text(format!("Week of {}", since.format("%B %-d, %Y"))))
+    )).await {
+        error!("Failed posting the weekly top for {}: {}", guild_id, ex);
+    }
+}
+
+pub async fn post_weekly_top(data: Arc<RwLock<TypeMap>>, cache_and_http: Arc<CacheAndHttp>) {
+    loop {
+        let now = Utc::now().naive_utc();
+        let next = next_monday(now);
+        tokio::time::sleep((next - now).to_std().unwrap_or_default()).await;
+
+        let since = next - Duration::days(7);
+        for guild_id in cache_and_http.cache.guilds().await {
+            post_guild(&data, &cache_and_http, guild_id, since).await;
+        }
+    }
+}
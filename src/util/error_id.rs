@@ -0,0 +1,6 @@
+use crate::util::random_u64;
+
+// Short enough for someone to type into a bug report, and it shows up in the log line for the same error.
+pub fn error_id() -> String {
+    format!("{:06X}", random_u64() & 0xFF_FFFF)
+}
@@ -1,4 +1,8 @@
 mod duration;
+mod error_id;
+mod random;
 
 pub use duration::to_ms;
-pub use duration::from_ms;
\ No newline at end of file
+pub use duration::from_ms;
+pub use error_id::error_id;
+pub use random::{random_u64, random_between};
@@ -0,0 +1,20 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+// Good enough for XP rolls and error IDs; don't use it for anything that has to be unpredictable.
+pub fn random_u64() -> u64 {
+    let mut hasher = RandomState::new().build_hasher();
+    COUNTER.fetch_add(1, Ordering::Relaxed).hash(&mut hasher);
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or_default().hash(&mut hasher);
+    hasher.finish()
+}
+
+// Both ends are included.
+pub fn random_between(min: i32, max: i32) -> i32 {
+    let range = (max as i64 - min as i64 + 1).max(1) as u64;
+    (min as i64 + (random_u64() % range) as i64) as i32
+}